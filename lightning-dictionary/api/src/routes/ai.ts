@@ -82,6 +82,10 @@ export async function aiRoutes(server: FastifyInstance) {
       feature = AIFeature.ETYMOLOGY;
     } else if (features?.difficultyAssessment) {
       feature = AIFeature.DIFFICULTY_LEVEL;
+    } else if (features?.usageExamples) {
+      feature = AIFeature.USAGE_EXAMPLES;
+    } else if (features?.translationContext) {
+      feature = AIFeature.TRANSLATION_CONTEXT;
     }
 
     // Create AI request
@@ -0,0 +1,86 @@
+#!/usr/bin/env python3
+"""
+SQLite Dictionary Builder for Lightning Dictionary
+Writes the word data the API serves into a SQLite file the desktop app can
+read directly, without the Node server. The app opens it when it's at
+DICTIONARY_DB_PATH, at sources.sqlite_path in settings, or named
+lightning-dictionary.db in the app data directory.
+
+Usage: python3 build_sqlite_dictionary.py [output.db]
+"""
+
+import json
+import os
+import sqlite3
+import sys
+
+# Same order the API tries in services/dictionary.ts
+INPUT_FILES = [
+    "data/processed/dictionary_enhanced.json",
+    "data/processed/dictionary.json",
+]
+# Next to the JSON it comes from, out of version control
+OUTPUT_FILE = "data/processed/lightning-dictionary.db"
+
+# One row per headword and part of speech, shaped like the API's
+# WordDefinition (api/src/types/dictionary.ts). List columns hold JSON arrays.
+SCHEMA = """
+CREATE TABLE words (
+    word TEXT NOT NULL COLLATE NOCASE,
+    rank INTEGER,
+    pos TEXT,
+    frequency INTEGER,
+    definitions TEXT,
+    pronunciation TEXT,
+    examples TEXT
+);
+CREATE INDEX idx_words_word ON words(word);
+CREATE INDEX idx_words_rank ON words(rank);
+"""
+
+
+def build_database(output_file):
+    input_file = next((f for f in INPUT_FILES if os.path.exists(f)), None)
+    if input_file is None:
+        print(f"✗ Error: none of {', '.join(INPUT_FILES)} exists")
+        print("\nRun process_excel_simple.py first.")
+        return False
+
+    print(f"Input file: {input_file}")
+    print(f"Output file: {output_file}")
+
+    with open(input_file, encoding="utf-8") as f:
+        words = json.load(f)["words"]
+
+    # Rebuilt from scratch so removed words don't linger
+    if os.path.exists(output_file):
+        os.remove(output_file)
+
+    connection = sqlite3.connect(output_file)
+    connection.executescript(SCHEMA)
+    connection.executemany(
+        "INSERT INTO words (word, rank, pos, frequency, definitions, pronunciation, examples) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        [
+            (
+                word,
+                entry.get("rank"),
+                entry.get("pos"),
+                entry.get("frequency"),
+                json.dumps(entry.get("definitions", []), ensure_ascii=False),
+                entry.get("pronunciation", ""),
+                json.dumps(entry.get("examples", []), ensure_ascii=False),
+            )
+            for word, entry in words.items()
+        ],
+    )
+    connection.commit()
+    connection.execute("VACUUM")
+    connection.close()
+
+    print(f"✓ Wrote {len(words)} words")
+    return True
+
+
+if __name__ == "__main__":
+    if not build_database(sys.argv[1] if len(sys.argv) > 1 else OUTPUT_FILE):
+        print("\n⚠ Build failed. Please check the errors above.")
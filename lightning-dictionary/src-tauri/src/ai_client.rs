@@ -0,0 +1,468 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use reqwest::Client;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::runtime::Handle;
+use crate::api_client::DictionaryApiClient;
+use crate::error::{DictionaryError, DictionaryResult};
+
+/// AI features exposed by `/api/v1/ai/*`. The serialized names match the
+/// `AIFeature` enum on the API side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiFeature {
+    ContextDefinition,
+    UsageExamples,
+    DifficultyLevel,
+    TranslationContext,
+}
+
+impl AiFeature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AiFeature::ContextDefinition => "context_definition",
+            AiFeature::UsageExamples => "usage_examples",
+            AiFeature::DifficultyLevel => "difficulty_level",
+            AiFeature::TranslationContext => "translation_context",
+        }
+    }
+
+    /// Hard latency budget for a single request. AI enrichment is optional,
+    /// so anything slower than this is dropped rather than delaying the popup.
+    pub fn latency_budget(&self) -> Duration {
+        match self {
+            AiFeature::DifficultyLevel => Duration::from_millis(600),
+            AiFeature::ContextDefinition => Duration::from_millis(800),
+            AiFeature::UsageExamples | AiFeature::TranslationContext => Duration::from_millis(1200),
+        }
+    }
+
+    /// Features to request for a hotkey lookup. Context-dependent features
+    /// are only worth asking for when we actually have a sentence.
+    pub fn defaults_for(sentence: Option<&str>) -> Vec<AiFeature> {
+        match sentence {
+            Some(s) if !s.trim().is_empty() => vec![AiFeature::ContextDefinition, AiFeature::UsageExamples],
+            _ => vec![AiFeature::UsageExamples, AiFeature::DifficultyLevel],
+        }
+    }
+
+    // Feature flags understood by POST /api/v1/ai/enhance
+    fn request_flags(&self) -> serde_json::Value {
+        match self {
+            AiFeature::ContextDefinition => serde_json::json!({}),
+            AiFeature::UsageExamples => serde_json::json!({ "usageExamples": true }),
+            AiFeature::DifficultyLevel => serde_json::json!({ "difficultyAssessment": true }),
+            AiFeature::TranslationContext => serde_json::json!({ "translationContext": true }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiContext {
+    pub word: String,
+    pub sentence: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiEnhancement {
+    pub word: String,
+    pub contextual_meaning: Option<String>,
+    pub confidence: Option<f64>,
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+    #[serde(default)]
+    pub related_concepts: Vec<String>,
+    pub difficulty_level: Option<String>,
+    pub usage_in_context: Option<String>,
+}
+
+impl AiEnhancement {
+    /// Build an enhancement from a raw `AIResponse.result` object, the shape
+    /// returned by batch jobs. Mirrors the mapping done by `/ai/enhance`.
+    pub fn from_ai_result(word: &str, result: &serde_json::Value, confidence: Option<f64>) -> Self {
+        let text = |key: &str| result.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let strings = |key: &str| -> Vec<String> {
+            result.get(key)
+                .and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|i| i.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default()
+        };
+
+        Self {
+            word: word.to_string(),
+            contextual_meaning: text("contextualMeaning").or_else(|| text("summary")),
+            confidence,
+            suggestions: strings("suggestions"),
+            related_concepts: result.get("concepts")
+                .and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|c| c.get("word").and_then(|w| w.as_str()).map(|s| s.to_string())).collect())
+                .unwrap_or_default(),
+            difficulty_level: text("level"),
+            usage_in_context: strings("examples").into_iter().next().or_else(|| text("usageInContext")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnhanceResponse {
+    enhancement: Option<AiEnhancement>,
+    processing_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AiEnrichment {
+    pub word: String,
+    pub feature: AiFeature,
+    pub enhancement: AiEnhancement,
+    pub from_cache: bool,
+    pub lookup_time_ms: u128,
+}
+
+struct AiCacheEntry {
+    enhancement: AiEnhancement,
+    inserted_at: Instant,
+}
+
+/// Cache for AI responses. Kept apart from `DictionaryCache` because entries
+/// depend on the surrounding sentence and go stale much faster.
+pub struct AiCache {
+    entries: HashMap<String, AiCacheEntry>,
+    insertion_order: VecDeque<String>,
+    max_size: usize,
+    ttl: Duration,
+}
+
+impl AiCache {
+    pub fn new(max_size: usize, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::with_capacity(max_size),
+            insertion_order: VecDeque::with_capacity(max_size),
+            max_size,
+            ttl,
+        }
+    }
+
+    pub fn key(context: &AiContext, feature: AiFeature) -> String {
+        let sentence = context.sentence.as_deref().unwrap_or("").trim().to_lowercase();
+        format!("{}|{}|{}", feature.as_str(), context.word.trim().to_lowercase(), sentence)
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<AiEnhancement> {
+        let expired = match self.entries.get(key) {
+            Some(entry) => entry.inserted_at.elapsed() > self.ttl,
+            None => return None,
+        };
+
+        if expired {
+            self.entries.remove(key);
+            if let Some(pos) = self.insertion_order.iter().position(|k| k == key) {
+                self.insertion_order.remove(pos);
+            }
+            return None;
+        }
+
+        self.entries.get(key).map(|entry| entry.enhancement.clone())
+    }
+
+    pub fn insert(&mut self, key: String, enhancement: AiEnhancement) {
+        if self.entries.len() >= self.max_size && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        if let Some(pos) = self.insertion_order.iter().position(|k| k == &key) {
+            self.insertion_order.remove(pos);
+        }
+        self.insertion_order.push_back(key.clone());
+
+        self.entries.insert(key, AiCacheEntry {
+            enhancement,
+            inserted_at: Instant::now(),
+        });
+    }
+}
+
+pub type ThreadSafeAiCache = Arc<Mutex<AiCache>>;
+
+pub fn create_ai_cache(max_size: usize) -> ThreadSafeAiCache {
+    Arc::new(Mutex::new(AiCache::new(max_size, Duration::from_secs(60 * 60))))
+}
+
+pub struct AiClient {
+    client: Client,
+    // Shared with lookups, so AI requests fail over across the same servers
+    api: Arc<DictionaryApiClient>,
+    cache: ThreadSafeAiCache,
+    runtime_handle: Handle,
+}
+
+impl AiClient {
+    pub fn new(api: Arc<DictionaryApiClient>, cache: ThreadSafeAiCache) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(1500)) // Upper bound; per-feature budgets are stricter
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api,
+            cache,
+            runtime_handle: Handle::current(),
+        }
+    }
+
+    /// Fetch a single AI enhancement, serving from the AI cache when possible.
+    /// Returns the enhancement and whether it came from the cache.
+    pub async fn enhance(&self, context: &AiContext, feature: AiFeature) -> DictionaryResult<(AiEnhancement, bool)> {
+        if context.word.trim().is_empty() {
+            return Err(DictionaryError::InvalidInput {
+                message: "Word cannot be empty".to_string(),
+            });
+        }
+
+        let key = AiCache::key(context, feature);
+        if let Ok(mut cache) = self.cache.lock() {
+            if let Some(enhancement) = cache.get(&key) {
+                return Ok((enhancement, true));
+            }
+        }
+
+        // No retries here: a second attempt would blow the budget anyway
+        let enhancement = match tokio::time::timeout(feature.latency_budget(), self.request(context, feature)).await {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err(DictionaryError::NetworkError {
+                    message: format!("AI {} exceeded {}ms budget", feature.as_str(), feature.latency_budget().as_millis()),
+                    is_timeout: true,
+                });
+            }
+        };
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(key, enhancement.clone());
+        }
+
+        Ok((enhancement, false))
+    }
+
+    /// Blocking variant for sync Tauri commands, mirroring `DictionaryService`.
+    pub fn enhance_blocking(&self, context: &AiContext, feature: AiFeature) -> DictionaryResult<(AiEnhancement, bool)> {
+        self.runtime_handle.block_on(self.enhance(context, feature))
+    }
+
+    /// Request AI enrichment in the background and emit each feature as a
+    /// `word-enrichment` event as soon as it arrives. The base definition has
+    /// already been emitted by the caller, so failures are only logged.
+    pub fn spawn_enrichment<R: Runtime>(self: &Arc<Self>, app: AppHandle<R>, context: AiContext, features: Vec<AiFeature>) {
+        for feature in features {
+            let client = self.clone();
+            let app = app.clone();
+            let context = context.clone();
+
+            self.runtime_handle.spawn(async move {
+                let start = Instant::now();
+                match client.enhance(&context, feature).await {
+                    Ok((enhancement, from_cache)) => {
+                        let _ = app.emit("word-enrichment", AiEnrichment {
+                            word: context.word.clone(),
+                            feature,
+                            enhancement,
+                            from_cache,
+                            lookup_time_ms: start.elapsed().as_millis(),
+                        });
+                    }
+                    Err(e) => {
+                        println!("AI enrichment '{}' skipped for '{}': {}", feature.as_str(), context.word, e);
+                    }
+                }
+            });
+        }
+    }
+
+    async fn request(&self, context: &AiContext, feature: AiFeature) -> DictionaryResult<AiEnhancement> {
+        self.api.with_failover("AI", |base_url| self.request_endpoint(base_url, context, feature)).await
+    }
+
+    async fn request_endpoint(&self, base_url: String, context: &AiContext, feature: AiFeature) -> DictionaryResult<AiEnhancement> {
+        let url = format!("{}/api/v1/ai/enhance", base_url);
+        let body = serde_json::json!({
+            "word": context.word,
+            "context": {
+                "surroundingSentence": context.sentence,
+            },
+            "features": feature.request_flags(),
+        });
+
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(DictionaryError::from)?;
+
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(DictionaryError::ServiceUnavailable {
+                service: "AI".to_string(),
+                retry_after: None,
+            });
+        }
+
+        let parsed = response.json::<EnhanceResponse>()
+            .await
+            .map_err(|e| DictionaryError::ApiError {
+                status_code: Some(status.as_u16()),
+                message: format!("Failed to parse AI response: {}", e),
+            })?;
+
+        if let Some(ms) = parsed.processing_time {
+            println!("AI {} for '{}' processed in {}ms", feature.as_str(), context.word, ms);
+        }
+
+        parsed.enhancement.ok_or_else(|| DictionaryError::ApiError {
+            status_code: Some(status.as_u16()),
+            message: "AI response contained no enhancement".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::ApiEndpointConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const ENHANCE_BODY: &str = r#"{"enhancement":{"word":"bank","contextualMeaning":"the side of a river","confidence":0.9,"usageInContext":"They sat on the bank."},"processingTime":3}"#;
+
+    /// Minimal HTTP server answering every request with `body` after
+    /// `delay_ms`. Returns its base URL and the JSON bodies it received.
+    async fn spawn_mock_server(body: &'static str, delay_ms: u64) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else { break };
+                let received = received.clone();
+                tokio::spawn(async move {
+                    // Read the headers, then as much body as they announce
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let Ok(n) = socket.read(&mut buf).await else { return };
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request);
+                        if let Some(split) = text.find("\r\n\r\n") {
+                            let length = text[..split].lines()
+                                .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                                .unwrap_or(0);
+                            if request.len() >= split + 4 + length || n == 0 {
+                                let body = serde_json::from_slice(&request[split + 4..]).unwrap_or(serde_json::Value::Null);
+                                received.lock().unwrap().push(body);
+                                break;
+                            }
+                        }
+                        if n == 0 {
+                            return;
+                        }
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        (format!("http://{}", addr), requests)
+    }
+
+    fn context(sentence: Option<&str>) -> AiContext {
+        AiContext {
+            word: "bank".to_string(),
+            sentence: sentence.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sends_sentence_and_feature_flags() {
+        let (url, requests) = spawn_mock_server(ENHANCE_BODY, 0).await;
+        let client = AiClient::new(Arc::new(DictionaryApiClient::new(url)), create_ai_cache(10));
+
+        let sentence = Some("We sat on the bank of the river.");
+        let (enhancement, from_cache) = client.enhance(&context(sentence), AiFeature::UsageExamples).await.unwrap();
+        assert!(!from_cache);
+        assert_eq!(enhancement.contextual_meaning.as_deref(), Some("the side of a river"));
+        client.enhance(&context(sentence), AiFeature::TranslationContext).await.unwrap();
+        client.enhance(&context(None), AiFeature::DifficultyLevel).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0]["word"], "bank");
+        assert_eq!(requests[0]["context"]["surroundingSentence"], "We sat on the bank of the river.");
+        // The API reads selectedText as the paragraph, which we don't have
+        assert!(requests[0]["context"].get("selectedText").is_none());
+        assert_eq!(requests[0]["features"], serde_json::json!({ "usageExamples": true }));
+        assert_eq!(requests[1]["features"], serde_json::json!({ "translationContext": true }));
+        assert_eq!(requests[2]["features"], serde_json::json!({ "difficultyAssessment": true }));
+        assert!(requests[2]["context"]["surroundingSentence"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_cached_per_feature_and_sentence() {
+        let (url, requests) = spawn_mock_server(ENHANCE_BODY, 0).await;
+        let client = AiClient::new(Arc::new(DictionaryApiClient::new(url)), create_ai_cache(10));
+
+        let river = Some("We sat on the bank of the river.");
+        client.enhance(&context(river), AiFeature::ContextDefinition).await.unwrap();
+        let (_, from_cache) = client.enhance(&context(river), AiFeature::ContextDefinition).await.unwrap();
+        assert!(from_cache);
+
+        // Another feature or another sentence is a different answer
+        let (_, from_cache) = client.enhance(&context(river), AiFeature::UsageExamples).await.unwrap();
+        assert!(!from_cache);
+        let (_, from_cache) = client.enhance(&context(Some("The bank closed early.")), AiFeature::ContextDefinition).await.unwrap();
+        assert!(!from_cache);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_latency_budget() {
+        let (url, _) = spawn_mock_server(ENHANCE_BODY, 800).await;
+        let client = AiClient::new(Arc::new(DictionaryApiClient::new(url)), create_ai_cache(10));
+
+        let err = client.enhance(&context(None), AiFeature::DifficultyLevel).await.unwrap_err();
+        assert!(matches!(err, DictionaryError::NetworkError { is_timeout: true, .. }));
+        assert!(client.cache.lock().unwrap().get(&AiCache::key(&context(None), AiFeature::DifficultyLevel)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_next_endpoint() {
+        // Bound and dropped, so connecting is refused
+        let dead = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let (url, requests) = spawn_mock_server(ENHANCE_BODY, 0).await;
+        let api = Arc::new(DictionaryApiClient::with_endpoints(vec![
+            ApiEndpointConfig::new(dead),
+            ApiEndpointConfig { priority: 1, ..ApiEndpointConfig::new(url) },
+        ]));
+        let client = AiClient::new(api.clone(), create_ai_cache(10));
+
+        client.enhance(&context(None), AiFeature::UsageExamples).await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        assert_eq!(api.endpoint_stats()[0].failures, 1);
+    }
+}
@@ -0,0 +1,729 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use reqwest::Client;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::runtime::Handle;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+use crate::ai_client::{AiCache, AiContext, AiEnhancement, AiFeature, ThreadSafeAiCache};
+use crate::api_client::DictionaryApiClient;
+use crate::error::{DictionaryError, DictionaryResult};
+
+const MIN_POLL_INTERVAL_MS: u64 = 1_000;
+const MAX_POLL_INTERVAL_MS: u64 = 60_000;
+const FINISHED_JOB_RETENTION_SECS: u64 = 7 * 24 * 60 * 60;
+// The server rejects batches with more words than this
+const MAX_BATCH_WORDS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiJobStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl AiJobStatus {
+    fn parse(status: &str) -> Self {
+        match status {
+            "processing" => AiJobStatus::Processing,
+            "completed" => AiJobStatus::Completed,
+            "failed" => AiJobStatus::Failed,
+            "cancelled" => AiJobStatus::Cancelled,
+            _ => AiJobStatus::Pending,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, AiJobStatus::Completed | AiJobStatus::Failed | AiJobStatus::Cancelled)
+    }
+}
+
+/// A batch job we submitted, persisted so polling resumes after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedAiJob {
+    pub job_id: String,
+    /// Base URL of the server holding the job; jobs don't move between servers
+    #[serde(default)]
+    pub endpoint: String,
+    pub words: Vec<String>,
+    pub features: Vec<AiFeature>,
+    pub status: AiJobStatus,
+    pub progress: u32,
+    pub completed_items: usize,
+    pub total_items: usize,
+    pub submitted_at: u64,
+    pub finished_at: Option<u64>,
+    #[serde(skip)]
+    poll_interval_ms: u64,
+    #[serde(skip)]
+    next_poll_at: Option<Instant>,
+}
+
+impl TrackedAiJob {
+    /// Batch requests are expanded word-major on the server, so result index
+    /// `i` belongs to `words[i / features.len()]` and `features[i % features.len()]`.
+    fn request_at(&self, index: usize) -> Option<(&str, AiFeature)> {
+        if self.features.is_empty() {
+            return None;
+        }
+        let word = self.words.get(index / self.features.len())?;
+        Some((word.as_str(), self.features[index % self.features.len()]))
+    }
+
+    fn schedule_next_poll(&mut self, made_progress: bool) {
+        self.poll_interval_ms = if made_progress || self.poll_interval_ms == 0 {
+            MIN_POLL_INTERVAL_MS
+        } else {
+            (self.poll_interval_ms * 2).min(MAX_POLL_INTERVAL_MS)
+        };
+        self.next_poll_at = Some(Instant::now() + Duration::from_millis(self.poll_interval_ms));
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AiJobProgress {
+    pub job_id: String,
+    pub status: AiJobStatus,
+    pub progress: u32,
+    pub completed_items: usize,
+    pub total_items: usize,
+    pub cached_results: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmitResponse {
+    job_id: Option<String>,
+    total_items: Option<usize>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobStatusResponse {
+    status: String,
+    #[serde(default)]
+    progress: u32,
+    #[serde(default)]
+    total_items: usize,
+    #[serde(default)]
+    completed_items: usize,
+    result: Option<BatchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    results: HashMap<String, BatchItemResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchItemResponse {
+    result: serde_json::Value,
+    confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiJobStats {
+    pub queue: serde_json::Value,
+    pub jobs: serde_json::Value,
+}
+
+struct JobStore {
+    jobs: Vec<TrackedAiJob>,
+    path: Option<PathBuf>,
+}
+
+impl JobStore {
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        match serde_json::to_string_pretty(&self.jobs) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(path, contents) {
+                    eprintln!("Failed to persist AI jobs: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize AI jobs: {}", e),
+        }
+    }
+}
+
+/// Submits batch AI enrichment jobs (`/api/v1/ai/batch`) and polls them to
+/// completion on the tokio runtime, feeding finished results into the AI cache.
+pub struct AiJobManager {
+    client: Client,
+    api: Arc<DictionaryApiClient>,
+    cache: ThreadSafeAiCache,
+    store: Arc<RwLock<JobStore>>,
+    runtime_handle: Handle,
+}
+
+impl AiJobManager {
+    pub fn new(api: Arc<DictionaryApiClient>, cache: ThreadSafeAiCache) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api,
+            cache,
+            store: Arc::new(RwLock::new(JobStore {
+                jobs: Vec::new(),
+                path: None,
+            })),
+            runtime_handle: Handle::current(),
+        }
+    }
+
+    /// Load persisted jobs from the app data dir and start the polling loop.
+    pub fn start<R: Runtime>(self: Arc<Self>, app: AppHandle<R>) {
+        let path = app.path().app_data_dir().ok().map(|dir| {
+            let _ = fs::create_dir_all(&dir);
+            dir.join("ai_jobs.json")
+        });
+
+        let manager = self.clone();
+        self.runtime_handle.spawn(async move {
+            manager.load(path).await;
+            manager.poll_loop(app).await;
+        });
+    }
+
+    async fn load(&self, path: Option<PathBuf>) {
+        let mut jobs: Vec<TrackedAiJob> = path.as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        // Drop finished jobs nobody has looked at for a week
+        let now = unix_now();
+        jobs.retain(|job| match job.finished_at {
+            Some(finished) => now.saturating_sub(finished) < FINISHED_JOB_RETENTION_SECS,
+            None => true,
+        });
+
+        let active = jobs.iter().filter(|j| !j.status.is_finished()).count();
+        if active > 0 {
+            println!("Resuming {} AI batch job(s)", active);
+        }
+
+        let mut store = self.store.write().await;
+        // Keep anything submitted while we were still loading
+        jobs.append(&mut store.jobs);
+        store.jobs = jobs;
+        store.path = path;
+        store.persist();
+    }
+
+    /// Submit `words` as one job per `MAX_BATCH_WORDS`. If a later job is
+    /// refused, the ones before it stay submitted and tracked.
+    pub async fn submit(&self, words: Vec<String>, mut features: Vec<AiFeature>, priority: &str) -> DictionaryResult<Vec<TrackedAiJob>> {
+        let words: Vec<String> = words.into_iter()
+            .map(|w| w.trim().to_string())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        if words.is_empty() || features.is_empty() {
+            return Err(DictionaryError::InvalidInput {
+                message: "A batch needs at least one word and one feature".to_string(),
+            });
+        }
+
+        // The server expands requests in object key order, and serde_json
+        // serializes maps sorted, so keep our list in that same order
+        features.sort_by_key(|f| f.as_str());
+        features.dedup();
+
+        let mut jobs = Vec::new();
+        for chunk in words.chunks(MAX_BATCH_WORDS) {
+            jobs.push(self.submit_batch(chunk.to_vec(), features.clone(), priority).await?);
+        }
+        Ok(jobs)
+    }
+
+    async fn submit_batch(&self, words: Vec<String>, features: Vec<AiFeature>, priority: &str) -> DictionaryResult<TrackedAiJob> {
+        let mut feature_flags = serde_json::Map::new();
+        for feature in &features {
+            feature_flags.insert(feature.as_str().to_string(), serde_json::Value::Bool(true));
+        }
+
+        let body = serde_json::json!({
+            "words": words,
+            "features": feature_flags,
+            "options": {
+                "priority": priority,
+                "notifyProgress": true,
+            },
+        });
+
+        let (endpoint, status, parsed) = self.api.with_failover("AI batch", |base_url| {
+            let request = self.client.post(format!("{}/api/v1/ai/batch", base_url)).json(&body);
+            async move {
+                let response = request.send().await.map_err(DictionaryError::from)?;
+                let status = response.status();
+                let parsed = response.json::<SubmitResponse>()
+                    .await
+                    .map_err(|e| DictionaryError::ApiError {
+                        status_code: Some(status.as_u16()),
+                        message: format!("Failed to parse batch response: {}", e),
+                    })?;
+                Ok((base_url, status, parsed))
+            }
+        }).await?;
+
+        let job_id = parsed.job_id.ok_or_else(|| DictionaryError::ApiError {
+            status_code: Some(status.as_u16()),
+            message: parsed.error.unwrap_or_else(|| "Batch submission rejected".to_string()),
+        })?;
+
+        let mut job = TrackedAiJob {
+            job_id,
+            endpoint,
+            total_items: parsed.total_items.unwrap_or(words.len() * features.len()),
+            words,
+            features,
+            status: AiJobStatus::Pending,
+            progress: 0,
+            completed_items: 0,
+            submitted_at: unix_now(),
+            finished_at: None,
+            poll_interval_ms: 0,
+            next_poll_at: None,
+        };
+        job.schedule_next_poll(true);
+
+        let mut store = self.store.write().await;
+        store.jobs.push(job.clone());
+        store.persist();
+
+        Ok(job)
+    }
+
+    pub async fn cancel(&self, job_id: &str) -> DictionaryResult<()> {
+        let endpoint = {
+            let store = self.store.read().await;
+            store.jobs.iter().find(|j| j.job_id == job_id).map(|job| self.job_endpoint(job))
+        };
+        let endpoint = endpoint.ok_or_else(|| DictionaryError::InvalidInput {
+            message: format!("Unknown AI job {}", job_id),
+        })?;
+
+        let response = self.client
+            .delete(format!("{}/api/v1/ai/batch/{}", endpoint, urlencoding::encode(job_id)))
+            .send()
+            .await
+            .map_err(DictionaryError::from)?;
+
+        if !response.status().is_success() {
+            return Err(DictionaryError::ApiError {
+                status_code: Some(response.status().as_u16()),
+                message: format!("Failed to cancel job {}", job_id),
+            });
+        }
+
+        let mut store = self.store.write().await;
+        if let Some(job) = store.jobs.iter_mut().find(|j| j.job_id == job_id) {
+            job.status = AiJobStatus::Cancelled;
+            job.finished_at = Some(unix_now());
+        }
+        store.persist();
+
+        Ok(())
+    }
+
+    pub async fn jobs(&self) -> Vec<TrackedAiJob> {
+        self.store.read().await.jobs.clone()
+    }
+
+    pub async fn server_stats(&self) -> DictionaryResult<AiJobStats> {
+        self.api.with_failover("AI batch", |base_url| {
+            let request = self.client.get(format!("{}/api/v1/ai/batch/stats", base_url));
+            async move {
+                let response = request.send().await.map_err(DictionaryError::from)?;
+                let status = response.status();
+                response.json::<AiJobStats>()
+                    .await
+                    .map_err(|e| DictionaryError::ApiError {
+                        status_code: Some(status.as_u16()),
+                        message: format!("Failed to parse batch stats: {}", e),
+                    })
+            }
+        }).await
+    }
+
+    /// Where `job` lives. Jobs persisted before endpoints were recorded went
+    /// to what is still the first configured endpoint.
+    fn job_endpoint(&self, job: &TrackedAiJob) -> String {
+        if !job.endpoint.is_empty() {
+            return job.endpoint.clone();
+        }
+        self.api.endpoint_urls().into_iter().next().unwrap_or_default()
+    }
+
+    async fn poll_loop<R: Runtime>(&self, app: AppHandle<R>) {
+        loop {
+            let due: Vec<TrackedAiJob> = {
+                let store = self.store.read().await;
+                let now = Instant::now();
+                store.jobs.iter()
+                    .filter(|j| !j.status.is_finished())
+                    .filter(|j| match j.next_poll_at {
+                        Some(at) => at <= now,
+                        None => true,
+                    })
+                    .cloned()
+                    .collect()
+            };
+
+            for job in due {
+                let (updated, cached_results) = self.poll_job(job).await;
+
+                let progress = AiJobProgress {
+                    job_id: updated.job_id.clone(),
+                    status: updated.status.clone(),
+                    progress: updated.progress,
+                    completed_items: updated.completed_items,
+                    total_items: updated.total_items,
+                    cached_results,
+                };
+
+                let changed = {
+                    let mut store = self.store.write().await;
+                    let changed = match store.jobs.iter_mut().find(|j| j.job_id == updated.job_id) {
+                        Some(existing) => {
+                            let changed = existing.status != updated.status || existing.progress != updated.progress;
+                            *existing = updated;
+                            changed
+                        }
+                        None => false,
+                    };
+                    if changed {
+                        store.persist();
+                    }
+                    changed
+                };
+
+                if changed {
+                    let _ = app.emit_to("main", "ai-job-progress", progress);
+                }
+            }
+
+            sleep(Duration::from_millis(MIN_POLL_INTERVAL_MS / 2)).await;
+        }
+    }
+
+    /// Poll one job, returning its updated state and how many results were cached.
+    async fn poll_job(&self, mut job: TrackedAiJob) -> (TrackedAiJob, usize) {
+        let url = format!("{}/api/v1/ai/batch/{}", self.job_endpoint(&job), urlencoding::encode(&job.job_id));
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                DictionaryError::from(e).log_error();
+                job.schedule_next_poll(false);
+                return (job, 0);
+            }
+        };
+
+        if response.status().as_u16() == 404 {
+            // Server restarted and lost the job; nothing left to poll
+            job.status = AiJobStatus::Failed;
+            job.finished_at = Some(unix_now());
+            return (job, 0);
+        }
+
+        let status = match response.json::<JobStatusResponse>().await {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("Failed to parse status for AI job {}: {}", job.job_id, e);
+                job.schedule_next_poll(false);
+                return (job, 0);
+            }
+        };
+
+        let made_progress = status.completed_items != job.completed_items;
+        job.status = AiJobStatus::parse(&status.status);
+        job.progress = status.progress;
+        job.completed_items = status.completed_items;
+        if status.total_items > 0 {
+            job.total_items = status.total_items;
+        }
+
+        let mut cached_results = 0;
+        if job.status.is_finished() {
+            job.finished_at = Some(unix_now());
+            if let Some(result) = status.result {
+                cached_results = self.cache_results(&job, result);
+            }
+        } else {
+            job.schedule_next_poll(made_progress);
+        }
+
+        (job, cached_results)
+    }
+
+    fn cache_results(&self, job: &TrackedAiJob, result: BatchResult) -> usize {
+        let mut cache = match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(e) => {
+                eprintln!("Warning: Failed to cache AI batch results: {}", e);
+                return 0;
+            }
+        };
+
+        let mut cached = 0;
+        for (index, item) in result.results {
+            let Some((word, feature)) = index.parse::<usize>().ok().and_then(|i| job.request_at(i)) else {
+                continue;
+            };
+            let context = AiContext {
+                word: word.to_string(),
+                sentence: None,
+            };
+            let enhancement = AiEnhancement::from_ai_result(word, &item.result, item.confidence);
+            cache.insert(AiCache::key(&context, feature), enhancement);
+            cached += 1;
+        }
+
+        cached
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Tauri command to submit batch enrichment jobs, e.g. for a reading list
+#[tauri::command]
+pub async fn submit_ai_batch(
+    words: Vec<String>,
+    features: Vec<AiFeature>,
+    priority: Option<String>,
+    job_manager: State<'_, Arc<AiJobManager>>,
+) -> Result<Vec<TrackedAiJob>, String> {
+    let priority = match priority.as_deref() {
+        Some("high") => "high",
+        Some("low") => "low",
+        _ => "normal",
+    };
+
+    job_manager.submit(words, features, priority)
+        .await
+        .map_err(|e| e.user_message())
+}
+
+/// Tauri command to list tracked batch jobs
+#[tauri::command]
+pub async fn get_ai_jobs(
+    job_manager: State<'_, Arc<AiJobManager>>,
+) -> Result<Vec<TrackedAiJob>, String> {
+    Ok(job_manager.jobs().await)
+}
+
+/// Tauri command to cancel a pending or running batch job
+#[tauri::command]
+pub async fn cancel_ai_job(
+    job_id: String,
+    job_manager: State<'_, Arc<AiJobManager>>,
+) -> Result<(), String> {
+    job_manager.cancel(&job_id)
+        .await
+        .map_err(|e| e.user_message())
+}
+
+/// Tauri command to fetch server-side batch queue statistics
+#[tauri::command]
+pub async fn get_ai_job_stats(
+    job_manager: State<'_, Arc<AiJobManager>>,
+) -> Result<AiJobStats, String> {
+    job_manager.server_stats()
+        .await
+        .map_err(|e| e.user_message())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_client::create_ai_cache;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn job(words: &[&str], features: Vec<AiFeature>) -> TrackedAiJob {
+        TrackedAiJob {
+            job_id: "job-1".to_string(),
+            endpoint: String::new(),
+            words: words.iter().map(|w| w.to_string()).collect(),
+            total_items: words.len() * features.len(),
+            features,
+            status: AiJobStatus::Pending,
+            progress: 0,
+            completed_items: 0,
+            submitted_at: unix_now(),
+            finished_at: None,
+            poll_interval_ms: 0,
+            next_poll_at: None,
+        }
+    }
+
+    /// Batch endpoint stand-in handing out numbered job ids. Returns its
+    /// base URL and how many words each submission carried.
+    async fn spawn_batch_server() -> (String, Arc<std::sync::Mutex<Vec<usize>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = batches.clone();
+        let next_id = Arc::new(AtomicUsize::new(1));
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let received = received.clone();
+                let id = next_id.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    // Read the headers, then as much body as they announce
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    let body = loop {
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request);
+                        let Some(split) = text.find("\r\n\r\n") else {
+                            if n == 0 { return } else { continue }
+                        };
+                        let length = text[..split].lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if request.len() >= split + 4 + length || n == 0 {
+                            break serde_json::from_slice::<serde_json::Value>(&request[split + 4..]).unwrap_or_default();
+                        }
+                    };
+
+                    let words = body["words"].as_array().map(|w| w.len()).unwrap_or(0);
+                    received.lock().unwrap().push(words);
+                    let response_body = format!(r#"{{"jobId":"job-{}","totalItems":{}}}"#, id, words);
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response_body.len(), response_body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        (format!("http://{}", addr), batches)
+    }
+
+    #[test]
+    fn test_result_index_mapping() {
+        let job = job(&["cat", "dog"], vec![AiFeature::ContextDefinition, AiFeature::UsageExamples]);
+        assert_eq!(job.request_at(0), Some(("cat", AiFeature::ContextDefinition)));
+        assert_eq!(job.request_at(1), Some(("cat", AiFeature::UsageExamples)));
+        assert_eq!(job.request_at(2), Some(("dog", AiFeature::ContextDefinition)));
+        assert_eq!(job.request_at(3), Some(("dog", AiFeature::UsageExamples)));
+        assert_eq!(job.request_at(4), None);
+    }
+
+    #[test]
+    fn test_poll_backoff() {
+        let mut job = job(&["cat"], vec![AiFeature::DifficultyLevel]);
+        job.schedule_next_poll(false);
+        assert_eq!(job.poll_interval_ms, MIN_POLL_INTERVAL_MS);
+
+        // Doubles while nothing moves, up to the cap
+        let mut intervals = Vec::new();
+        for _ in 0..8 {
+            job.schedule_next_poll(false);
+            intervals.push(job.poll_interval_ms);
+        }
+        assert_eq!(&intervals[..3], &[2_000, 4_000, 8_000]);
+        assert_eq!(*intervals.last().unwrap(), MAX_POLL_INTERVAL_MS);
+
+        // Progress resets it
+        job.schedule_next_poll(true);
+        assert_eq!(job.poll_interval_ms, MIN_POLL_INTERVAL_MS);
+        assert!(job.next_poll_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_results_cached_under_word_and_feature() {
+        let cache = create_ai_cache(10);
+        let manager = AiJobManager::new(Arc::new(DictionaryApiClient::new("http://localhost:0".to_string())), cache.clone());
+        let job = job(&["cat", "dog"], vec![AiFeature::ContextDefinition, AiFeature::DifficultyLevel]);
+
+        let result: BatchResult = serde_json::from_value(serde_json::json!({
+            "results": {
+                "1": { "result": { "level": "beginner" }, "confidence": 0.8 },
+                "2": { "result": { "summary": "a loyal animal" } },
+                "9": { "result": {} }
+            }
+        })).unwrap();
+        assert_eq!(manager.cache_results(&job, result), 2);
+
+        let key = |word: &str, feature| AiCache::key(&AiContext { word: word.to_string(), sentence: None }, feature);
+        let mut cache = cache.lock().unwrap();
+        assert_eq!(cache.get(&key("cat", AiFeature::DifficultyLevel)).unwrap().difficulty_level.as_deref(), Some("beginner"));
+        assert_eq!(cache.get(&key("dog", AiFeature::ContextDefinition)).unwrap().contextual_meaning.as_deref(), Some("a loyal animal"));
+        assert!(cache.get(&key("cat", AiFeature::ContextDefinition)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_persisted_jobs() {
+        let path = std::env::temp_dir().join(format!("ai-jobs-{}.json", std::process::id()));
+        let mut active = job(&["cat"], vec![AiFeature::UsageExamples]);
+        active.job_id = "active".to_string();
+        let mut recent = job(&["dog"], vec![AiFeature::UsageExamples]);
+        recent.job_id = "recent".to_string();
+        recent.status = AiJobStatus::Completed;
+        recent.finished_at = Some(unix_now() - 60);
+        let mut stale = job(&["eel"], vec![AiFeature::UsageExamples]);
+        stale.job_id = "stale".to_string();
+        stale.status = AiJobStatus::Completed;
+        stale.finished_at = Some(unix_now() - FINISHED_JOB_RETENTION_SECS - 60);
+
+        // Jobs saved before endpoints were recorded have no "endpoint" field
+        let mut saved = serde_json::to_value(vec![active, recent, stale]).unwrap();
+        saved[0].as_object_mut().unwrap().remove("endpoint");
+        fs::write(&path, saved.to_string()).unwrap();
+
+        let manager = AiJobManager::new(Arc::new(DictionaryApiClient::new("http://localhost:3001".to_string())), create_ai_cache(10));
+        manager.load(Some(path.clone())).await;
+
+        let jobs = manager.jobs().await;
+        let ids: Vec<&str> = jobs.iter().map(|j| j.job_id.as_str()).collect();
+        assert_eq!(ids, vec!["active", "recent"]);
+        assert_eq!(manager.job_endpoint(&jobs[0]), "http://localhost:3001");
+
+        // The pruned list is written back
+        let persisted: Vec<TrackedAiJob> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(persisted.len(), 2);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_large_batches_are_split() {
+        let (url, batches) = spawn_batch_server().await;
+        let manager = AiJobManager::new(Arc::new(DictionaryApiClient::new(url.clone())), create_ai_cache(10));
+
+        let words: Vec<String> = (0..250).map(|i| format!("word{}", i)).collect();
+        let jobs = manager.submit(words, vec![AiFeature::UsageExamples], "normal").await.unwrap();
+
+        assert_eq!(*batches.lock().unwrap(), vec![100, 100, 50]);
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[2].words[0], "word200");
+        assert!(jobs.iter().all(|job| job.endpoint == url));
+        assert_eq!(manager.jobs().await.len(), 3);
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::dictionary::DictionaryService;
+
+pub const DEFAULT_MIN_LENGTH: usize = 3;
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Letters of `word` in sorted order, lowercased, ignoring spaces and
+/// punctuation, so "Listen" and "silent" share `eilnst` and "dirty room"
+/// matches "dormitory"
+pub fn signature(word: &str) -> String {
+    let mut letters: Vec<char> = word.chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    letters.sort_unstable();
+    letters.into_iter().collect()
+}
+
+/// How many of each letter are left to spend while building words
+#[derive(Debug, Clone)]
+pub struct LetterBag {
+    counts: Vec<(char, usize)>,
+}
+
+impl LetterBag {
+    pub fn new(letters: &str) -> Self {
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for c in signature(letters).chars() {
+            match counts.last_mut() {
+                Some((last, count)) if *last == c => *count += 1,
+                _ => counts.push((c, 1)),
+            }
+        }
+        Self { counts }
+    }
+
+    /// Letters left in the bag
+    pub fn remaining(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Take one `c` out of the bag, if there is one
+    pub fn take(&mut self, c: char) -> bool {
+        match self.counts.iter_mut().find(|(letter, count)| *letter == c && *count > 0) {
+            Some((_, count)) => {
+                *count -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn put_back(&mut self, c: char) {
+        if let Some((_, count)) = self.counts.iter_mut().find(|(letter, _)| *letter == c) {
+            *count += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Anagrams {
+    /// Words using exactly the given letters
+    pub anagrams: Vec<String>,
+    /// Shorter words that can be built from some of the letters
+    pub sub_anagrams: Vec<String>,
+}
+
+/// Tauri command for anagrams and words buildable from a set of letters,
+/// most frequent first
+#[tauri::command]
+pub fn find_anagrams(
+    letters: String,
+    min_length: Option<usize>,
+    limit: Option<usize>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Anagrams {
+    dictionary_service.anagrams(&letters, min_length.unwrap_or(DEFAULT_MIN_LENGTH), limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_and_bag() {
+        assert_eq!(signature("Listen"), "eilnst");
+        assert_eq!(signature("dirty room"), signature("dormitory"));
+        assert_eq!(signature("o'clock"), "cckloo");
+
+        let mut bag = LetterBag::new("Aab");
+        assert_eq!(bag.remaining(), 3);
+        assert!(bag.take('a') && bag.take('a'));
+        assert!(!bag.take('a'));
+        assert!(!bag.take('z'));
+        bag.put_back('a');
+        assert!(bag.take('a'));
+    }
+}
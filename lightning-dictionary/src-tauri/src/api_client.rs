@@ -1,7 +1,10 @@
-use std::time::Duration;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use crate::error::{DictionaryError, DictionaryResult};
+use crate::language::ENGLISH;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WordDefinition {
@@ -29,14 +32,131 @@ pub struct SearchResult {
     pub frequency: u64,
 }
 
+// An endpoint is skipped for this long after repeated failures
+const ENDPOINT_COOLDOWN: Duration = Duration::from_secs(10);
+const FAILURES_BEFORE_COOLDOWN: u32 = 2;
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// One API server. Lower `priority` wins; among equal priorities the
+/// endpoint with the best latency-per-weight is preferred.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEndpointConfig {
+    pub url: String,
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+impl ApiEndpointConfig {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            priority: 0,
+            weight: default_weight(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStats {
+    pub url: String,
+    pub priority: u32,
+    pub weight: u32,
+    pub requests: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub avg_latency_ms: Option<f64>,
+    pub last_latency_ms: Option<f64>,
+    pub healthy: bool,
+}
+
+struct Endpoint {
+    config: ApiEndpointConfig,
+    requests: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    avg_latency_ms: Option<f64>,
+    last_latency_ms: Option<f64>,
+    cooldown_until: Option<Instant>,
+}
+
+impl Endpoint {
+    fn new(config: ApiEndpointConfig) -> Self {
+        Self {
+            config,
+            requests: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            avg_latency_ms: None,
+            last_latency_ms: None,
+            cooldown_until: None,
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        match self.cooldown_until {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+
+    // Unmeasured endpoints score 0 so they get tried at least once
+    fn score(&self) -> f64 {
+        self.avg_latency_ms.unwrap_or(0.0) / self.config.weight.max(1) as f64
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        self.requests += 1;
+        self.consecutive_failures = 0;
+        self.cooldown_until = None;
+        self.last_latency_ms = Some(ms);
+        self.avg_latency_ms = Some(match self.avg_latency_ms {
+            Some(avg) => avg * 0.7 + ms * 0.3,
+            None => ms,
+        });
+    }
+
+    fn record_failure(&mut self) {
+        self.requests += 1;
+        self.failures += 1;
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= FAILURES_BEFORE_COOLDOWN {
+            self.cooldown_until = Some(Instant::now() + ENDPOINT_COOLDOWN);
+        }
+    }
+
+    fn stats(&self) -> EndpointStats {
+        EndpointStats {
+            url: self.config.url.clone(),
+            priority: self.config.priority,
+            weight: self.config.weight,
+            requests: self.requests,
+            failures: self.failures,
+            consecutive_failures: self.consecutive_failures,
+            avg_latency_ms: self.avg_latency_ms,
+            last_latency_ms: self.last_latency_ms,
+            healthy: self.is_healthy(),
+        }
+    }
+}
+
 pub struct DictionaryApiClient {
     client: Client,
-    base_url: String,
+    endpoints: Mutex<Vec<Endpoint>>,
     max_retries: u32,
 }
 
 impl DictionaryApiClient {
     pub fn new(base_url: String) -> Self {
+        Self::with_endpoints(vec![ApiEndpointConfig::new(base_url)])
+    }
+
+    pub fn with_endpoints(endpoints: Vec<ApiEndpointConfig>) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_millis(100)) // 100ms timeout as per requirements
             .build()
@@ -44,12 +164,71 @@ impl DictionaryApiClient {
         
         Self {
             client,
-            base_url,
+            endpoints: Mutex::new(endpoints.into_iter().map(Endpoint::new).collect()),
             max_retries: 2,
         }
     }
 
+    /// Replace the endpoint list, keeping statistics for URLs that stay.
+    pub fn set_endpoints(&self, configs: Vec<ApiEndpointConfig>) {
+        if configs.is_empty() {
+            return;
+        }
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let mut previous: Vec<Endpoint> = endpoints.drain(..).collect();
+        for config in configs {
+            let endpoint = match previous.iter().position(|e| e.config.url == config.url) {
+                Some(pos) => {
+                    let mut existing = previous.remove(pos);
+                    existing.config = config;
+                    existing
+                }
+                None => Endpoint::new(config),
+            };
+            endpoints.push(endpoint);
+        }
+    }
+
+    pub fn endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.endpoints.lock().unwrap().iter().map(|e| e.stats()).collect()
+    }
+
+    pub fn endpoint_urls(&self) -> Vec<String> {
+        self.endpoints.lock().unwrap().iter().map(|e| e.config.url.clone()).collect()
+    }
+
+    /// Feed an out-of-band health probe into an endpoint's statistics.
+    pub fn record_probe(&self, url: &str, latency: Option<Duration>) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        if let Some(endpoint) = endpoints.iter_mut().find(|e| e.config.url == url) {
+            match latency {
+                Some(latency) => endpoint.record_success(latency),
+                None => endpoint.record_failure(),
+            }
+        }
+    }
+
+    /// Endpoint URLs in the order they should be tried: healthy before
+    /// cooling down, then by priority, then by latency scaled by weight.
+    fn ordered_endpoints(&self) -> Vec<String> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let mut order: Vec<&Endpoint> = endpoints.iter().collect();
+        order.sort_by(|a, b| {
+            b.is_healthy().cmp(&a.is_healthy())
+                .then(a.config.priority.cmp(&b.config.priority))
+                .then(a.score().partial_cmp(&b.score()).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        order.into_iter().map(|e| e.config.url.clone()).collect()
+    }
+
     pub async fn get_definition(&self, word: &str) -> DictionaryResult<Option<WordDefinition>> {
+        self.get_definition_with_retries(word, self.max_retries).await
+    }
+
+    /// Same as `get_definition`, with an explicit retry budget. Offline-first
+    /// lookups use zero retries so a struggling server can't stall the popup.
+    pub async fn get_definition_with_retries(&self, word: &str, max_retries: u32) -> DictionaryResult<Option<WordDefinition>> {
         // Validate input
         if word.trim().is_empty() {
             return Err(DictionaryError::InvalidInput {
@@ -57,11 +236,12 @@ impl DictionaryApiClient {
             });
         }
         
-        let url = format!("{}/api/v1/define/{}", self.base_url, word);
+        // Encoded so phrases ("give up") and apostrophes survive the path
+        let path = format!("/api/v1/define/{}", urlencoding::encode(word));
         let mut last_error = None;
         
-        for attempt in 0..=self.max_retries {
-            match self.make_request::<WordDefinition>(&url).await {
+        for attempt in 0..=max_retries {
+            match self.make_request::<WordDefinition>(&path).await {
                 Ok(response) => {
                     if response.success {
                         return Ok(response.data);
@@ -70,6 +250,7 @@ impl DictionaryApiClient {
                         if response.error.as_ref().map(|e| e.contains("not found")).unwrap_or(false) {
                             return Err(DictionaryError::WordNotFound {
                                 word: word.to_string(),
+                                suggestions: Vec::new(),
                             });
                         }
                         return Ok(None);
@@ -77,7 +258,7 @@ impl DictionaryApiClient {
                 },
                 Err(e) => {
                     last_error = Some(e);
-                    if attempt < self.max_retries {
+                    if attempt < max_retries {
                         // Log retry attempt
                         println!("Retry attempt {} for word '{}' after error", attempt + 1, word);
                         tokio::time::sleep(Duration::from_millis(50)).await;
@@ -95,11 +276,11 @@ impl DictionaryApiClient {
             return Ok(vec![]); // Empty query returns empty results
         }
         
-        let url = format!("{}/api/v1/search?q={}", self.base_url, urlencoding::encode(query));
+        let path = format!("/api/v1/search?q={}", urlencoding::encode(query));
         let mut last_error = None;
         
         for attempt in 0..=self.max_retries {
-            match self.make_request::<Vec<SearchResult>>(&url).await {
+            match self.make_request::<Vec<SearchResult>>(&path).await {
                 Ok(response) => {
                     if response.success {
                         return Ok(response.data.unwrap_or_default());
@@ -124,7 +305,52 @@ impl DictionaryApiClient {
         Ok(vec![])
     }
 
-    async fn make_request<T: for<'de> Deserialize<'de>>(&self, url: &str) -> DictionaryResult<ApiResponse<T>> {
+    async fn make_request<T: for<'de> Deserialize<'de>>(&self, path: &str) -> DictionaryResult<ApiResponse<T>> {
+        self.with_failover("Dictionary API", |base_url| {
+            let url = format!("{}{}", base_url, path);
+            async move { self.request_endpoint::<T>(&url).await }
+        }).await
+    }
+
+    /// Run `request` against each endpoint's base URL in preference order
+    /// until one answers, recording latency and failures per endpoint.
+    /// Other clients of the same servers (AI) share the endpoint health.
+    pub async fn with_failover<T, F, Fut>(&self, service: &str, request: F) -> DictionaryResult<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = DictionaryResult<T>>,
+    {
+        let mut last_error = None;
+        
+        for base_url in self.ordered_endpoints() {
+            let start = Instant::now();
+            let result = request(base_url.clone()).await;
+            
+            let elapsed = start.elapsed();
+            
+            if let Some(endpoint) = self.endpoints.lock().unwrap().iter_mut().find(|e| e.config.url == base_url) {
+                match &result {
+                    Ok(_) => endpoint.record_success(elapsed),
+                    Err(_) => endpoint.record_failure(),
+                }
+            }
+            
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    println!("Endpoint {} failed, trying next: {}", base_url, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        
+        Err(last_error.unwrap_or_else(|| DictionaryError::ServiceUnavailable {
+            service: service.to_string(),
+            retry_after: None,
+        }))
+    }
+
+    async fn request_endpoint<T: for<'de> Deserialize<'de>>(&self, url: &str) -> DictionaryResult<ApiResponse<T>> {
         let response = self.client
             .get(url)
             .send()
@@ -165,6 +391,107 @@ impl From<WordDefinition> for crate::cache::Definition {
             pos: api_def.pos,
             pronunciation: api_def.pronunciation,
             frequency: Some(api_def.frequency as u32),
+            inflection: None,
+            language: Some(ENGLISH.to_string()),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const DEFINITION_BODY: &str = r#"{"success":true,"data":{"rank":1,"pos":"n","frequency":10,"definitions":["a test"],"pronunciation":null,"examples":null},"timestamp":0}"#;
+
+    /// Minimal HTTP server answering every request with the same status and
+    /// body after `delay_ms`. Returns its base URL and a hit counter.
+    async fn spawn_mock_server(status: u16, body: &'static str, delay_ms: u64) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else { break };
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                    let response = format!(
+                        "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status, body.len(), body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    fn endpoint(url: &str, priority: u32) -> ApiEndpointConfig {
+        ApiEndpointConfig {
+            url: url.to_string(),
+            priority,
+            weight: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_second_endpoint() {
+        let (broken, broken_hits) = spawn_mock_server(500, "{}", 0).await;
+        let (working, working_hits) = spawn_mock_server(200, DEFINITION_BODY, 0).await;
+
+        let client = DictionaryApiClient::with_endpoints(vec![endpoint(&broken, 0), endpoint(&working, 1)]);
+        let definition = client.get_definition("test").await.unwrap().unwrap();
+
+        assert_eq!(definition.definitions, vec!["a test".to_string()]);
+        assert_eq!(broken_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(working_hits.load(Ordering::SeqCst), 1);
+
+        let stats = client.endpoint_stats();
+        assert_eq!(stats[0].failures, 1);
+        assert_eq!(stats[1].failures, 0);
+        assert!(stats[1].avg_latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prefers_lowest_latency_endpoint() {
+        let (slow, slow_hits) = spawn_mock_server(200, DEFINITION_BODY, 40).await;
+        let (fast, fast_hits) = spawn_mock_server(200, DEFINITION_BODY, 0).await;
+
+        let client = DictionaryApiClient::with_endpoints(vec![endpoint(&slow, 0), endpoint(&fast, 0)]);
+
+        // Measure both, then every further lookup should go to the fast one
+        client.record_probe(&slow, Some(Duration::from_millis(40)));
+        client.record_probe(&fast, Some(Duration::from_millis(1)));
+        for _ in 0..3 {
+            client.get_definition("test").await.unwrap();
+        }
+
+        assert_eq!(slow_hits.load(Ordering::SeqCst), 0);
+        assert_eq!(fast_hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoint_is_skipped() {
+        let (broken, broken_hits) = spawn_mock_server(503, "{}", 0).await;
+        let (working, working_hits) = spawn_mock_server(200, DEFINITION_BODY, 0).await;
+
+        let client = DictionaryApiClient::with_endpoints(vec![endpoint(&broken, 0), endpoint(&working, 1)]);
+        for _ in 0..4 {
+            client.get_definition("test").await.unwrap();
         }
+
+        // Two failures put the preferred endpoint into cooldown
+        assert_eq!(broken_hits.load(Ordering::SeqCst), FAILURES_BEFORE_COOLDOWN as usize);
+        assert_eq!(working_hits.load(Ordering::SeqCst), 4);
+        assert!(!client.endpoint_stats()[0].healthy);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,372 @@
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::language::LanguagePair;
+use crate::local_store::{open_import_file, ImportProgress};
+use crate::source::DictionarySource;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// Separates alternative translations within one column
+const TRANSLATION_SEPARATOR: char = ';';
+
+/// Translations from one language into another, e.g. English to
+/// Vietnamese, stored in its own SQLite file per language pair. Packs are
+/// imported from tab-separated word lists:
+///
+/// `word<TAB>translation; translation<TAB>part of speech`
+///
+/// The part of speech is optional, as are `#` comment lines.
+pub struct BilingualPack {
+    connection: Mutex<Connection>,
+    path: PathBuf,
+    pair: LanguagePair,
+    name: String,
+}
+
+impl BilingualPack {
+    pub fn open(path: &Path, pair: LanguagePair) -> DictionaryResult<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| pack_error(path, &e.to_string()))?;
+        }
+
+        let connection = Connection::open(path).map_err(|e| pack_error(path, &e.to_string()))?;
+        // WAL lets lookups keep reading the old pack while an import runs
+        connection.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS translations (
+                 id INTEGER PRIMARY KEY,
+                 word TEXT NOT NULL COLLATE NOCASE,
+                 pos TEXT NOT NULL,
+                 translations TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_translations_word ON translations(word);",
+        ).map_err(|e| pack_error(path, &e.to_string()))?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            path: path.to_path_buf(),
+            name: pack_name(&pair),
+            pair,
+        })
+    }
+
+    fn connection(&self) -> DictionaryResult<std::sync::MutexGuard<'_, Connection>> {
+        self.connection.lock().map_err(|e| pack_error(&self.path, &format!("Failed to acquire connection lock: {}", e)))
+    }
+
+    /// Replace the pack's contents with a tab-separated word list. Lines
+    /// without a translation are skipped and reported.
+    pub fn import<B: BufRead>(
+        &self,
+        mut reader: B,
+        bytes_read: &AtomicU64,
+        progress: &mut ImportProgress,
+        mut on_progress: impl FnMut(&ImportProgress),
+    ) -> DictionaryResult<()> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction().map_err(|e| pack_error(&self.path, &e.to_string()))?;
+        transaction.execute("DELETE FROM translations", []).map_err(|e| pack_error(&self.path, &e.to_string()))?;
+
+        let mut line = String::new();
+        let mut line_number = 0u64;
+        let mut last_report = Instant::now();
+        loop {
+            line.clear();
+            line_number += 1;
+            let n = reader.read_line(&mut line).map_err(|e| DictionaryError::SourceError {
+                source: self.name.clone(),
+                message: format!("read failed after {} entries: {}", progress.entries_imported, e),
+            })?;
+            if n == 0 {
+                break;
+            }
+
+            let (word, pos, translations) = match parse_line(&line) {
+                Ok(Some(entry)) => entry,
+                Ok(None) => continue,
+                Err(e) => {
+                    progress.report_issue(line_number, e);
+                    continue;
+                }
+            };
+            let translations = serde_json::to_string(&translations).unwrap_or_default();
+            transaction
+                .prepare_cached("INSERT INTO translations (word, pos, translations) VALUES (?1, ?2, ?3)")
+                .and_then(|mut statement| statement.execute(params![word, pos, translations]))
+                .map_err(|e| pack_error(&self.path, &e.to_string()))?;
+            progress.entries_imported += 1;
+
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+                on_progress(progress);
+                last_report = Instant::now();
+            }
+        }
+
+        transaction.commit().map_err(|e| pack_error(&self.path, &e.to_string()))?;
+        progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl DictionarySource for BilingualPack {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn language(&self) -> Option<&str> {
+        Some(&self.pair.source)
+    }
+
+    fn target_language(&self) -> Option<&str> {
+        Some(&self.pair.target)
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        let connection = self.connection()?;
+        let rows: Vec<(String, String, String)> = connection
+            .prepare_cached("SELECT word, pos, translations FROM translations WHERE word = ?1 ORDER BY id")
+            .and_then(|mut statement| {
+                statement.query_map(params![word.trim()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect()
+            })
+            .map_err(|e| pack_error(&self.path, &e.to_string()))?;
+        let Some((headword, pos, _)) = rows.first().cloned() else { return Ok(None) };
+
+        // Translations for another part of speech say which one they are
+        let definitions = rows.iter()
+            .flat_map(|(_, row_pos, json)| {
+                let other_pos = !row_pos.is_empty() && *row_pos != pos;
+                serde_json::from_str::<Vec<String>>(json).unwrap_or_default().into_iter()
+                    .map(move |t| if other_pos { format!("({}) {}", row_pos, t) } else { t })
+            })
+            .collect();
+
+        Ok(Some(Definition {
+            word: headword,
+            pronunciation: None,
+            pos,
+            definitions,
+            frequency: None,
+            inflection: None,
+            language: Some(self.pair.target.clone()),
+        }))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Escape LIKE wildcards so user input is matched literally
+        let pattern = format!(
+            "{}%",
+            prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        let connection = self.connection()?;
+        connection
+            .prepare_cached(
+                "SELECT word FROM translations WHERE word LIKE ?1 ESCAPE '\\'
+                 GROUP BY word ORDER BY MIN(id) LIMIT ?2",
+            )
+            .and_then(|mut statement| {
+                statement.query_map(params![pattern, limit as i64], |row| row.get(0))?
+                    .collect()
+            })
+            .map_err(|e| pack_error(&self.path, &e.to_string()))
+    }
+
+    // No `headwords`: words with only a translation would pass for local
+    // monolingual headwords in the index, so packs are searched directly
+}
+
+/// `(word, pos, translations)` from one line of a word list; `None` for
+/// blank and comment lines
+fn parse_line(line: &str) -> Result<Option<(String, String, Vec<String>)>, String> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let mut columns = line.split('\t');
+    let word = columns.next().unwrap_or_default().trim();
+    let translations: Vec<String> = columns.next().unwrap_or_default()
+        .split(TRANSLATION_SEPARATOR)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    let pos = columns.next().unwrap_or_default().trim();
+
+    if word.is_empty() || translations.is_empty() {
+        return Err(format!("expected 'word<TAB>translation', got '{}'", line));
+    }
+    Ok(Some((word.to_string(), pos.to_string(), translations)))
+}
+
+fn pack_name(pair: &LanguagePair) -> String {
+    format!("bilingual:{}", pair)
+}
+
+fn pack_error(path: &Path, message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "bilingual".to_string(),
+        message: format!("{}: {}", path.display(), message),
+    }
+}
+
+pub fn bilingual_dir<R: Runtime>(handle: &AppHandle<R>) -> Option<PathBuf> {
+    handle.path().app_data_dir().ok().map(|dir| dir.join("dictionaries").join("bilingual"))
+}
+
+/// Installed packs, named `<source>-<target>.db`
+pub fn installed_packs(dir: &Path) -> Vec<(LanguagePair, PathBuf)> {
+    let mut packs: Vec<(LanguagePair, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("db"))
+        .filter_map(|path| {
+            let pair = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((pair, path))
+        })
+        .collect();
+    packs.sort_by_key(|(pair, _)| pair.to_string());
+    packs
+}
+
+/// (Re)register one pack as a lookup source, e.g. after an import
+pub fn register_pack(dictionary_service: &DictionaryService, path: &Path, pair: LanguagePair) {
+    dictionary_service.remove_source(&pack_name(&pair));
+    match BilingualPack::open(path, pair) {
+        Ok(pack) => dictionary_service.add_source(Arc::new(pack)),
+        Err(e) => e.log_error(),
+    }
+}
+
+pub fn register_bilingual_packs<R: Runtime>(handle: &AppHandle<R>, dictionary_service: &DictionaryService) {
+    let Some(dir) = bilingual_dir(handle) else { return };
+    for (pair, path) in installed_packs(&dir) {
+        register_pack(dictionary_service, &path, pair);
+    }
+}
+
+/// Tauri command listing the installed language pairs
+#[tauri::command]
+pub fn list_bilingual_packs(app_handle: AppHandle) -> Vec<LanguagePair> {
+    bilingual_dir(&app_handle)
+        .map(|dir| installed_packs(&dir).into_iter().map(|(pair, _)| pair).collect())
+        .unwrap_or_default()
+}
+
+/// Tauri command to import a tab-separated word list (optionally gzipped)
+/// as the pack for a language pair, replacing any earlier one. Runs in the
+/// background and reports through `import-progress` events.
+#[tauri::command]
+pub fn import_bilingual_pack(
+    path: String,
+    source_language: String,
+    target_language: String,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<(), String> {
+    let pair: LanguagePair = format!("{}-{}", source_language.trim(), target_language.trim()).parse()?;
+    let dir = bilingual_dir(&app_handle).ok_or("Failed to get app data directory")?;
+    let pack_path = dir.join(format!("{}.db", pair));
+    let source_path = PathBuf::from(path);
+    if !source_path.is_file() {
+        return Err(format!("File not found: {}", source_path.display()));
+    }
+
+    let dictionary_service = dictionary_service.inner().clone();
+    let mut progress = ImportProgress {
+        format: "bilingual".to_string(),
+        path: source_path.display().to_string(),
+        ..Default::default()
+    };
+
+    std::thread::spawn(move || {
+        let mut emit = |p: &ImportProgress| {
+            let _ = app_handle.emit("import-progress", p.clone());
+        };
+
+        let result = open_import_file(&source_path)
+            .map_err(|e| pack_error(&source_path, &e.to_string()))
+            .and_then(|(reader, bytes_read, total_bytes)| {
+                progress.total_bytes = total_bytes;
+                BilingualPack::open(&pack_path, pair.clone())?.import(reader, &bytes_read, &mut progress, &mut emit)
+            });
+
+        match result {
+            Ok(()) => {
+                println!("Imported {} {} translations from {} ({} skipped)", progress.entries_imported, pair, progress.path, progress.entries_skipped);
+                register_pack(&dictionary_service, &pack_path, pair);
+            }
+            Err(e) => {
+                e.log_error();
+                progress.error = Some(e.user_message());
+            }
+        }
+
+        progress.done = true;
+        emit(&progress);
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const PACK: &str = "# English-Vietnamese sample
+cat\tcon mèo\tnoun
+run\tchạy; điều hành\tverb
+run\tcuộc chạy\tnoun
+broken line
+water\tnước
+";
+
+    #[test]
+    fn test_import_and_lookup() {
+        let path = std::env::temp_dir().join(format!("bilingual-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let pack = BilingualPack::open(&path, "en-vi".parse().unwrap()).unwrap();
+
+        let mut progress = ImportProgress::default();
+        pack.import(Cursor::new(PACK), &AtomicU64::new(0), &mut progress, |_| {}).unwrap();
+        assert_eq!(progress.entries_imported, 4);
+        assert_eq!(progress.issues.len(), 1);
+        assert_eq!(progress.issues[0].line, 5);
+
+        let run = pack.lookup("Run").unwrap().unwrap();
+        assert_eq!(run.word, "run");
+        assert_eq!(run.pos, "verb");
+        assert_eq!(run.definitions, vec!["chạy", "điều hành", "(noun) cuộc chạy"]);
+        assert_eq!(run.language.as_deref(), Some("vi"));
+        assert_eq!(pack.lookup("water").unwrap().unwrap().definitions, vec!["nước"]);
+        assert!(pack.lookup("dog").unwrap().is_none());
+        assert_eq!(pack.search("c", 10).unwrap(), vec!["cat"]);
+        assert_eq!((pack.language(), pack.target_language()), (Some("en"), Some("vi")));
+        assert!(!pack.headwords(&mut |_, _| {}).unwrap());
+
+        // Re-importing replaces the pack
+        pack.import(Cursor::new("dog\tcon chó\n"), &AtomicU64::new(0), &mut ImportProgress::default(), |_| {}).unwrap();
+        assert!(pack.lookup("cat").unwrap().is_none());
+        assert!(pack.lookup("dog").unwrap().is_some());
+
+        drop(pack);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use serde::{Deserialize, Serialize};
+use crate::morphology::Inflection;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Definition {
@@ -10,6 +11,13 @@ pub struct Definition {
     pub pos: String, // part of speech
     pub definitions: Vec<String>,
     pub frequency: Option<u32>,
+    /// Set when the word was found through its headword, e.g. "ran" via "run"
+    #[serde(default)]
+    pub inflection: Option<Inflection>,
+    /// Language the definitions are written in, e.g. "vi" for an English
+    /// word translated to Vietnamese. `None` when the source doesn't say.
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 #[derive(Debug)]
@@ -82,6 +90,18 @@ impl DictionaryCache {
         self.lru_order.clear();
     }
 
+    /// Drop every cached spelling of `word`, ignoring case
+    pub fn remove(&mut self, word: &str) {
+        let word = word.to_lowercase();
+        self.words.retain(|key, _| key.to_lowercase() != word);
+        self.lru_order.retain(|key| key.to_lowercase() != word);
+    }
+
+    /// Every cached word, for seeding the autocomplete index
+    pub fn words(&self) -> Vec<String> {
+        self.words.keys().cloned().collect()
+    }
+
     pub fn contains(&self, word: &str) -> bool {
         self.words.contains_key(word)
     }
@@ -153,6 +173,8 @@ mod tests {
             pos: "noun".to_string(),
             definitions: vec!["a test definition".to_string()],
             frequency: Some(100),
+            inflection: None,
+            language: None,
         };
         
         cache.insert("test".to_string(), def1.clone());
@@ -174,6 +196,8 @@ mod tests {
             pos: "noun".to_string(),
             definitions: vec!["first".to_string()],
             frequency: None,
+            inflection: None,
+            language: None,
         };
         
         let def2 = Definition {
@@ -182,6 +206,8 @@ mod tests {
             pos: "noun".to_string(),
             definitions: vec!["second".to_string()],
             frequency: None,
+            inflection: None,
+            language: None,
         };
         
         let def3 = Definition {
@@ -190,6 +216,8 @@ mod tests {
             pos: "noun".to_string(),
             definitions: vec!["third".to_string()],
             frequency: None,
+            inflection: None,
+            language: None,
         };
         
         cache.insert("one".to_string(), def1);
@@ -218,6 +246,8 @@ mod tests {
                 pos: "noun".to_string(),
                 definitions: vec![format!("definition{}", i)],
                 frequency: Some(i),
+                inflection: None,
+                language: None,
             };
             cache.insert(format!("word{}", i), def);
         }
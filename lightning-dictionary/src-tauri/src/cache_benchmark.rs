@@ -141,6 +141,8 @@ fn create_test_definition(word: &str) -> Definition {
             format!("Secondary definition for {}", word),
         ],
         frequency: Some((word.len() * 100) as u32),
+        inflection: None,
+        language: None,
     }
 }
 
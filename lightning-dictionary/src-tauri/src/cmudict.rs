@@ -0,0 +1,502 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::local_store::{open_import_file, ImportProgress};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// Rows read per rhyme key before ranking; common endings have thousands
+const MAX_RHYME_CANDIDATES: usize = 5_000;
+
+// ARPAbet phone (stress digit removed) -> IPA. Stressed AH and ER are
+// the open vowels; unstressed they reduce to schwa.
+const PHONES: &[(&str, &str)] = &[
+    ("AA", "ɑ"), ("AE", "æ"), ("AH", "ʌ"), ("AO", "ɔ"), ("AW", "aʊ"), ("AY", "aɪ"), ("EH", "ɛ"), ("ER", "ɝ"),
+    ("EY", "eɪ"), ("IH", "ɪ"), ("IY", "i"), ("OW", "oʊ"), ("OY", "ɔɪ"), ("UH", "ʊ"), ("UW", "u"),
+    ("B", "b"), ("CH", "tʃ"), ("D", "d"), ("DH", "ð"), ("F", "f"), ("G", "ɡ"), ("HH", "h"), ("JH", "dʒ"),
+    ("K", "k"), ("L", "l"), ("M", "m"), ("N", "n"), ("NG", "ŋ"), ("P", "p"), ("R", "ɹ"), ("S", "s"),
+    ("SH", "ʃ"), ("T", "t"), ("TH", "θ"), ("V", "v"), ("W", "w"), ("Y", "j"), ("Z", "z"), ("ZH", "ʒ"),
+];
+
+// Consonant clusters that can start an English syllable; a stress mark
+// goes before the longest one ending at its vowel
+const ONSETS: &[&str] = &[
+    "P L", "P R", "P Y", "B L", "B R", "B Y", "T R", "T W", "D R", "D W", "K L", "K R", "K W", "K Y",
+    "G L", "G R", "G W", "F L", "F R", "F Y", "TH R", "TH W", "SH R", "V Y", "M Y", "HH Y", "S P", "S T",
+    "S K", "S M", "S N", "S L", "S W", "S F", "S P L", "S P R", "S P Y", "S T R", "S K R", "S K W", "S K Y", "S K L",
+];
+
+/// One way of saying a word, as listed in the CMU Pronouncing Dictionary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pronunciation {
+    pub word: String,
+    /// e.g. `AH0 B AW1 T`
+    pub arpabet: String,
+    /// e.g. `əˈbaʊt`
+    pub ipa: String,
+    pub syllables: u32,
+    /// Stress per syllable: 1 primary, 2 secondary, 0 none, e.g. `01`
+    pub stress: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rhyme {
+    pub word: String,
+    pub syllables: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Rhymes {
+    /// Same sounds from the stressed vowel on ("cat" / "hat")
+    pub perfect: Vec<Rhyme>,
+    /// Same vowels or the same consonants after the stressed vowel ("cat" / "cap", "cut")
+    pub near: Vec<Rhyme>,
+}
+
+struct Phone<'a> {
+    name: &'a str,
+    stress: Option<char>,
+}
+
+fn parse_phones(arpabet: &str) -> Option<Vec<Phone<'_>>> {
+    arpabet.split_whitespace()
+        .map(|phone| {
+            let (name, stress) = match phone.strip_suffix(['0', '1', '2']) {
+                Some(name) => (name, phone.chars().last()),
+                None => (phone, None),
+            };
+            PHONES.iter().any(|(p, _)| *p == name).then_some(Phone { name, stress })
+        })
+        .collect()
+}
+
+fn is_vowel(phone: &str) -> bool {
+    phone.starts_with(['A', 'E', 'I', 'O', 'U'])
+}
+
+/// Index of the vowel rhymes are measured from: the last primary stress,
+/// else the last secondary stress, else the last vowel
+fn rhyme_start(phones: &[Phone]) -> Option<usize> {
+    let last = |stress: char| phones.iter().rposition(|p| p.stress == Some(stress));
+    last('1').or_else(|| last('2')).or_else(|| phones.iter().rposition(|p| is_vowel(p.name)))
+}
+
+/// Rhyme keys of a pronunciation: the phones from the stressed vowel on,
+/// just its vowels, and its consonants with vowels as `*`
+fn rhyme_keys(phones: &[Phone]) -> (String, String, String) {
+    let Some(start) = rhyme_start(phones) else { return Default::default() };
+    let tail = &phones[start..];
+    let join = |parts: Vec<&str>| parts.join(" ");
+    (
+        join(tail.iter().map(|p| p.name).collect()),
+        join(tail.iter().filter(|p| is_vowel(p.name)).map(|p| p.name).collect()),
+        join(tail.iter().map(|p| if is_vowel(p.name) { "*" } else { p.name }).collect()),
+    )
+}
+
+/// Broad IPA transcription of ARPAbet phones, with stress marks at the
+/// start of stressed syllables
+pub fn arpabet_to_ipa(arpabet: &str) -> Option<String> {
+    let phones = parse_phones(arpabet)?;
+    let mut ipa = String::new();
+    let mut syllable_start = 0;
+
+    for (i, phone) in phones.iter().enumerate() {
+        if !is_vowel(phone.name) {
+            continue;
+        }
+
+        // The consonants since the previous vowel, split between its coda
+        // and this syllable's onset
+        let consonants: Vec<&str> = phones[syllable_start..i].iter().map(|p| p.name).collect();
+        let onset = (0..consonants.len())
+            .find(|from| {
+                let cluster = consonants[*from..].join(" ");
+                ONSETS.contains(&cluster.as_str()) || (consonants.len() - from == 1 && cluster != "NG")
+            })
+            .unwrap_or(consonants.len());
+        let ipa_of = |names: &[&str]| names.iter().map(|name| phone_ipa(name, None)).collect::<String>();
+
+        ipa.push_str(&ipa_of(&consonants[..onset]));
+        match phone.stress {
+            Some('1') => ipa.push('ˈ'),
+            Some('2') => ipa.push('ˌ'),
+            _ => {}
+        }
+        ipa.push_str(&ipa_of(&consonants[onset..]));
+        ipa.push_str(phone_ipa(phone.name, phone.stress));
+        syllable_start = i + 1;
+    }
+    for phone in &phones[syllable_start..] {
+        ipa.push_str(phone_ipa(phone.name, None));
+    }
+    Some(ipa)
+}
+
+fn phone_ipa(name: &str, stress: Option<char>) -> &'static str {
+    match (name, stress) {
+        ("AH", Some('0')) => "ə",
+        ("ER", Some('0')) => "ɚ",
+        _ => PHONES.iter().find(|(p, _)| *p == name).map(|(_, ipa)| *ipa).unwrap_or(""),
+    }
+}
+
+/// Parse one dictionary line, e.g. `READ(2)  R EH1 D`. Comments and blank
+/// lines give `Ok(None)`; alternate spellings keep their base word.
+fn parse_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    if line.is_empty() || line.starts_with(";;;") {
+        return Ok(None);
+    }
+    // Punctuation entries such as "!EXCLAMATION-POINT"
+    if !line.starts_with(|c: char| c.is_alphanumeric() || c == '\'') {
+        return Ok(None);
+    }
+
+    let (word, arpabet) = line.split_once(char::is_whitespace).ok_or("missing pronunciation")?;
+    let word = match word.find('(') {
+        Some(at) if word.ends_with(')') => &word[..at],
+        _ => word,
+    };
+    let arpabet = arpabet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if arpabet.is_empty() {
+        return Err("missing pronunciation".to_string());
+    }
+    Ok(Some((word.to_lowercase(), arpabet)))
+}
+
+/// Pronunciations and the rhyme index, kept in their own SQLite database
+/// next to the import store
+pub struct PronunciationStore {
+    connection: Mutex<Connection>,
+    path: PathBuf,
+}
+
+impl PronunciationStore {
+    pub fn open(path: &Path) -> DictionaryResult<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| store_error(path, &e.to_string()))?;
+        }
+
+        let connection = Connection::open(path).map_err(|e| store_error(path, &e.to_string()))?;
+        // WAL lets lookups keep reading the old data while an import runs
+        connection.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS pronunciations (
+                 id INTEGER PRIMARY KEY,
+                 word TEXT NOT NULL COLLATE NOCASE,
+                 arpabet TEXT NOT NULL,
+                 ipa TEXT NOT NULL,
+                 syllables INTEGER NOT NULL,
+                 stress TEXT NOT NULL,
+                 rhyme TEXT NOT NULL,
+                 assonance TEXT NOT NULL,
+                 consonance TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_pronunciations_word ON pronunciations(word);
+             CREATE INDEX IF NOT EXISTS idx_pronunciations_rhyme ON pronunciations(rhyme);
+             CREATE INDEX IF NOT EXISTS idx_pronunciations_assonance ON pronunciations(assonance);
+             CREATE INDEX IF NOT EXISTS idx_pronunciations_consonance ON pronunciations(consonance);",
+        ).map_err(|e| store_error(path, &e.to_string()))?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            path: path.to_path_buf(),
+        })
+    }
+
+    fn connection(&self) -> DictionaryResult<std::sync::MutexGuard<'_, Connection>> {
+        self.connection.lock().map_err(|e| store_error(&self.path, &format!("Failed to acquire connection lock: {}", e)))
+    }
+
+    /// Replace the stored pronunciations with a `cmudict` file. Lines with
+    /// unknown phones are skipped and reported.
+    pub fn import<B: BufRead>(
+        &self,
+        mut reader: B,
+        bytes_read: &AtomicU64,
+        progress: &mut ImportProgress,
+        mut on_progress: impl FnMut(&ImportProgress),
+    ) -> DictionaryResult<()> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction().map_err(|e| store_error(&self.path, &e.to_string()))?;
+        transaction.execute("DELETE FROM pronunciations", []).map_err(|e| store_error(&self.path, &e.to_string()))?;
+
+        let mut line = Vec::new();
+        let mut line_number = 0u64;
+        let mut last_report = Instant::now();
+        loop {
+            line.clear();
+            line_number += 1;
+            let n = reader.read_until(b'\n', &mut line).map_err(|e| DictionaryError::SourceError {
+                source: "cmudict".to_string(),
+                message: format!("read failed after {} entries: {}", progress.entries_imported, e),
+            })?;
+            if n == 0 {
+                break;
+            }
+
+            // Older releases are Latin-1; the entries themselves are ASCII
+            let text = String::from_utf8_lossy(&line);
+            let (word, arpabet) = match parse_line(&text) {
+                Ok(Some(entry)) => entry,
+                Ok(None) => continue,
+                Err(e) => {
+                    progress.report_issue(line_number, e);
+                    continue;
+                }
+            };
+            let (Some(phones), Some(ipa)) = (parse_phones(&arpabet), arpabet_to_ipa(&arpabet)) else {
+                progress.report_issue(line_number, format!("unknown phone in '{}'", arpabet));
+                continue;
+            };
+
+            let stress: String = phones.iter().filter_map(|p| p.stress).collect();
+            let (rhyme, assonance, consonance) = rhyme_keys(&phones);
+            transaction
+                .prepare_cached(
+                    "INSERT INTO pronunciations (word, arpabet, ipa, syllables, stress, rhyme, assonance, consonance)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )
+                .and_then(|mut statement| statement.execute(params![
+                    word, arpabet, ipa, stress.len() as u32, stress, rhyme, assonance, consonance,
+                ]))
+                .map_err(|e| store_error(&self.path, &e.to_string()))?;
+            progress.entries_imported += 1;
+
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+                on_progress(progress);
+                last_report = Instant::now();
+            }
+        }
+
+        transaction.commit().map_err(|e| store_error(&self.path, &e.to_string()))?;
+        progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Every listed pronunciation of `word`, most common first
+    pub fn lookup(&self, word: &str) -> DictionaryResult<Vec<Pronunciation>> {
+        let connection = self.connection()?;
+        let mut statement = connection
+            .prepare_cached("SELECT word, arpabet, ipa, syllables, stress FROM pronunciations WHERE word = ?1 ORDER BY id")
+            .map_err(|e| store_error(&self.path, &e.to_string()))?;
+
+        let rows = statement
+            .query_map(params![word.trim()], |row| {
+                Ok(Pronunciation {
+                    word: row.get(0)?,
+                    arpabet: row.get(1)?,
+                    ipa: row.get(2)?,
+                    syllables: row.get(3)?,
+                    stress: row.get(4)?,
+                })
+            })
+            .map_err(|e| store_error(&self.path, &e.to_string()))?
+            .filter_map(Result::ok)
+            .collect();
+        Ok(rows)
+    }
+
+    /// Words rhyming with any pronunciation of `word`, unranked
+    pub fn rhymes(&self, word: &str) -> DictionaryResult<Rhymes> {
+        let connection = self.connection()?;
+        let keys: Vec<(String, String, String)> = connection
+            .prepare_cached("SELECT rhyme, assonance, consonance FROM pronunciations WHERE word = ?1 AND rhyme != ''")
+            .and_then(|mut statement| {
+                statement.query_map(params![word.trim()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect()
+            })
+            .map_err(|e| store_error(&self.path, &e.to_string()))?;
+
+        let word = word.trim().to_lowercase();
+        let mut seen = HashSet::from([word]);
+        let mut rhymes = Rhymes::default();
+        let matching = |sql: &str, key: &str, found: &mut Vec<Rhyme>, seen: &mut HashSet<String>| {
+            connection
+                .prepare_cached(sql)
+                .and_then(|mut statement| {
+                    let rows = statement.query_map(params![key, MAX_RHYME_CANDIDATES as i64], |row| {
+                        Ok(Rhyme { word: row.get(0)?, syllables: row.get(1)? })
+                    })?;
+                    for rhyme in rows.filter_map(Result::ok) {
+                        if seen.insert(rhyme.word.clone()) {
+                            found.push(rhyme);
+                        }
+                    }
+                    Ok(())
+                })
+                .map_err(|e| store_error(&self.path, &e.to_string()))
+        };
+
+        for (rhyme, _, _) in &keys {
+            matching("SELECT word, syllables FROM pronunciations WHERE rhyme = ?1 LIMIT ?2", rhyme, &mut rhymes.perfect, &mut seen)?;
+        }
+        for (_, assonance, consonance) in &keys {
+            matching("SELECT word, syllables FROM pronunciations WHERE assonance = ?1 LIMIT ?2", assonance, &mut rhymes.near, &mut seen)?;
+            matching("SELECT word, syllables FROM pronunciations WHERE consonance = ?1 LIMIT ?2", consonance, &mut rhymes.near, &mut seen)?;
+        }
+        Ok(rhymes)
+    }
+}
+
+fn store_error(path: &Path, message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "cmudict".to_string(),
+        message: format!("{}: {}", path.display(), message),
+    }
+}
+
+pub fn pronunciation_store_path<R: Runtime>(handle: &AppHandle<R>) -> Option<PathBuf> {
+    handle.path().app_data_dir().ok().map(|dir| dir.join("dictionaries").join("cmudict.db"))
+}
+
+/// Attach previously imported pronunciations, if there are any
+pub fn register_pronunciations(dictionary_service: &DictionaryService, path: &Path) {
+    match PronunciationStore::open(path) {
+        Ok(store) => dictionary_service.set_pronunciations(Arc::new(store)),
+        Err(e) => e.log_error(),
+    }
+}
+
+/// Tauri command to import the CMU Pronouncing Dictionary (`cmudict.dict`
+/// or `cmudict-0.7b`, optionally gzipped). Runs in the background and
+/// reports through `import-progress` events.
+#[tauri::command]
+pub fn import_cmudict(
+    path: String,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<(), String> {
+    let store_path = pronunciation_store_path(&app_handle).ok_or("Failed to get app data directory")?;
+    let source_path = PathBuf::from(path);
+    if !source_path.is_file() {
+        return Err(format!("File not found: {}", source_path.display()));
+    }
+
+    let dictionary_service = dictionary_service.inner().clone();
+    let mut progress = ImportProgress {
+        format: "cmudict".to_string(),
+        path: source_path.display().to_string(),
+        ..Default::default()
+    };
+
+    std::thread::spawn(move || {
+        let mut emit = |p: &ImportProgress| {
+            let _ = app_handle.emit("import-progress", p.clone());
+        };
+
+        let result = open_import_file(&source_path)
+            .map_err(|e| store_error(&source_path, &e.to_string()))
+            .and_then(|(reader, bytes_read, total_bytes)| {
+                progress.total_bytes = total_bytes;
+                PronunciationStore::open(&store_path)?.import(reader, &bytes_read, &mut progress, &mut emit)
+            });
+
+        match result {
+            Ok(()) => {
+                println!("Imported {} pronunciations from {} ({} skipped)", progress.entries_imported, progress.path, progress.entries_skipped);
+                register_pronunciations(&dictionary_service, &store_path);
+            }
+            Err(e) => {
+                e.log_error();
+                progress.error = Some(e.user_message());
+            }
+        }
+
+        progress.done = true;
+        emit(&progress);
+    });
+
+    Ok(())
+}
+
+/// Tauri command for a word's pronunciations with syllables and stress
+#[tauri::command]
+pub fn get_pronunciations(word: String, dictionary_service: State<'_, Arc<DictionaryService>>) -> Result<Vec<Pronunciation>, String> {
+    dictionary_service.pronunciations(&word).map_err(|e| e.user_message())
+}
+
+/// Tauri command for perfect and near rhymes, most frequent first
+#[tauri::command]
+pub fn find_rhymes(
+    word: String,
+    limit: Option<usize>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<Rhymes, String> {
+    dictionary_service.rhymes(&word, limit.unwrap_or(50)).map_err(|e| e.user_message())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICT: &str = ";;; # CMUdict  --  Major Version: 0.07
+!EXCLAMATION-POINT  EH2 K S K L AH0 M EY1 SH AH0 N P OY2 N T
+ABOUT  AH0 B AW1 T
+CAT  K AE1 T
+HAT  HH AE1 T
+CAP  K AE1 P
+CUT  K AH1 T
+DOUBT  D AW1 T
+READ  R IY1 D
+READ(2)  R EH1 D
+BED  B EH1 D
+NEED  N IY1 D # a comment
+BROKEN  B R OW1 K QQ0 N
+";
+
+    #[test]
+    fn test_ipa_and_keys() {
+        assert_eq!(arpabet_to_ipa("AH0 B AW1 T").as_deref(), Some("əˈbaʊt"));
+        assert_eq!(arpabet_to_ipa("EH1 K S T R AH0").as_deref(), Some("ˈɛkstɹə"));
+        assert_eq!(arpabet_to_ipa("K AH0 N S T R AH1 K T").as_deref(), Some("kənˈstɹʌkt"));
+        assert_eq!(arpabet_to_ipa("S IH1 NG ER0").as_deref(), Some("ˈsɪŋɚ"));
+        assert_eq!(arpabet_to_ipa("K XX1 T"), None);
+
+        let phones = parse_phones("T EY1 B AH0 L").unwrap();
+        assert_eq!(rhyme_keys(&phones), ("EY B AH L".to_string(), "EY AH".to_string(), "* B * L".to_string()));
+
+        assert_eq!(parse_line("READ(2)  R EH1 D"), Ok(Some(("read".to_string(), "R EH1 D".to_string()))));
+        assert_eq!(parse_line(";;; comment"), Ok(None));
+        assert!(parse_line("LONELY").is_err());
+    }
+
+    #[test]
+    fn test_import_and_rhymes() {
+        let path = std::env::temp_dir().join(format!("cmudict-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = PronunciationStore::open(&path).unwrap();
+        let mut progress = ImportProgress::default();
+        store.import(DICT.as_bytes(), &AtomicU64::new(0), &mut progress, |_| {}).unwrap();
+        assert_eq!(progress.entries_imported, 10);
+        assert_eq!(progress.issues.len(), 1);
+        assert_eq!(progress.issues[0].line, 13);
+
+        let read = store.lookup("Read").unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!((read[1].ipa.as_str(), read[1].syllables, read[1].stress.as_str()), ("ˈɹɛd", 1, "1"));
+        assert_eq!(store.lookup("about").unwrap()[0].stress, "01");
+
+        let words = |rhymes: &[Rhyme]| rhymes.iter().map(|r| r.word.clone()).collect::<Vec<_>>();
+        let cat = store.rhymes("cat").unwrap();
+        assert_eq!(words(&cat.perfect), vec!["hat"]);
+        assert_eq!(words(&cat.near), vec!["cap", "about", "cut", "doubt"]);
+        // Every pronunciation counts
+        assert_eq!(words(&store.rhymes("read").unwrap().perfect), vec!["need", "bed"]);
+
+        drop(store);
+        let _ = fs::remove_file(&path);
+    }
+}
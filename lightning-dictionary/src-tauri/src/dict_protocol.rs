@@ -0,0 +1,640 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::cache::Definition;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::settings::SettingsManager;
+use crate::source::DictionarySource;
+
+pub const DEFAULT_DICT_PORT: u16 = 2628;
+const CONNECT_TIMEOUT_MS: u64 = 500;
+const READ_TIMEOUT_MS: u64 = 2_000;
+
+fn default_port() -> u16 {
+    DEFAULT_DICT_PORT
+}
+
+fn default_database() -> String {
+    "*".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A dictd server to query. `database` is a database name from SHOW DB,
+/// `*` to search all of them, or `!` to stop at the first one that matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictServerConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_database")]
+    pub database: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl DictServerConfig {
+    pub fn new(host: String) -> Self {
+        Self {
+            host,
+            port: DEFAULT_DICT_PORT,
+            database: default_database(),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchStrategy {
+    Exact,
+    Prefix,
+    Soundex,
+    Lev,
+}
+
+impl MatchStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchStrategy::Exact => "exact",
+            MatchStrategy::Prefix => "prefix",
+            MatchStrategy::Soundex => "soundex",
+            MatchStrategy::Lev => "lev",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DictDatabase {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DictMatch {
+    pub database: String,
+    pub word: String,
+}
+
+/// One definition from a DEFINE response: the 151 header plus its text block
+#[derive(Debug, Clone, Serialize)]
+pub struct DictDefinition {
+    pub word: String,
+    pub database: String,
+    pub description: String,
+    pub text: Vec<String>,
+}
+
+/// Split a response or command line into words, honouring double quotes
+/// and backslash escapes the way RFC 2229 does.
+fn split_quoted(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_quotes => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                has_word = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_word = true;
+            }
+        }
+    }
+    if has_word {
+        words.push(current);
+    }
+
+    words
+}
+
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// One TCP session with a dictd server
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(host: &str, port: u16) -> io::Result<Self> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("could not resolve {}", host));
+        for addr in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, Duration::from_millis(CONNECT_TIMEOUT_MS)) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(Duration::from_millis(READ_TIMEOUT_MS)))?;
+                    stream.set_write_timeout(Some(Duration::from_millis(READ_TIMEOUT_MS)))?;
+                    return Ok(Self {
+                        reader: BufReader::new(stream.try_clone()?),
+                        writer: stream,
+                    });
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        self.writer.write_all(format!("{}\r\n", command).as_bytes())?;
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "server closed the connection"));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    /// Read a status line, returning the code and the rest of the line
+    fn status(&mut self) -> io::Result<(u16, String)> {
+        let line = self.read_line()?;
+        let code = line.get(..3).and_then(|c| c.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("malformed status line: {}", line)))?;
+        Ok((code, line.get(4..).unwrap_or("").to_string()))
+    }
+
+    /// Read a text block up to the lone "." line, undoing dot-stuffing
+    fn text_block(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line == "." {
+                return Ok(lines);
+            }
+            lines.push(line.strip_prefix('.').filter(|_| line.starts_with("..")).unwrap_or(&line).to_string());
+        }
+    }
+}
+
+/// Client for a DICT protocol (RFC 2229) server such as dictd. Each call
+/// opens its own short session, so an idle server never holds a socket.
+pub struct DictClient {
+    config: DictServerConfig,
+    source_name: String,
+}
+
+impl DictClient {
+    pub fn new(config: DictServerConfig) -> Self {
+        Self {
+            source_name: format!("dict://{}:{}/{}", config.host, config.port, config.database),
+            config,
+        }
+    }
+
+    fn network_error(&self, e: io::Error) -> DictionaryError {
+        DictionaryError::NetworkError {
+            message: format!("{}: {}", self.source_name, e),
+            is_timeout: matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock),
+        }
+    }
+
+    fn protocol_error(&self, code: u16, message: &str) -> DictionaryError {
+        DictionaryError::SourceError {
+            source: self.source_name.clone(),
+            message: format!("{} {}", code, message),
+        }
+    }
+
+    /// Connect, identify ourselves, run `f`, then say goodbye
+    fn session<T>(&self, f: impl FnOnce(&mut Connection) -> DictionaryResult<T>) -> DictionaryResult<T> {
+        let mut connection = Connection::open(&self.config.host, self.config.port)
+            .map_err(|e| self.network_error(e))?;
+
+        let (code, message) = connection.status().map_err(|e| self.network_error(e))?;
+        if code != 220 {
+            return Err(self.protocol_error(code, &message));
+        }
+
+        connection.send("CLIENT lightning-dictionary").map_err(|e| self.network_error(e))?;
+        let (code, message) = connection.status().map_err(|e| self.network_error(e))?;
+        if code != 250 {
+            return Err(self.protocol_error(code, &message));
+        }
+
+        let result = f(&mut connection);
+        // Best effort; the answer is already in hand
+        let _ = connection.send("QUIT");
+        result
+    }
+
+    /// Expect the trailing 250 that ends a successful command
+    fn finish(&self, connection: &mut Connection) -> DictionaryResult<()> {
+        match connection.status().map_err(|e| self.network_error(e))? {
+            (250, _) => Ok(()),
+            (code, message) => Err(self.protocol_error(code, &message)),
+        }
+    }
+
+    pub fn define(&self, word: &str) -> DictionaryResult<Vec<DictDefinition>> {
+        self.define_in(&self.config.database, word)
+    }
+
+    pub fn define_in(&self, database: &str, word: &str) -> DictionaryResult<Vec<DictDefinition>> {
+        self.session(|connection| {
+            connection.send(&format!("DEFINE {} {}", database, quote(word))).map_err(|e| self.network_error(e))?;
+
+            match connection.status().map_err(|e| self.network_error(e))? {
+                (552, _) => return Ok(vec![]),
+                (150, _) => {}
+                (code, message) => return Err(self.protocol_error(code, &message)),
+            }
+
+            let mut definitions = Vec::new();
+            loop {
+                match connection.status().map_err(|e| self.network_error(e))? {
+                    (151, header) => {
+                        let mut fields = split_quoted(&header).into_iter();
+                        let text = connection.text_block().map_err(|e| self.network_error(e))?;
+                        definitions.push(DictDefinition {
+                            word: fields.next().unwrap_or_else(|| word.to_string()),
+                            database: fields.next().unwrap_or_default(),
+                            description: fields.next().unwrap_or_default(),
+                            text,
+                        });
+                    }
+                    (250, _) => return Ok(definitions),
+                    (code, message) => return Err(self.protocol_error(code, &message)),
+                }
+            }
+        })
+    }
+
+    pub fn match_words(&self, word: &str, strategy: MatchStrategy) -> DictionaryResult<Vec<DictMatch>> {
+        self.session(|connection| {
+            let command = format!("MATCH {} {} {}", self.config.database, strategy.as_str(), quote(word));
+            connection.send(&command).map_err(|e| self.network_error(e))?;
+
+            match connection.status().map_err(|e| self.network_error(e))? {
+                (552, _) => return Ok(vec![]),
+                (152, _) => {}
+                (code, message) => return Err(self.protocol_error(code, &message)),
+            }
+
+            let matches = connection.text_block().map_err(|e| self.network_error(e))?
+                .iter()
+                .filter_map(|line| {
+                    let mut fields = split_quoted(line).into_iter();
+                    Some(DictMatch {
+                        database: fields.next()?,
+                        word: fields.next()?,
+                    })
+                })
+                .collect();
+
+            self.finish(connection)?;
+            Ok(matches)
+        })
+    }
+
+    pub fn show_databases(&self) -> DictionaryResult<Vec<DictDatabase>> {
+        self.session(|connection| {
+            connection.send("SHOW DB").map_err(|e| self.network_error(e))?;
+
+            match connection.status().map_err(|e| self.network_error(e))? {
+                (554, _) => return Ok(vec![]),
+                (110, _) => {}
+                (code, message) => return Err(self.protocol_error(code, &message)),
+            }
+
+            let databases = connection.text_block().map_err(|e| self.network_error(e))?
+                .iter()
+                .filter_map(|line| {
+                    let mut fields = split_quoted(line).into_iter();
+                    Some(DictDatabase {
+                        name: fields.next()?,
+                        description: fields.next().unwrap_or_default(),
+                    })
+                })
+                .collect();
+
+            self.finish(connection)?;
+            Ok(databases)
+        })
+    }
+}
+
+/// A definition's text without the headword line dictd databases start it
+/// with: just the word in WordNet, with pronunciation and part of speech
+/// in GCIDE
+fn body(definition: &DictDefinition) -> &[String] {
+    match definition.text.split_first() {
+        Some((first, rest)) if first.trim().to_lowercase().starts_with(&definition.word.to_lowercase()) => rest,
+        _ => &definition.text,
+    }
+}
+
+/// dictd text is hard-wrapped; join each blank-line-separated paragraph into one line
+fn paragraphs(text: &[String]) -> Vec<String> {
+    text.split(|line| line.trim().is_empty())
+        .map(|lines| lines.iter().map(|l| l.trim()).collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+impl DictionarySource for DictClient {
+    fn name(&self) -> &str {
+        &self.source_name
+    }
+
+    fn is_network(&self) -> bool {
+        true
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        let results = self.define(word.trim())?;
+        let Some(first) = results.first() else { return Ok(None) };
+
+        Ok(Some(Definition {
+            word: first.word.clone(),
+            pronunciation: None,
+            pos: String::new(),
+            definitions: results.iter().flat_map(|d| paragraphs(body(d))).collect(),
+            frequency: None,
+            inflection: None,
+            language: None,
+        }))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut words: Vec<String> = Vec::new();
+        for m in self.match_words(prefix, MatchStrategy::Prefix)? {
+            if words.len() >= limit {
+                break;
+            }
+            // The same headword usually appears once per database
+            if !words.contains(&m.word) {
+                words.push(m.word);
+            }
+        }
+
+        Ok(words)
+    }
+}
+
+/// Tauri command to list the databases a DICT server offers, for picking one in settings
+#[tauri::command]
+pub fn get_dict_databases(host: String, port: Option<u16>) -> Result<Vec<DictDatabase>, String> {
+    let mut config = DictServerConfig::new(host);
+    config.port = port.unwrap_or(DEFAULT_DICT_PORT);
+
+    DictClient::new(config).show_databases().map_err(|e| e.user_message())
+}
+
+/// Tauri command to DEFINE a word on every enabled DICT server, keeping
+/// per-database results apart. `database` overrides each server's setting.
+#[tauri::command]
+pub fn dict_define(word: String, database: Option<String>, app_handle: AppHandle) -> Result<Vec<DictDefinition>, String> {
+    let settings = SettingsManager::new(&app_handle).map_err(|e| e.to_string())?;
+
+    let mut definitions = Vec::new();
+    for server in settings.get_settings().sources.dict_servers.iter().filter(|s| s.enabled) {
+        let client = DictClient::new(server.clone());
+        let database = database.as_deref().unwrap_or(&server.database);
+        match client.define_in(database, &word) {
+            Ok(found) => definitions.extend(found),
+            Err(e) => e.log_error(),
+        }
+    }
+
+    Ok(definitions)
+}
+
+/// Tauri command to run MATCH against every enabled DICT server
+#[tauri::command]
+pub fn dict_match(word: String, strategy: MatchStrategy, app_handle: AppHandle) -> Result<Vec<DictMatch>, String> {
+    let settings = SettingsManager::new(&app_handle).map_err(|e| e.to_string())?;
+
+    let mut matches = Vec::new();
+    for server in settings.get_settings().sources.dict_servers.iter().filter(|s| s.enabled) {
+        match DictClient::new(server.clone()).match_words(&word, strategy) {
+            Ok(found) => matches.extend(found),
+            Err(e) => e.log_error(),
+        }
+    }
+
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phonetic::soundex;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // (database, description, headword, text lines)
+    const ENTRIES: &[(&str, &str, &str, &[&str])] = &[
+        ("wn", "WordNet (r) 3.0", "apple", &["apple", "    n 1: fruit with red or yellow or green skin", "         and sweet to tart crisp whitish flesh"]),
+        ("gcide", "Collaborative International Dictionary", "apple", &["Apple \\Ap\"ple\\, n.", "", "   The fleshy pome of a tree.", ".hidden by dot-stuffing"]),
+        ("wn", "WordNet (r) 3.0", "applesauce", &["applesauce", "    n 1: puree of stewed apples"]),
+        ("wn", "WordNet (r) 3.0", "ice cream", &["ice cream", "    n 1: frozen dessert"]),
+    ];
+
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut previous = row[0];
+            row[0] = i + 1;
+            for (j, cb) in b.iter().enumerate() {
+                let substitution = previous + usize::from(ca != *cb);
+                previous = row[j + 1];
+                row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+            }
+        }
+        row[b.len()]
+    }
+
+    fn serve(stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut out = stream;
+        let mut reply = |text: &str| out.write_all(text.replace('\n', "\r\n").as_bytes()).unwrap();
+
+        reply("220 fake.dictd <auth.mime> <1.2@fake>\n");
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            let args = split_quoted(line.trim_end());
+            line.clear();
+            let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+
+            match args.as_slice() {
+                ["CLIENT", ..] => reply("250 ok\n"),
+                ["QUIT"] => {
+                    reply("221 bye\n");
+                    return;
+                }
+                ["SHOW", "DB"] => reply("110 2 databases present\nwn \"WordNet (r) 3.0\"\ngcide \"Collaborative International Dictionary\"\n.\n250 ok\n"),
+                ["DEFINE", db, word] => {
+                    if !matches!(*db, "*" | "!" | "wn" | "gcide") {
+                        reply("550 invalid database\n");
+                        continue;
+                    }
+                    let mut found: Vec<_> = ENTRIES.iter()
+                        .filter(|(d, _, w, _)| (*db == "*" || *db == "!" || d == db) && w.eq_ignore_ascii_case(word))
+                        .collect();
+                    if *db == "!" {
+                        found.truncate(1);
+                    }
+                    if found.is_empty() {
+                        reply("552 no match\n");
+                        continue;
+                    }
+                    reply(&format!("150 {} definitions retrieved\n", found.len()));
+                    for (d, description, w, text) in found {
+                        reply(&format!("151 \"{}\" {} \"{}\"\n", w, d, description));
+                        for text_line in text.iter() {
+                            let stuffed = if text_line.starts_with('.') { format!(".{}", text_line) } else { text_line.to_string() };
+                            reply(&format!("{}\n", stuffed));
+                        }
+                        reply(".\n");
+                    }
+                    reply("250 ok\n");
+                }
+                ["MATCH", _, strategy, word] => {
+                    let found: Vec<_> = ENTRIES.iter()
+                        .filter(|(_, _, w, _)| match *strategy {
+                            "prefix" => w.starts_with(word),
+                            "exact" => w == word,
+                            "soundex" => soundex(w) == soundex(word),
+                            "lev" => levenshtein(w, word) == 1,
+                            _ => false,
+                        })
+                        .collect();
+                    if !matches!(*strategy, "prefix" | "exact" | "soundex" | "lev") {
+                        reply("551 invalid strategy\n");
+                    } else if found.is_empty() {
+                        reply("552 no match\n");
+                    } else {
+                        reply(&format!("152 {} matches found\n", found.len()));
+                        for (d, _, w, _) in found {
+                            reply(&format!("{} \"{}\"\n", d, w));
+                        }
+                        reply(".\n250 ok\n");
+                    }
+                }
+                _ => reply("500 unknown command\n"),
+            }
+        }
+    }
+
+    fn start_fake_dictd() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                thread::spawn(move || serve(stream));
+            }
+        });
+        port
+    }
+
+    fn client(port: u16, database: &str) -> DictClient {
+        DictClient::new(DictServerConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            database: database.to_string(),
+            enabled: true,
+        })
+    }
+
+    #[test]
+    fn test_define_across_databases() {
+        let port = start_fake_dictd();
+
+        let all = client(port, "*").define("apple").unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].database, "gcide");
+        assert_eq!(all[1].word, "apple");
+        assert_eq!(all[1].text.last().unwrap(), ".hidden by dot-stuffing");
+
+        let wordnet = client(port, "wn").define("apple").unwrap();
+        assert_eq!(wordnet.len(), 1);
+        assert_eq!(wordnet[0].description, "WordNet (r) 3.0");
+
+        assert_eq!(client(port, "!").define("apple").unwrap().len(), 1);
+        assert!(client(port, "*").define("banana").unwrap().is_empty());
+        assert!(client(port, "nope").define("apple").is_err());
+    }
+
+    #[test]
+    fn test_match_and_show_db() {
+        let port = start_fake_dictd();
+        let dict = client(port, "*");
+
+        let prefixed = dict.match_words("apple", MatchStrategy::Prefix).unwrap();
+        assert_eq!(prefixed.len(), 3);
+        assert!(dict.match_words("zzz", MatchStrategy::Prefix).unwrap().is_empty());
+
+        // Fuzzy strategies find the misspelled word, once per database
+        let apple = vec![
+            DictMatch { database: "wn".to_string(), word: "apple".to_string() },
+            DictMatch { database: "gcide".to_string(), word: "apple".to_string() },
+        ];
+        assert_eq!(dict.match_words("apel", MatchStrategy::Soundex).unwrap(), apple);
+        assert_eq!(dict.match_words("aple", MatchStrategy::Lev).unwrap(), apple);
+        assert!(dict.match_words("zzz", MatchStrategy::Lev).unwrap().is_empty());
+
+        let databases = dict.show_databases().unwrap();
+        assert_eq!(databases, vec![
+            DictDatabase { name: "wn".to_string(), description: "WordNet (r) 3.0".to_string() },
+            DictDatabase { name: "gcide".to_string(), description: "Collaborative International Dictionary".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_source_maps_into_definition() {
+        let port = start_fake_dictd();
+        let dict = client(port, "wn");
+
+        let definition = dict.lookup("apple").unwrap().unwrap();
+        assert_eq!(definition.word, "apple");
+        assert_eq!(definition.definitions, vec![
+            "n 1: fruit with red or yellow or green skin and sweet to tart crisp whitish flesh".to_string(),
+        ]);
+
+        // The headword line, pronunciation and all, is dropped from each body
+        let definition = client(port, "*").lookup("apple").unwrap().unwrap();
+        assert_eq!(definition.definitions[1], "The fleshy pome of a tree. .hidden by dot-stuffing");
+
+        // Multi-word headwords are quoted on the wire
+        assert!(dict.lookup("ice cream").unwrap().is_some());
+        assert_eq!(client(port, "*").search("apple", 10).unwrap(), vec!["apple", "applesauce"]);
+    }
+
+    #[test]
+    fn test_unreachable_server_is_network_error() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert!(matches!(client(port, "*").lookup("apple"), Err(DictionaryError::NetworkError { .. })));
+    }
+}
@@ -1,15 +1,79 @@
 use crate::cache::{ThreadSafeCache, Definition};
+use crate::cmudict::{Pronunciation, PronunciationStore, Rhyme, Rhymes};
+use crate::anagram::Anagrams;
 use crate::api_client::DictionaryApiClient;
 use crate::error::{DictionaryError, DictionaryResult};
+use crate::glossary::{Glossary, GlossaryPrecedence};
+use crate::identifier::{identifier_parts, is_identifier, ComponentDefinition, IdentifierLookup};
+use crate::language::{DetectedLanguage, LanguagePair, ENGLISH};
+use crate::morphology::{lemmatizers, Inflection, Lemmatizer};
 use crate::performance::PERF_TRACKER;
-use std::sync::Arc;
+use crate::pattern::{PatternPage, PatternQuery};
+use crate::phrase::{self, PhraseMatch};
+use crate::prefix_index::PrefixIndex;
+use crate::reverse_index::{ReverseIndex, ReverseMatch};
+use crate::source::DictionarySource;
+use crate::tokenizer;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 use tokio::runtime::Handle;
 
+/// Which side the service trusts first on a cache miss. Flipped by the
+/// API health monitor as the server comes and goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceMode {
+    /// API first, with the client's normal retry budget
+    OnlineFirst,
+    /// Local data first; the API is only tried once, as a last resort
+    OfflineFirst,
+}
+
 pub struct DictionaryService {
     cache: ThreadSafeCache,
     api_client: Arc<DictionaryApiClient>,
     runtime_handle: Handle,
+    source_mode: RwLock<SourceMode>,
+    local_sources: RwLock<Vec<Arc<dyn DictionarySource>>>,
+    glossary: RwLock<Option<(Arc<Glossary>, GlossaryPrecedence)>>,
+    pronunciation_store: RwLock<Option<Arc<PronunciationStore>>>,
+    prefix_index: Arc<PrefixIndex>,
+    reverse_index: Arc<ReverseIndex>,
+    api_suggestions: AtomicBool,
+    identifier_capture: AtomicBool,
+    lemmatizers: Vec<Box<dyn Lemmatizer>>,
+    language_pairs: RwLock<Vec<LanguagePair>>,
+}
+
+// How many suggestions the index and each unindexed source contribute to a search
+const LOCAL_SEARCH_LIMIT: usize = 20;
+// "Did you mean" candidates attached to a miss
+const SUGGESTION_LIMIT: usize = 5;
+// Candidate headwords tried for an unknown surface form
+const MAX_LEMMA_CANDIDATES: usize = 4;
+// Longer captures are copied passages, not selections to look up
+const MAX_CAPTURE_CHARS: usize = 300;
+
+/// What a hotkey or clipboard capture was looked up as
+pub struct CaptureLookup {
+    /// The word picked from the capture
+    pub word: String,
+    /// The expression around the word that was looked up instead, if any
+    pub phrase: Option<PhraseMatch>,
+    /// The sentence of the capture the word was read in, if there's more
+    /// to it than the word
+    pub sentence: Option<String>,
+    pub result: DictionaryResult<Definition>,
+}
+
+impl CaptureLookup {
+    /// The text the definition is for: the expression, else the word
+    pub fn headword(&self) -> &str {
+        self.phrase.as_ref().map_or(&self.word, |phrase| &phrase.headword)
+    }
 }
 
 impl DictionaryService {
@@ -21,14 +85,359 @@ impl DictionaryService {
             cache,
             api_client,
             runtime_handle,
+            source_mode: RwLock::new(SourceMode::OnlineFirst),
+            local_sources: RwLock::new(Vec::new()),
+            glossary: RwLock::new(None),
+            pronunciation_store: RwLock::new(None),
+            prefix_index: Arc::new(PrefixIndex::new()),
+            reverse_index: Arc::new(ReverseIndex::new()),
+            api_suggestions: AtomicBool::new(true),
+            identifier_capture: AtomicBool::new(true),
+            lemmatizers: lemmatizers(),
+            language_pairs: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Register a local source. Sources are consulted in registration order.
+    pub fn add_source(&self, source: Arc<dyn DictionarySource>) {
+        if let Ok(mut sources) = self.local_sources.write() {
+            println!("Registered local dictionary source: {}", source.name());
+            sources.push(source);
+        }
+        self.reindex();
+    }
+
+    /// Drop a local source by name, e.g. when the user disables a dictionary
+    pub fn remove_source(&self, name: &str) {
+        if let Ok(mut sources) = self.local_sources.write() {
+            sources.retain(|source| source.name() != name);
+        }
+        self.reindex();
+    }
+
+    fn local_sources(&self) -> Vec<Arc<dyn DictionarySource>> {
+        self.local_sources.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Attach the user's glossary. It is kept apart from the other local
+    /// sources because its place in the lookup order is configurable.
+    pub fn set_glossary(&self, glossary: Arc<Glossary>, precedence: GlossaryPrecedence) {
+        if let Ok(mut current) = self.glossary.write() {
+            *current = Some((glossary, precedence));
+        }
+        self.reindex();
+    }
+
+    /// Attach imported CMU pronunciations, used for rhymes and to fill in
+    /// definitions that come without a pronunciation
+    pub fn set_pronunciations(&self, store: Arc<PronunciationStore>) {
+        if let Ok(mut current) = self.pronunciation_store.write() {
+            *current = Some(store);
+        }
+    }
+
+    fn pronunciation_store(&self) -> DictionaryResult<Arc<PronunciationStore>> {
+        self.pronunciation_store.read().ok()
+            .and_then(|store| store.clone())
+            .ok_or_else(|| DictionaryError::SourceError {
+                source: "cmudict".to_string(),
+                message: "No pronunciation dictionary has been imported".to_string(),
+            })
+    }
+
+    pub fn pronunciations(&self, word: &str) -> DictionaryResult<Vec<Pronunciation>> {
+        self.pronunciation_store()?.lookup(word)
+    }
+
+    /// Perfect and near rhymes for `word`, most frequent first
+    pub fn rhymes(&self, word: &str, limit: usize) -> DictionaryResult<Rhymes> {
+        let Rhymes { mut perfect, mut near } = self.pronunciation_store()?.rhymes(word)?;
+        for rhymes in [&mut perfect, &mut near] {
+            let mut ranked: Vec<(u32, Rhyme)> = rhymes.drain(..)
+                .map(|rhyme| (self.prefix_index.rank(&rhyme.word).unwrap_or(u32::MAX), rhyme))
+                .collect();
+            ranked.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.word.cmp(&y.word)));
+            rhymes.extend(ranked.into_iter().take(limit).map(|(_, rhyme)| rhyme));
+        }
+        Ok(Rhymes { perfect, near })
+    }
+
+    /// Fill in a missing pronunciation from the CMU data, if imported
+    fn with_pronunciation(&self, mut definition: Definition) -> Definition {
+        if definition.pronunciation.is_none() {
+            if let Ok(found) = self.pronunciations(&definition.word) {
+                definition.pronunciation = found.into_iter().next().map(|p| format!("/{}/", p.ipa));
+            }
+        }
+        definition
+    }
+
+    pub fn set_glossary_precedence(&self, precedence: GlossaryPrecedence) {
+        if let Ok(mut current) = self.glossary.write() {
+            if let Some((_, current_precedence)) = current.as_mut() {
+                if *current_precedence != precedence {
+                    println!("Glossary precedence: {:?} -> {:?}", *current_precedence, precedence);
+                    *current_precedence = precedence;
+                    drop(current);
+                    self.clear_cache();
+                }
+            }
+        }
+    }
+
+    /// The glossary, if attached with the given precedence
+    fn glossary(&self, precedence: GlossaryPrecedence) -> Option<Arc<Glossary>> {
+        self.glossary.read().ok()?
+            .as_ref()
+            .filter(|(_, p)| *p == precedence)
+            .map(|(glossary, _)| glossary.clone())
+    }
+
+    fn lookup_glossary(&self, word: &str, precedence: GlossaryPrecedence) -> Option<Definition> {
+        let definition = self.glossary(precedence)?.lookup(word).ok()??;
+        println!("Found '{}' in glossary", word);
+        Some(definition)
+    }
+
+    /// Forget any cached definition of `word`, e.g. after the glossary changes
+    pub fn invalidate(&self, word: &str) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.remove(word);
+        }
+    }
+
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
         }
     }
 
-    /// Look up a word with fallback strategy:
+    /// Rebuild the autocomplete and reverse-search indexes in the
+    /// background from every local source, the glossary and the cache
+    pub fn reindex(&self) {
+        let mut sources: Vec<_> = self.local_sources().into_iter()
+            .filter(|source| !source.is_network())
+            .collect();
+        if let Some((glossary, _)) = self.glossary.read().ok().and_then(|g| g.clone()) {
+            sources.push(glossary);
+        }
+        let cached = self.cache.lock().map(|cache| cache.words()).unwrap_or_default();
+        self.reverse_index.schedule_rebuild(sources.clone());
+        self.prefix_index.schedule_rebuild(sources, cached);
+    }
+
+    /// Make a single new word searchable without a full rebuild
+    pub fn index_word(&self, word: &str) {
+        self.prefix_index.insert(word, None);
+    }
+
+    /// Note a lookup the user made, so it ranks higher in suggestions
+    pub fn record_lookup(&self, word: &str) {
+        self.prefix_index.record_lookup(word);
+    }
+
+    pub fn load_search_history(&self, path: PathBuf) {
+        self.prefix_index.load_history(path);
+    }
+
+    /// The user's preferred translation directions, best first
+    pub fn set_language_pairs(&self, pairs: Vec<LanguagePair>) {
+        if let Ok(mut current) = self.language_pairs.write() {
+            *current = pairs;
+        }
+    }
+
+    /// Whether `search_words` also asks the API while online
+    pub fn set_api_suggestions(&self, enabled: bool) {
+        self.api_suggestions.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether captured code identifiers are split into their words
+    pub fn set_identifier_capture(&self, enabled: bool) {
+        self.identifier_capture.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether a capture that no source or the API defines whole should be
+    /// split as an identifier. Callers look it up whole first, so
+    /// "well-being" and "JavaScript" stay words.
+    pub fn is_identifier_capture(&self, text: &str) -> bool {
+        self.identifier_capture.load(Ordering::Relaxed) && is_identifier(text)
+    }
+
+    /// Look up the most plausible word in a capture, surrounding
+    /// punctuation and all ("naïve." or "“Give up!”"), in the context of
+    /// the rest of it. `None` when there's no word in it or it's too long
+    /// to be a selection.
+    pub fn lookup_capture(&self, captured: &str, language: Option<&str>) -> Option<CaptureLookup> {
+        if captured.trim().chars().count() > MAX_CAPTURE_CHARS {
+            return None;
+        }
+        let word = tokenizer::lookup_token(captured, |word| self.is_known(word))?;
+        let (phrase, result) = self.lookup_in_context(&word, captured, None, language);
+        let sentence = tokenizer::sentence_around(captured, phrase.as_ref().map_or(&word, |phrase| &phrase.phrase));
+        Some(CaptureLookup { word, phrase, sentence, result })
+    }
+
+    /// Look a selection up as the longest known expression around it in
+    /// `context`, falling back to the selection itself
+    pub fn lookup_in_context(
+        &self,
+        selection: &str,
+        context: &str,
+        offset: Option<usize>,
+        language: Option<&str>,
+    ) -> (Option<PhraseMatch>, DictionaryResult<Definition>) {
+        if let Some(phrase) = self.find_phrase(selection, context, offset) {
+            if let Ok(definition) = self.lookup_word_in(&phrase.headword, language) {
+                return (Some(phrase), Ok(definition));
+            }
+        }
+        (None, self.lookup_word_in(selection, language))
+    }
+
+    pub fn api_client(&self) -> Arc<DictionaryApiClient> {
+        self.api_client.clone()
+    }
+
+    pub fn source_mode(&self) -> SourceMode {
+        self.source_mode.read().map(|mode| *mode).unwrap_or(SourceMode::OnlineFirst)
+    }
+
+    pub fn set_source_mode(&self, mode: SourceMode) {
+        if let Ok(mut current) = self.source_mode.write() {
+            if *current != mode {
+                println!("Dictionary source mode: {:?} -> {:?}", *current, mode);
+                *current = mode;
+            }
+        }
+    }
+
+    /// Look up a word as selected. When nothing knows the surface form,
+    /// its candidate headwords are tried next ("ran" -> "run") and the
+    /// definition notes how the two are related. A complete miss carries
+    /// "did you mean" suggestions.
+    pub fn lookup_word(&self, word: &str) -> DictionaryResult<Definition> {
+        self.lookup_word_in(word, None)
+    }
+
+    /// `lookup_word` trying sources for `language` first, e.g. the one
+    /// detected for a capture. Another language than English asks only its
+    /// own sources, skipping the English-only API and the cache (which is
+    /// keyed by spelling alone); when they miss, the word is looked up as
+    /// usual, since the language may have been guessed wrong.
+    pub fn lookup_word_in(&self, word: &str, language: Option<&str>) -> DictionaryResult<Definition> {
+        let language = if is_foreign(language) {
+            let found = match self.lookup_form(word, language, true) {
+                Ok(definition) => Some(definition),
+                Err(error) => self.lookup_lemma(word, &error, language),
+            };
+            if let Some(definition) = found {
+                return Ok(definition);
+            }
+            println!("No {} source knows '{}', looking it up as usual", language.unwrap_or_default(), word);
+            None
+        } else {
+            language
+        };
+
+        let mut error = match self.lookup_form(word, language, true) {
+            Ok(definition) => return Ok(self.with_pronunciation(definition)),
+            Err(error) => error,
+        };
+
+        if let Some(definition) = self.lookup_lemma(word, &error, language) {
+            return Ok(self.with_pronunciation(definition));
+        }
+
+        // A miss everywhere: offer the closest local headwords instead
+        if let DictionaryError::WordNotFound { suggestions, .. } = &mut error {
+            if suggestions.is_empty() {
+                *suggestions = self.suggest(word, SUGGESTION_LIMIT);
+            }
+        }
+        
+        error.log_error();
+        Err(error)
+    }
+
+    /// Try each candidate headword for `word`. Once the API has said it
+    /// doesn't know the surface form it's asked about lemmas too;
+    /// otherwise only offline data is consulted, so an unreachable API
+    /// isn't retried once per candidate. Network sources are never asked
+    /// about guesses.
+    fn lookup_lemma(&self, word: &str, surface_error: &DictionaryError, language: Option<&str>) -> Option<Definition> {
+        let api_reachable = matches!(surface_error, DictionaryError::WordNotFound { .. });
+        let mut candidates: Vec<Inflection> = self.lemmatizers.iter()
+            .filter(|lemmatizer| language.is_none_or(|l| lemmatizer.language() == l))
+            .flat_map(|lemmatizer| lemmatizer.candidates(word))
+            .collect();
+        // Headwords we know locally first; any other may cost an API request
+        candidates.sort_by_key(|c| !self.prefix_index.contains(&c.lemma));
+
+        for mut inflection in candidates.into_iter().take(MAX_LEMMA_CANDIDATES) {
+            let found = if api_reachable {
+                self.lookup_form(&inflection.lemma, language, false)
+            } else {
+                self.lookup_offline(&inflection.lemma, language).ok_or(DictionaryError::WordNotFound {
+                    word: inflection.lemma.clone(),
+                    suggestions: Vec::new(),
+                })
+            };
+
+            match found {
+                Ok(mut definition) => {
+                    inflection.resolve_pos(&definition.pos);
+                    println!("Found '{}' under its headword: {}", word, inflection.note);
+                    definition.inflection = Some(inflection);
+                    if is_foreign(language) {
+                        return Some(definition);
+                    }
+                    // Cached under the surface form so the note survives repeat lookups
+                    return Some(self.cache_local_hit(word, definition));
+                }
+                Err(DictionaryError::WordNotFound { .. }) => {}
+                Err(e) => {
+                    // The API started failing; don't spend more requests on guesses
+                    e.log_error();
+                    break;
+                }
+            }
+        }
+        None
+    }
+
+    /// Everything off the network: override glossary, cache, local sources
+    fn lookup_offline(&self, word: &str, language: Option<&str>) -> Option<Definition> {
+        self.lookup_glossary(word, GlossaryPrecedence::Override)
+            .or_else(|| if is_foreign(language) { None } else { self.cache.lock().ok()?.get(word) })
+            .or_else(|| self.lookup_local(word, language, false))
+    }
+
+    /// Look up one exact form with fallback strategy:
+    /// 0. A glossary entry with override precedence beats everything
     /// 1. Check memory cache first (instant)
-    /// 2. If not found, fetch from API
+    /// 2. If not found, ask local sources and the API, in the order given by
+    ///    the source mode (the API gets a single attempt when offline-first)
     /// 3. Cache the result for future lookups
-    pub fn lookup_word(&self, word: &str) -> DictionaryResult<Definition> {
+    ///
+    /// A language other than English only asks that language's local sources.
+    /// Without `network`, local sources that go over the network are skipped.
+    fn lookup_form(&self, word: &str, language: Option<&str>, network: bool) -> DictionaryResult<Definition> {
+        if let Some(definition) = self.lookup_glossary(word, GlossaryPrecedence::Override) {
+            PERF_TRACKER.mark("backend_complete");
+            PERF_TRACKER.measure_backend(true, None);
+            return Ok(definition);
+        }
+
+        if is_foreign(language) {
+            PERF_TRACKER.mark("backend_complete");
+            PERF_TRACKER.measure_backend(false, None);
+            return self.lookup_local(word, language, network).ok_or_else(|| DictionaryError::WordNotFound {
+                word: word.to_string(),
+                suggestions: Vec::new(),
+            });
+        }
+
         PERF_TRACKER.mark("cache_lookup_start");
         
         // First, check the cache
@@ -57,20 +466,36 @@ impl DictionaryService {
         
         PERF_TRACKER.mark("cache_lookup_end");
 
+        let offline_first = self.source_mode() == SourceMode::OfflineFirst;
+        
+        // Offline-first: local data answers before we touch the network
+        if offline_first {
+            if let Some(definition) = self.lookup_local(word, language, network) {
+                return Ok(self.cache_local_hit(word, definition));
+            }
+        }
+
         // Cache miss - try to fetch from API
         println!("Cache miss for word: {}. Fetching from API...", word);
         
         // Use blocking to run async code in sync context
         let api_client = self.api_client.clone();
         let word_str = word.to_string();
+        let max_retries = match self.source_mode() {
+            SourceMode::OnlineFirst => None,
+            SourceMode::OfflineFirst => Some(0),
+        };
         
         let api_start = Instant::now();
         let result = self.runtime_handle.block_on(async move {
-            api_client.get_definition(&word_str).await
+            match max_retries {
+                Some(retries) => api_client.get_definition_with_retries(&word_str, retries).await,
+                None => api_client.get_definition(&word_str).await,
+            }
         });
         let api_duration = api_start.elapsed();
 
-        match result {
+        let error = match result {
             Ok(Some(api_def)) => {
                 // Convert API definition to our format
                 let mut definition: Definition = api_def.into();
@@ -85,28 +510,238 @@ impl DictionaryService {
                         eprintln!("Warning: Failed to cache word '{}': {}", word, e);
                     }
                 }
+                self.index_word(word);
                 
                 println!("Successfully fetched and cached word: {}", word);
                 PERF_TRACKER.mark("backend_complete");
                 PERF_TRACKER.measure_backend(false, Some(api_duration));
-                Ok(definition)
+                return Ok(definition);
             },
-            Ok(None) => {
-                Err(DictionaryError::WordNotFound {
-                    word: word.to_string(),
-                })
+            Ok(None) => DictionaryError::WordNotFound {
+                word: word.to_string(),
+                suggestions: Vec::new(),
+            },
+            Err(e) => e,
+        };
+        
+        // Online-first: local data is the fallback when the API can't answer
+        if !offline_first {
+            if let Some(definition) = self.lookup_local(word, language, network) {
+                return Ok(self.cache_local_hit(word, definition));
+            }
+        }
+        
+        Err(error)
+    }
+
+    /// The longest known expression around a selection, e.g. "in spite of"
+    /// for "spite" taken from "in spite of the rain"
+    pub fn find_phrase(&self, selection: &str, context: &str, offset: Option<usize>) -> Option<PhraseMatch> {
+        phrase::longest_match(
+            selection,
+            context,
+            offset,
+            |candidate| self.prefix_index.contains(candidate),
+            |word| self.lemmatizers.iter()
+                .flat_map(|lemmatizer| lemmatizer.candidates(word))
+                .map(|inflection| inflection.lemma)
+                .collect(),
+        )
+    }
+
+    /// Words whose definitions best match a description of their meaning
+    pub fn reverse_search(&self, query: &str, limit: usize) -> Vec<ReverseMatch> {
+        self.reverse_index.search(query, limit)
+    }
+
+    /// Crossword-style search over local headwords
+    pub fn pattern_search(&self, query: &PatternQuery) -> DictionaryResult<PatternPage> {
+        let (matcher, filter) = query.compile()?;
+        let (words, total) = self.prefix_index.pattern_search(&matcher, &filter, query.offset, query.page_size());
+        Ok(PatternPage { words, total, offset: query.offset })
+    }
+
+    /// Local headwords that sound like `word`
+    pub fn sounds_like(&self, word: &str, limit: usize) -> Vec<String> {
+        self.prefix_index.sounds_like(word, limit)
+    }
+
+    /// Anagrams and sub-anagrams of `letters` among local headwords
+    pub fn anagrams(&self, letters: &str, min_length: usize, limit: usize) -> Anagrams {
+        self.prefix_index.anagrams(letters, min_length, limit)
+    }
+
+    /// A translation of `word` from bilingual packs. Without a `target`,
+    /// the preferred language pairs for the source language (English if
+    /// not given) are tried in order. Like definitions, an unknown surface
+    /// form falls back to its headwords.
+    pub fn translate(&self, word: &str, source: Option<&str>, target: Option<&str>) -> Option<Definition> {
+        let source = source.unwrap_or(ENGLISH);
+        let targets: Vec<String> = match target {
+            Some(target) => vec![target.to_string()],
+            None => self.language_pairs.read().ok()?.iter()
+                .filter(|pair| pair.source == source)
+                .map(|pair| pair.target.clone())
+                .collect(),
+        };
+
+        let packs: Vec<Arc<dyn DictionarySource>> = targets.iter()
+            .flat_map(|target| self.local_sources().into_iter()
+                .filter(move |s| s.language() == Some(source) && s.target_language() == Some(target.as_str())))
+            .collect();
+        if packs.is_empty() {
+            return None;
+        }
+
+        let lemmas = self.lemmatizers.iter()
+            .filter(|lemmatizer| lemmatizer.language() == source)
+            .flat_map(|lemmatizer| lemmatizer.candidates(word))
+            .take(MAX_LEMMA_CANDIDATES);
+        let forms = std::iter::once((word.to_string(), None))
+            .chain(lemmas.map(|inflection| (inflection.lemma.clone(), Some(inflection))));
+
+        for (form, inflection) in forms {
+            for pack in &packs {
+                match pack.lookup(&form) {
+                    Ok(Some(mut translation)) => {
+                        println!("Translated '{}' with {}", word, pack.name());
+                        if let Some(mut inflection) = inflection {
+                            inflection.resolve_pos(&translation.pos);
+                            translation.inflection = Some(inflection);
+                        }
+                        return Some(translation);
+                    }
+                    Ok(None) => {}
+                    Err(e) => e.log_error(),
+                }
+            }
+        }
+        None
+    }
+
+    /// Define each word of a code identifier, abbreviations expanded
+    /// ("reqCtx" -> request, context). A word repeated in the identifier
+    /// is only looked up once.
+    pub fn lookup_identifier(&self, identifier: &str) -> IdentifierLookup {
+        let parts = identifier_parts(identifier);
+        let mut components: Vec<ComponentDefinition> = Vec::new();
+        for part in &parts {
+            let Some(word) = &part.word else { continue };
+            if components.iter().any(|c| c.word == *word) {
+                continue;
+            }
+            let (definition, error) = match self.lookup_word(word) {
+                Ok(definition) => (Some(definition), None),
+                Err(e) => (None, Some(e.user_message())),
+            };
+            components.push(ComponentDefinition {
+                part: part.part.clone(),
+                word: word.clone(),
+                definition,
+                error,
+            });
+        }
+
+        IdentifierLookup {
+            identifier: identifier.to_string(),
+            parts,
+            components,
+        }
+    }
+
+    /// The language a capture should be looked up in: the detected one,
+    /// when detection is conclusive and a monolingual source for it is
+    /// installed. Anything else goes the usual English way.
+    pub fn capture_language<'a>(&self, detected: Option<&'a DetectedLanguage>) -> Option<&'a str> {
+        let language = detected?.routing_language()?;
+        self.local_sources().iter()
+            .any(|source| source.target_language().is_none() && source.language() == Some(language))
+            .then_some(language)
+    }
+
+    /// Whether `word` is a local headword, directly or through one of its
+    /// candidate headwords ("gave up" via "give up")
+    pub fn is_known(&self, word: &str) -> bool {
+        self.prefix_index.contains(word) ||
+        self.lemmatizers.iter()
+            .flat_map(|lemmatizer| lemmatizer.candidates(word))
+            .any(|c| self.prefix_index.contains(&c.lemma))
+    }
+
+    /// Local headwords within a few typos of `word`, best first
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        self.prefix_index.fuzzy_search(word, limit, false)
+    }
+
+    /// Ask each monolingual local source in turn; the first one that knows
+    /// the word wins. With a `language`, sources declaring another one are
+    /// passed over, as are network sources without `network`. A fallback
+    /// glossary is asked last.
+    fn lookup_local(&self, word: &str, language: Option<&str>, network: bool) -> Option<Definition> {
+        let sources = self.local_sources().into_iter()
+            .filter(|source| network || !source.is_network())
+            .filter(|source| source.target_language().is_none())
+            .filter(|source| language.is_none_or(|l| source.language().is_none_or(|s| s == l)));
+        for source in sources {
+            match source.lookup(word) {
+                Ok(Some(mut definition)) => {
+                    println!("Found '{}' in local source: {}", word, source.name());
+                    definition.language = definition.language.or_else(|| source.language().map(str::to_string));
+                    return Some(definition);
+                }
+                Ok(None) => {}
+                Err(e) => e.log_error(),
+            }
+        }
+        self.lookup_glossary(word, GlossaryPrecedence::Fallback)
+    }
+
+    fn cache_local_hit(&self, word: &str, definition: Definition) -> Definition {
+        match self.cache.lock() {
+            Ok(mut cache) => {
+                cache.insert(word.to_string(), definition.clone());
             },
             Err(e) => {
-                e.log_error();
-                Err(e)
+                eprintln!("Warning: Failed to cache word '{}': {}", word, e);
             }
         }
+        
+        PERF_TRACKER.mark("backend_complete");
+        PERF_TRACKER.measure_backend(false, None);
+        definition
     }
 
-    /// Search for words with a given prefix
-    pub fn search_words(&self, query: &str) -> DictionaryResult<Vec<String>> {
-        // For now, we'll just use the API search
-        // In a future enhancement, we could also search cached words
+    /// Search for words with a given prefix. Local words come from the
+    /// prefix index, ranked by lookup history and frequency; the API is
+    /// only asked while we're online-first and API suggestions are on.
+    /// Glossary words lead the results under override precedence. With
+    /// `typo_tolerant`, words whose start is a typo or two away follow the
+    /// exact prefix matches.
+    pub fn search_words(&self, query: &str, typo_tolerant: bool) -> DictionaryResult<Vec<String>> {
+        let leading = self.glossary(GlossaryPrecedence::Override)
+            .and_then(|glossary| glossary.search(query, LOCAL_SEARCH_LIMIT).ok())
+            .unwrap_or_default();
+
+        // Sources the index doesn't cover, e.g. DICT servers, or everything
+        // until the first build finishes, are asked directly
+        let mut local_results = self.prefix_index.search(query, LOCAL_SEARCH_LIMIT);
+        let mut sources = self.local_sources();
+        if let Some(glossary) = self.glossary(GlossaryPrecedence::Fallback) {
+            sources.push(glossary);
+        }
+        for source in sources.iter().filter(|s| !self.prefix_index.covers(s.name())) {
+            match source.search(query, LOCAL_SEARCH_LIMIT) {
+                Ok(words) => local_results.extend(words),
+                Err(e) => e.log_error(),
+            }
+        }
+        if typo_tolerant {
+            local_results.extend(self.prefix_index.fuzzy_search(query, LOCAL_SEARCH_LIMIT, true));
+        }
+        
+        if self.source_mode() == SourceMode::OfflineFirst || !self.api_suggestions.load(Ordering::Relaxed) {
+            return Ok(merge_unique(leading, local_results));
+        }
         
         let api_client = self.api_client.clone();
         let query_str = query.to_string();
@@ -117,15 +752,300 @@ impl DictionaryService {
 
         match result {
             Ok(results) => {
-                Ok(results.into_iter()
+                let api_results = results.into_iter()
                     .map(|r| r.word)
-                    .collect())
+                    .collect();
+                Ok(merge_unique(leading, merge_unique(local_results, api_results)))
             },
             Err(e) => {
                 e.log_error();
-                // For search, we're more forgiving - return empty results instead of error
-                Ok(vec![])
+                // For search, we're more forgiving - fall back to whatever local sources found
+                Ok(merge_unique(leading, local_results))
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Whether lookups in `language` must bypass the English API and cache
+fn is_foreign(language: Option<&str>) -> bool {
+    language.is_some_and(|l| l != ENGLISH)
+}
+
+/// Concatenate two result lists, dropping case-insensitive duplicates
+fn merge_unique(first: Vec<String>, second: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    first.into_iter()
+        .chain(second)
+        .filter(|w| seen.insert(w.to_lowercase()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::create_cache;
+    use crate::glossary::GlossaryEntry;
+    use crate::language::detect_language;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const DEFINITION_BODY: &str = r#"{"success":true,"data":{"rank":1,"pos":"n","frequency":10,"definitions":["a test"],"pronunciation":null,"examples":null},"timestamp":0}"#;
+    const NOT_FOUND_BODY: &str = r#"{"success":false,"data":null,"error":"Word not found","timestamp":0}"#;
+
+    /// API stand-in answering every request with a definition
+    async fn spawn_api() -> (String, Arc<AtomicUsize>) {
+        spawn_api_knowing(None).await
+    }
+
+    /// API stand-in defining only `known` words, or every word for `None`
+    async fn spawn_api_knowing(known: Option<&'static [&'static str]>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let path = request.split_whitespace().nth(1).unwrap_or("");
+                    let word = urlencoding::decode(path.rsplit('/').next().unwrap_or("")).unwrap_or_default().to_string();
+                    let body = if known.is_none_or(|known| known.contains(&word.to_lowercase().as_str())) {
+                        DEFINITION_BODY
+                    } else {
+                        NOT_FOUND_BODY
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        (format!("http://{}", addr), hits)
+    }
+
+    /// A French source that knows no words
+    struct EmptyFrench;
+
+    impl DictionarySource for EmptyFrench {
+        fn name(&self) -> &str {
+            "empty-fr"
+        }
+
+        fn language(&self) -> Option<&str> {
+            Some("fr")
+        }
+
+        fn lookup(&self, _word: &str) -> DictionaryResult<Option<Definition>> {
+            Ok(None)
+        }
+
+        fn search(&self, _prefix: &str, _limit: usize) -> DictionaryResult<Vec<String>> {
+            Ok(vec![])
+        }
+    }
+
+    /// A network source that knows no words and records what it was asked
+    #[derive(Default)]
+    struct RecordingServer {
+        asked: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl DictionarySource for RecordingServer {
+        fn name(&self) -> &str {
+            "dict-server"
+        }
+
+        fn is_network(&self) -> bool {
+            true
+        }
+
+        fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+            self.asked.lock().unwrap().push(word.to_string());
+            Ok(None)
+        }
+
+        fn search(&self, _prefix: &str, _limit: usize) -> DictionaryResult<Vec<String>> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn test_network_sources_skip_headword_guesses() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, _) = runtime.block_on(spawn_api_knowing(Some(&["walk"])));
+        let service = DictionaryService::new(create_cache(100), url);
+        let server = Arc::new(RecordingServer::default());
+        service.add_source(server.clone());
+
+        let definition = service.lookup_word("walked").unwrap();
+        assert!(definition.inflection.is_some());
+        // Asked about the selection itself, never about "walk" and other guesses
+        assert_eq!(*server.asked.lock().unwrap(), vec!["walked".to_string()]);
+    }
+
+    #[test]
+    fn test_captures_reach_the_api() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, hits) = runtime.block_on(spawn_api());
+        let service = DictionaryService::new(create_cache(100), url);
+
+        // English words that look foreign on n-grams alone go to the API
+        for word in ["table", "station", "experience"] {
+            let detected = detect_language(word);
+            let routed = service.capture_language(detected.as_ref());
+            assert_eq!(routed, None, "{}", word);
+            assert_eq!(service.lookup_word_in(word, routed).unwrap().definitions, vec!["a test"]);
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Without a French source, French isn't routed at all
+        let detected = detect_language("où est la gare");
+        assert_eq!(service.capture_language(detected.as_ref()), None);
+
+        // With one that misses, the lookup falls back to the usual path
+        service.add_source(Arc::new(EmptyFrench));
+        let routed = service.capture_language(detected.as_ref());
+        assert_eq!(routed, Some("fr"));
+        assert!(service.lookup_word_in("où est la gare", routed).is_ok());
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+    }
+
+    /// A source that knows one word
+    struct OneWord(&'static str);
+
+    impl DictionarySource for OneWord {
+        fn name(&self) -> &str {
+            "one-word"
+        }
+
+        fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+            Ok(word.eq_ignore_ascii_case(self.0).then(|| Definition {
+                word: self.0.to_string(),
+                pronunciation: None,
+                pos: "noun".to_string(),
+                definitions: vec!["from the source".to_string()],
+                frequency: None,
+                inflection: None,
+                language: None,
+            }))
+        }
+
+        fn search(&self, _prefix: &str, _limit: usize) -> DictionaryResult<Vec<String>> {
+            Ok(vec![])
+        }
+    }
+
+    fn glossary_with(words: &[&str]) -> Arc<Glossary> {
+        let glossary = Arc::new(Glossary::load(None));
+        for word in words {
+            glossary.create(GlossaryEntry {
+                word: word.to_string(),
+                pos: String::new(),
+                definitions: vec!["from the glossary".to_string()],
+                pronunciation: None,
+                updated_at: 0,
+            }).unwrap();
+        }
+        glossary
+    }
+
+    #[test]
+    fn test_glossary_overrides_every_source() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, hits) = runtime.block_on(spawn_api_knowing(Some(&["bank"])));
+        let service = DictionaryService::new(create_cache(100), url);
+        service.add_source(Arc::new(OneWord("tome")));
+        service.set_glossary(glossary_with(&["bank", "tome"]), GlossaryPrecedence::Override);
+
+        for word in ["bank", "tome"] {
+            assert_eq!(service.lookup_word(word).unwrap().definitions, vec!["from the glossary"], "{}", word);
+        }
+        // Not even the API is asked
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_glossary_fallback_fills_gaps() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, _) = runtime.block_on(spawn_api_knowing(Some(&["bank"])));
+        let service = DictionaryService::new(create_cache(100), url);
+        service.add_source(Arc::new(OneWord("tome")));
+        service.set_glossary(glossary_with(&["bank", "tome", "zeugma"]), GlossaryPrecedence::Fallback);
+
+        assert_eq!(service.lookup_word("bank").unwrap().definitions, vec!["a test"]);
+        assert_eq!(service.lookup_word("tome").unwrap().definitions, vec!["from the source"]);
+        assert_eq!(service.lookup_word("zeugma").unwrap().definitions, vec!["from the glossary"]);
+
+        // Switching precedence drops answers cached under the old order
+        service.set_glossary_precedence(GlossaryPrecedence::Override);
+        assert_eq!(service.lookup_word("bank").unwrap().definitions, vec!["from the glossary"]);
+        assert_eq!(service.lookup_word("tome").unwrap().definitions, vec!["from the glossary"]);
+    }
+
+    #[test]
+    fn test_punctuated_captures() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, _) = runtime.block_on(spawn_api_knowing(Some(&["naïve", "give up", "in spite of"])));
+        let service = DictionaryService::new(create_cache(100), url);
+        for word in ["give up", "spite", "in spite of"] {
+            service.index_word(word);
+        }
+
+        let capture = service.lookup_capture("naïve.", None).unwrap();
+        assert_eq!(capture.word, "naïve");
+        assert_eq!(capture.sentence, None);
+        assert!(capture.result.is_ok());
+
+        let capture = service.lookup_capture("“Give up!”", None).unwrap();
+        assert_eq!(capture.word, "Give up");
+        assert!(capture.result.is_ok());
+
+        // A word picked from a longer capture is read in the rest of it
+        let capture = service.lookup_capture("It poured. He came in spite of the rain.", None).unwrap();
+        assert_eq!(capture.word, "spite");
+        assert_eq!(capture.headword(), "in spite of");
+        assert_eq!(capture.sentence.as_deref(), Some("He came in spite of the rain."));
+        assert_eq!(capture.phrase.unwrap().phrase, "in spite of");
+        assert!(capture.result.is_ok());
+
+        assert!(service.lookup_capture("42 + 7", None).is_none());
+        assert!(service.lookup_capture(&"word ".repeat(100), None).is_none());
+    }
+
+    #[test]
+    fn test_identifier_capture_after_whole_lookup() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (url, _) = runtime.block_on(spawn_api_knowing(Some(&["well-being", "co-operate", "mother-in-law", "x-ray", "iphone", "javascript", "user", "name"])));
+        let service = DictionaryService::new(create_cache(100), url);
+
+        // Known whole, API included: these never get split
+        for word in ["well-being", "co-operate", "mother-in-law", "x-ray", "iPhone", "JavaScript"] {
+            assert!(service.lookup_word(word).is_ok(), "{}", word);
+        }
+
+        // Nothing defines the whole, so it's split into its words
+        assert!(service.lookup_word("getUserName").is_err());
+        assert!(service.is_identifier_capture("getUserName"));
+        let lookup = service.lookup_identifier("getUserName");
+        let defined: Vec<&str> = lookup.components.iter()
+            .filter(|c| c.definition.is_some())
+            .map(|c| c.word.as_str())
+            .collect();
+        assert_eq!(defined, vec!["user", "name"]);
+
+        service.set_identifier_capture(false);
+        assert!(!service.is_identifier_capture("getUserName"));
+    }
+}
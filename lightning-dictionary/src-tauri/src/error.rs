@@ -16,6 +16,9 @@ pub enum DictionaryError {
     },
     WordNotFound {
         word: String,
+        /// Close local headwords, best first
+        #[serde(default)]
+        suggestions: Vec<String>,
     },
     ServiceUnavailable {
         service: String,
@@ -24,6 +27,10 @@ pub enum DictionaryError {
     InvalidInput {
         message: String,
     },
+    SourceError {
+        source: String,
+        message: String,
+    },
 }
 
 impl fmt::Display for DictionaryError {
@@ -46,7 +53,7 @@ impl fmt::Display for DictionaryError {
             DictionaryError::CacheError { message } => {
                 write!(f, "Cache error: {}", message)
             }
-            DictionaryError::WordNotFound { word } => {
+            DictionaryError::WordNotFound { word, .. } => {
                 write!(f, "Word '{}' not found in dictionary", word)
             }
             DictionaryError::ServiceUnavailable { service, retry_after } => {
@@ -59,6 +66,9 @@ impl fmt::Display for DictionaryError {
             DictionaryError::InvalidInput { message } => {
                 write!(f, "Invalid input: {}", message)
             }
+            DictionaryError::SourceError { source, message } => {
+                write!(f, "Local source '{}' error: {}", source, message)
+            }
         }
     }
 }
@@ -87,8 +97,12 @@ impl DictionaryError {
             DictionaryError::CacheError { .. } => {
                 "Error accessing local cache. The app may run slower than usual.".to_string()
             }
-            DictionaryError::WordNotFound { word } => {
-                format!("'{}' not found in dictionary", word)
+            DictionaryError::WordNotFound { word, suggestions } => {
+                if suggestions.is_empty() {
+                    format!("'{}' not found in dictionary", word)
+                } else {
+                    format!("'{}' not found in dictionary. Did you mean: {}?", word, suggestions.join(", "))
+                }
             }
             DictionaryError::ServiceUnavailable { .. } => {
                 "Dictionary service is temporarily unavailable. Please try again later.".to_string()
@@ -96,6 +110,17 @@ impl DictionaryError {
             DictionaryError::InvalidInput { .. } => {
                 "Please enter a valid word to look up.".to_string()
             }
+            DictionaryError::SourceError { source, .. } => {
+                format!("Unable to read the '{}' dictionary. Other sources are still available.", source)
+            }
+        }
+    }
+
+    /// "Did you mean" candidates, for errors that carry them
+    pub fn suggestions(&self) -> &[String] {
+        match self {
+            DictionaryError::WordNotFound { suggestions, .. } => suggestions,
+            _ => &[],
         }
     }
 
@@ -114,7 +139,8 @@ impl DictionaryError {
             DictionaryError::ServiceUnavailable { .. } => {
                 eprintln!("[ERROR] {}", self);
             }
-            DictionaryError::CacheError { .. } => {
+            DictionaryError::CacheError { .. } |
+            DictionaryError::SourceError { .. } => {
                 eprintln!("[WARN] {}", self);
             }
             DictionaryError::WordNotFound { .. } |
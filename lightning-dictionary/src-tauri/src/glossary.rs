@@ -0,0 +1,463 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::source::DictionarySource;
+
+/// Where glossary entries sit relative to the other sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlossaryPrecedence {
+    /// Checked before the cache and every other source
+    #[default]
+    Override,
+    /// Only used when no other source knows the word
+    Fallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlossaryFormat {
+    Json,
+    Csv,
+}
+
+impl GlossaryFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(GlossaryFormat::Json),
+            "csv" => Some(GlossaryFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub word: String,
+    #[serde(default)]
+    pub pos: String,
+    pub definitions: Vec<String>,
+    #[serde(default)]
+    pub pronunciation: Option<String>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl GlossaryEntry {
+    fn to_definition(&self) -> Definition {
+        Definition {
+            word: self.word.clone(),
+            pronunciation: self.pronunciation.clone(),
+            pos: self.pos.clone(),
+            definitions: self.definitions.clone(),
+            frequency: None,
+            inflection: None,
+            language: None,
+        }
+    }
+
+    fn validate(mut self) -> DictionaryResult<Self> {
+        self.word = self.word.trim().to_string();
+        self.definitions = self.definitions.into_iter()
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        if self.word.is_empty() {
+            return Err(DictionaryError::InvalidInput { message: "Glossary word cannot be empty".to_string() });
+        }
+        if self.definitions.is_empty() {
+            return Err(DictionaryError::InvalidInput {
+                message: format!("Glossary entry '{}' needs at least one definition", self.word),
+            });
+        }
+
+        self.updated_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Ok(self)
+    }
+}
+
+// One row per definition; rows sharing a word are merged on import
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    word: String,
+    #[serde(default)]
+    pos: String,
+    #[serde(default)]
+    pronunciation: Option<String>,
+    #[serde(alias = "definitions")]
+    definition: String,
+}
+
+/// The user's own words and definition overrides, kept in
+/// `glossary.json` in the app data dir and rewritten on every change.
+pub struct Glossary {
+    path: Option<PathBuf>,
+    // Keyed by lowercased word
+    entries: RwLock<BTreeMap<String, GlossaryEntry>>,
+}
+
+impl Glossary {
+    /// Load from `path`; a missing or unreadable file starts an empty glossary.
+    /// `None` keeps the glossary in memory only.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path.as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p).map(|json| serde_json::from_str::<Vec<GlossaryEntry>>(&json)) {
+                Ok(Ok(entries)) => Some(entries),
+                Ok(Err(e)) => {
+                    eprintln!("Failed to parse glossary {}: {}", p.display(), e);
+                    None
+                }
+                Err(e) => {
+                    eprintln!("Failed to read glossary {}: {}", p.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: RwLock::new(entries.into_iter().map(|e| (e.word.to_lowercase(), e)).collect()),
+        }
+    }
+
+    fn read(&self) -> DictionaryResult<std::sync::RwLockReadGuard<'_, BTreeMap<String, GlossaryEntry>>> {
+        self.entries.read().map_err(|e| glossary_error(&format!("Failed to acquire glossary lock: {}", e)))
+    }
+
+    /// Apply `change` and persist the result; nothing is kept if saving fails
+    fn modify<T>(&self, change: impl FnOnce(&mut BTreeMap<String, GlossaryEntry>) -> DictionaryResult<T>) -> DictionaryResult<T> {
+        let mut entries = self.entries.write()
+            .map_err(|e| glossary_error(&format!("Failed to acquire glossary lock: {}", e)))?;
+
+        let mut updated = entries.clone();
+        let result = change(&mut updated)?;
+
+        if let Some(path) = &self.path {
+            let json = serde_json::to_string_pretty(&updated.values().collect::<Vec<_>>())
+                .map_err(|e| glossary_error(&e.to_string()))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| glossary_error(&e.to_string()))?;
+            }
+            // Write then rename so a crash never leaves half a glossary
+            let temp = path.with_extension("json.tmp");
+            fs::write(&temp, json)
+                .and_then(|_| fs::rename(&temp, path))
+                .map_err(|e| glossary_error(&format!("Failed to save {}: {}", path.display(), e)))?;
+        }
+
+        *entries = updated;
+        Ok(result)
+    }
+
+    pub fn list(&self) -> DictionaryResult<Vec<GlossaryEntry>> {
+        Ok(self.read()?.values().cloned().collect())
+    }
+
+    pub fn get(&self, word: &str) -> Option<GlossaryEntry> {
+        self.read().ok()?.get(&word.trim().to_lowercase()).cloned()
+    }
+
+    pub fn create(&self, entry: GlossaryEntry) -> DictionaryResult<GlossaryEntry> {
+        let entry = entry.validate()?;
+        self.modify(|entries| {
+            let key = entry.word.to_lowercase();
+            if entries.contains_key(&key) {
+                return Err(DictionaryError::InvalidInput {
+                    message: format!("'{}' is already in the glossary", entry.word),
+                });
+            }
+            entries.insert(key, entry.clone());
+            Ok(entry)
+        })
+    }
+
+    /// Replace the entry for `word`; `entry.word` may differ, which renames it
+    pub fn update(&self, word: &str, entry: GlossaryEntry) -> DictionaryResult<GlossaryEntry> {
+        let entry = entry.validate()?;
+        self.modify(|entries| {
+            if entries.remove(&word.trim().to_lowercase()).is_none() {
+                return Err(DictionaryError::WordNotFound { word: word.to_string(), suggestions: Vec::new() });
+            }
+            entries.insert(entry.word.to_lowercase(), entry.clone());
+            Ok(entry)
+        })
+    }
+
+    pub fn delete(&self, word: &str) -> DictionaryResult<bool> {
+        self.modify(|entries| Ok(entries.remove(&word.trim().to_lowercase()).is_some()))
+    }
+
+    /// Parse `contents` and add its entries, overwriting existing words.
+    /// With `replace`, the current glossary is discarded first.
+    pub fn import(&self, contents: &str, format: GlossaryFormat, replace: bool) -> DictionaryResult<Vec<String>> {
+        let imported = match format {
+            GlossaryFormat::Json => serde_json::from_str::<Vec<GlossaryEntry>>(contents)
+                .map_err(|e| DictionaryError::InvalidInput { message: format!("Invalid glossary JSON: {}", e) })?,
+            GlossaryFormat::Csv => parse_csv(contents)?,
+        };
+        let imported = imported.into_iter()
+            .map(GlossaryEntry::validate)
+            .collect::<DictionaryResult<Vec<_>>>()?;
+
+        self.modify(|entries| {
+            if replace {
+                entries.clear();
+            }
+            Ok(imported.into_iter()
+                .map(|entry| {
+                    let word = entry.word.clone();
+                    entries.insert(word.to_lowercase(), entry);
+                    word
+                })
+                .collect())
+        })
+    }
+
+    pub fn export(&self, format: GlossaryFormat) -> DictionaryResult<String> {
+        let entries = self.list()?;
+        match format {
+            GlossaryFormat::Json => serde_json::to_string_pretty(&entries).map_err(|e| glossary_error(&e.to_string())),
+            GlossaryFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for entry in &entries {
+                    for definition in &entry.definitions {
+                        writer.serialize(CsvRow {
+                            word: entry.word.clone(),
+                            pos: entry.pos.clone(),
+                            pronunciation: entry.pronunciation.clone(),
+                            definition: definition.clone(),
+                        }).map_err(|e| glossary_error(&e.to_string()))?;
+                    }
+                }
+                let bytes = writer.into_inner().map_err(|e| glossary_error(&e.to_string()))?;
+                String::from_utf8(bytes).map_err(|e| glossary_error(&e.to_string()))
+            }
+        }
+    }
+}
+
+impl DictionarySource for Glossary {
+    fn name(&self) -> &str {
+        "glossary"
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        Ok(self.get(word).map(|entry| entry.to_definition()))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(self.read()?
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(_, entry)| entry.word.clone())
+            .collect())
+    }
+
+    fn headwords(&self, visit: &mut dyn FnMut(&str, Option<u32>)) -> DictionaryResult<bool> {
+        for entry in self.read()?.values() {
+            visit(&entry.word, None);
+        }
+        Ok(true)
+    }
+
+    fn documents(&self, visit: &mut dyn FnMut(&str, &[String])) -> DictionaryResult<bool> {
+        for entry in self.read()?.values() {
+            visit(&entry.word, &entry.definitions);
+        }
+        Ok(true)
+    }
+}
+
+fn parse_csv(contents: &str) -> DictionaryResult<Vec<GlossaryEntry>> {
+    let mut merged: Vec<GlossaryEntry> = Vec::new();
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(contents.as_bytes());
+
+    for (i, row) in reader.deserialize::<CsvRow>().enumerate() {
+        // Header is line 1
+        let row = row.map_err(|e| DictionaryError::InvalidInput {
+            message: format!("Invalid glossary CSV at row {}: {}", i + 2, e),
+        })?;
+
+        match merged.iter_mut().find(|e| e.word.eq_ignore_ascii_case(&row.word)) {
+            Some(entry) => entry.definitions.push(row.definition),
+            None => merged.push(GlossaryEntry {
+                word: row.word,
+                pos: row.pos,
+                definitions: vec![row.definition],
+                pronunciation: row.pronunciation.filter(|p| !p.is_empty()),
+                updated_at: 0,
+            }),
+        }
+    }
+
+    Ok(merged)
+}
+
+fn glossary_error(message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "glossary".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Tauri command to list every glossary entry
+#[tauri::command]
+pub fn get_glossary(glossary: State<'_, Arc<Glossary>>) -> Result<Vec<GlossaryEntry>, String> {
+    glossary.list().map_err(|e| e.user_message())
+}
+
+/// Tauri command to add a new glossary word
+#[tauri::command]
+pub fn add_glossary_entry(
+    entry: GlossaryEntry,
+    glossary: State<'_, Arc<Glossary>>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<GlossaryEntry, String> {
+    let entry = glossary.create(entry).map_err(|e| e.user_message())?;
+    dictionary_service.invalidate(&entry.word);
+    dictionary_service.index_word(&entry.word);
+    Ok(entry)
+}
+
+/// Tauri command to edit (or rename) a glossary word
+#[tauri::command]
+pub fn update_glossary_entry(
+    word: String,
+    entry: GlossaryEntry,
+    glossary: State<'_, Arc<Glossary>>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<GlossaryEntry, String> {
+    let entry = glossary.update(&word, entry).map_err(|e| e.user_message())?;
+    dictionary_service.invalidate(&word);
+    dictionary_service.invalidate(&entry.word);
+    if !word.trim().eq_ignore_ascii_case(&entry.word) {
+        dictionary_service.reindex();
+    }
+    Ok(entry)
+}
+
+/// Tauri command to remove a glossary word; returns whether it existed
+#[tauri::command]
+pub fn delete_glossary_entry(
+    word: String,
+    glossary: State<'_, Arc<Glossary>>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<bool, String> {
+    let deleted = glossary.delete(&word).map_err(|e| e.user_message())?;
+    dictionary_service.invalidate(&word);
+    if deleted {
+        dictionary_service.reindex();
+    }
+    Ok(deleted)
+}
+
+/// Tauri command to import a CSV or JSON glossary file; returns the word count
+#[tauri::command]
+pub fn import_glossary(
+    path: String,
+    format: Option<GlossaryFormat>,
+    replace: Option<bool>,
+    glossary: State<'_, Arc<Glossary>>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    let format = format.or_else(|| GlossaryFormat::from_path(&path))
+        .ok_or("Unknown glossary format; use a .csv or .json file")?;
+    let contents = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+
+    let words = glossary.import(&contents, format, replace.unwrap_or(false)).map_err(|e| e.user_message())?;
+    if replace.unwrap_or(false) {
+        dictionary_service.clear_cache();
+        dictionary_service.reindex();
+    } else {
+        for word in &words {
+            dictionary_service.invalidate(word);
+            dictionary_service.index_word(word);
+        }
+    }
+    Ok(words.len())
+}
+
+/// Tauri command to export the glossary to a CSV or JSON file
+#[tauri::command]
+pub fn export_glossary(path: String, format: Option<GlossaryFormat>, glossary: State<'_, Arc<Glossary>>) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let format = format.or_else(|| GlossaryFormat::from_path(&path))
+        .ok_or("Unknown glossary format; use a .csv or .json file")?;
+
+    let contents = glossary.export(format).map_err(|e| e.user_message())?;
+    fs::write(&path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(word: &str, definition: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            word: word.to_string(),
+            pos: "n".to_string(),
+            definitions: vec![definition.to_string()],
+            pronunciation: None,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_crud_persists() {
+        let path = std::env::temp_dir().join(format!("glossary-crud-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let glossary = Glossary::load(Some(path.clone()));
+        glossary.create(entry("SLO", "Service level objective")).unwrap();
+        assert!(glossary.create(entry("slo", "duplicate")).is_err());
+        assert!(glossary.create(entry("  ", "no word")).is_err());
+
+        glossary.update("slo", entry("SLOs", "Service level objectives")).unwrap();
+        assert!(glossary.get("SLO").is_none());
+        assert_eq!(glossary.lookup("slos").unwrap().unwrap().definitions, vec!["Service level objectives"]);
+
+        // A fresh load sees the saved file
+        let reloaded = Glossary::load(Some(path.clone()));
+        assert_eq!(reloaded.list().unwrap().len(), 1);
+        assert!(reloaded.delete("SLOs").unwrap());
+        assert!(!reloaded.delete("SLOs").unwrap());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_csv_round_trip_and_search() {
+        let glossary = Glossary::load(None);
+        let csv = "word,pos,definition\nLightning,n,Our product\nlightning,n,\"Fast, like the product\"\nLDAP,abbr,Directory protocol\n";
+        assert_eq!(glossary.import(csv, GlossaryFormat::Csv, false).unwrap().len(), 2);
+
+        let lightning = glossary.get("LIGHTNING").unwrap();
+        assert_eq!(lightning.definitions, vec!["Our product", "Fast, like the product"]);
+        assert_eq!(glossary.search("l", 10).unwrap(), vec!["LDAP", "Lightning"]);
+
+        let exported = glossary.export(GlossaryFormat::Csv).unwrap();
+        let copy = Glossary::load(None);
+        copy.import(&exported, GlossaryFormat::Csv, true).unwrap();
+        assert_eq!(copy.get("lightning").unwrap().definitions, lightning.definitions);
+
+        let json = glossary.export(GlossaryFormat::Json).unwrap();
+        assert_eq!(copy.import(&json, GlossaryFormat::Json, true).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,315 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use reqwest::Client;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio::runtime::Handle;
+use tokio::time::sleep;
+
+use crate::api_client::DictionaryApiClient;
+use crate::dictionary::{DictionaryService, SourceMode};
+
+const PROBE_TIMEOUT_MS: u64 = 1_000;
+const STABLE_INTERVAL: Duration = Duration::from_secs(30);
+const DEGRADED_INTERVAL: Duration = Duration::from_secs(10);
+const OFFLINE_BASE_INTERVAL: Duration = Duration::from_secs(5);
+const OFFLINE_MAX_INTERVAL: Duration = Duration::from_secs(60);
+// Lookups use a 100ms client timeout, so anything slower is effectively down
+const SLOW_LATENCY_MS: f64 = 100.0;
+const FAILURES_BEFORE_OFFLINE: u32 = 2;
+const AVAILABILITY_WINDOW: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatus {
+    pub online: bool,
+    pub mode: SourceMode,
+    pub reason: String,
+    pub last_latency_ms: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+    pub availability: f64,
+    pub consecutive_failures: u32,
+    pub last_checked: Option<u64>,
+}
+
+struct HealthState {
+    online: bool,
+    last_latency_ms: Option<f64>,
+    avg_latency_ms: Option<f64>,
+    recent_probes: VecDeque<bool>,
+    consecutive_failures: u32,
+    last_checked: Option<u64>,
+}
+
+impl HealthState {
+    fn new() -> Self {
+        Self {
+            // Assume online until proven otherwise, matching the old behaviour
+            online: true,
+            last_latency_ms: None,
+            avg_latency_ms: None,
+            recent_probes: VecDeque::with_capacity(AVAILABILITY_WINDOW),
+            consecutive_failures: 0,
+            last_checked: None,
+        }
+    }
+
+    fn availability(&self) -> f64 {
+        if self.recent_probes.is_empty() {
+            return 0.0;
+        }
+        let ok = self.recent_probes.iter().filter(|p| **p).count();
+        ok as f64 / self.recent_probes.len() as f64 * 100.0
+    }
+
+    fn is_slow(&self) -> bool {
+        matches!(self.avg_latency_ms, Some(ms) if ms > SLOW_LATENCY_MS)
+    }
+
+    fn mode(&self) -> SourceMode {
+        if self.online && !self.is_slow() {
+            SourceMode::OnlineFirst
+        } else {
+            SourceMode::OfflineFirst
+        }
+    }
+
+    fn reason(&self) -> String {
+        match (self.online, self.avg_latency_ms) {
+            (false, _) => "Dictionary API is unreachable; using offline data".to_string(),
+            (true, Some(ms)) if ms > SLOW_LATENCY_MS => format!("Dictionary API is responding slowly ({:.0}ms); preferring offline data", ms),
+            (true, _) => "Dictionary API is healthy".to_string(),
+        }
+    }
+
+    fn record(&mut self, latency: Option<Duration>) {
+        self.last_checked = SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+        self.recent_probes.push_back(latency.is_some());
+        if self.recent_probes.len() > AVAILABILITY_WINDOW {
+            self.recent_probes.pop_front();
+        }
+
+        match latency {
+            Some(latency) => {
+                let ms = latency.as_secs_f64() * 1000.0;
+                self.last_latency_ms = Some(ms);
+                // Exponentially weighted so one slow probe doesn't flip the mode
+                self.avg_latency_ms = Some(match self.avg_latency_ms {
+                    Some(avg) => avg * 0.7 + ms * 0.3,
+                    None => ms,
+                });
+                self.consecutive_failures = 0;
+                self.online = true;
+            }
+            None => {
+                self.last_latency_ms = None;
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= FAILURES_BEFORE_OFFLINE {
+                    self.online = false;
+                    self.avg_latency_ms = None;
+                }
+            }
+        }
+    }
+
+    fn next_interval(&self) -> Duration {
+        if !self.online {
+            let backoff = OFFLINE_BASE_INTERVAL * 2u32.saturating_pow(self.consecutive_failures.saturating_sub(FAILURES_BEFORE_OFFLINE).min(4));
+            backoff.min(OFFLINE_MAX_INTERVAL)
+        } else if self.consecutive_failures > 0 || self.is_slow() || self.availability() < 100.0 {
+            DEGRADED_INTERVAL
+        } else {
+            STABLE_INTERVAL
+        }
+    }
+}
+
+/// Probes every configured API endpoint's `/health` and switches
+/// `DictionaryService` between online-first and offline-first lookups.
+pub struct ApiHealthMonitor {
+    client: Client,
+    api_client: Arc<DictionaryApiClient>,
+    dictionary_service: Arc<DictionaryService>,
+    state: Mutex<HealthState>,
+    runtime_handle: Handle,
+}
+
+impl ApiHealthMonitor {
+    pub fn new(dictionary_service: Arc<DictionaryService>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(PROBE_TIMEOUT_MS))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api_client: dictionary_service.api_client(),
+            dictionary_service,
+            state: Mutex::new(HealthState::new()),
+            runtime_handle: Handle::current(),
+        }
+    }
+
+    pub fn status(&self) -> ApiStatus {
+        let state = self.state.lock().unwrap();
+        ApiStatus {
+            online: state.online,
+            mode: state.mode(),
+            reason: state.reason(),
+            last_latency_ms: state.last_latency_ms,
+            avg_latency_ms: state.avg_latency_ms,
+            availability: state.availability(),
+            consecutive_failures: state.consecutive_failures,
+            last_checked: state.last_checked,
+        }
+    }
+
+    pub fn start<R: Runtime>(self: Arc<Self>, app: AppHandle<R>) {
+        let monitor = self.clone();
+        self.runtime_handle.spawn(async move {
+            loop {
+                let interval = monitor.check(&app).await;
+                sleep(interval).await;
+            }
+        });
+    }
+
+    /// Run one probe, apply the resulting mode and return the delay until the next one.
+    async fn check<R: Runtime>(&self, app: &AppHandle<R>) -> Duration {
+        let latency = self.probe().await;
+
+        let (changed, interval) = {
+            let mut state = self.state.lock().unwrap();
+            let before = (state.online, state.mode());
+            state.record(latency);
+            ((state.online, state.mode()) != before, state.next_interval())
+        };
+
+        let status = self.status();
+        self.dictionary_service.set_source_mode(status.mode);
+
+        if changed {
+            println!("API status changed: {}", status.reason);
+            let _ = app.emit("api-status-changed", status);
+        }
+
+        interval
+    }
+
+    /// Probe all endpoints, feeding each result into the client's endpoint
+    /// stats. The API counts as up if any endpoint answers; the fastest one
+    /// determines the latency we report.
+    async fn probe(&self) -> Option<Duration> {
+        let mut best: Option<Duration> = None;
+        
+        for base_url in self.api_client.endpoint_urls() {
+            let start = Instant::now();
+            let latency = match self.client.get(format!("{}/health", base_url)).send().await {
+                Ok(response) if response.status().is_success() => Some(start.elapsed()),
+                Ok(response) => {
+                    println!("Health check for {} returned HTTP {}", base_url, response.status());
+                    None
+                }
+                Err(_) => None,
+            };
+            
+            self.api_client.record_probe(&base_url, latency);
+            if let Some(latency) = latency {
+                best = Some(best.map_or(latency, |b| b.min(latency)));
+            }
+        }
+        
+        best
+    }
+}
+
+/// Tauri command to read the current API connectivity status
+#[tauri::command]
+pub fn get_api_status(monitor: State<'_, Arc<ApiHealthMonitor>>) -> ApiStatus {
+    monitor.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Option<Duration> {
+        Some(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn test_offline_after_repeated_failures() {
+        let mut state = HealthState::new();
+        state.record(ms(20));
+        assert_eq!(state.mode(), SourceMode::OnlineFirst);
+        assert_eq!(state.next_interval(), STABLE_INTERVAL);
+
+        // One failure is a blip
+        state.record(None);
+        assert!(state.online);
+        assert_eq!(state.mode(), SourceMode::OnlineFirst);
+        assert_eq!(state.next_interval(), DEGRADED_INTERVAL);
+
+        for _ in 1..FAILURES_BEFORE_OFFLINE {
+            state.record(None);
+        }
+        assert!(!state.online);
+        assert_eq!(state.mode(), SourceMode::OfflineFirst);
+        assert!(state.reason().contains("unreachable"));
+    }
+
+    #[test]
+    fn test_slow_average_prefers_offline() {
+        let mut state = HealthState::new();
+        state.record(ms(20));
+
+        // One slow probe is smoothed over, a second tips the average
+        state.record(ms(200));
+        assert_eq!(state.mode(), SourceMode::OnlineFirst);
+        state.record(ms(200));
+        assert!(state.avg_latency_ms.unwrap() > SLOW_LATENCY_MS);
+        assert!(state.online);
+        assert_eq!(state.mode(), SourceMode::OfflineFirst);
+        assert!(state.reason().contains("slowly"));
+        assert_eq!(state.next_interval(), DEGRADED_INTERVAL);
+    }
+
+    #[test]
+    fn test_recovers_on_first_success() {
+        let mut state = HealthState::new();
+        for _ in 0..5 {
+            state.record(None);
+        }
+        assert_eq!(state.mode(), SourceMode::OfflineFirst);
+
+        state.record(ms(30));
+        assert!(state.online);
+        assert_eq!(state.consecutive_failures, 0);
+        // The average restarts rather than carrying the outage
+        assert_eq!(state.avg_latency_ms, Some(30.0));
+        assert_eq!(state.mode(), SourceMode::OnlineFirst);
+        assert_eq!(state.reason(), "Dictionary API is healthy");
+    }
+
+    #[test]
+    fn test_offline_backoff_doubles_up_to_the_cap() {
+        let mut state = HealthState::new();
+        for _ in 0..FAILURES_BEFORE_OFFLINE {
+            state.record(None);
+        }
+
+        let mut intervals = vec![state.next_interval()];
+        for _ in 0..6 {
+            state.record(None);
+            intervals.push(state.next_interval());
+        }
+        assert_eq!(intervals[..4], [
+            OFFLINE_BASE_INTERVAL,
+            OFFLINE_BASE_INTERVAL * 2,
+            OFFLINE_BASE_INTERVAL * 4,
+            OFFLINE_BASE_INTERVAL * 8,
+        ]);
+        assert!(intervals.iter().all(|interval| *interval <= OFFLINE_MAX_INTERVAL));
+        assert_eq!(intervals.last(), Some(&OFFLINE_MAX_INTERVAL));
+    }
+}
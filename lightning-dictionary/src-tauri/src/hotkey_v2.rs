@@ -2,7 +2,9 @@ use tauri::{AppHandle, Manager, Runtime, Emitter, WebviewUrl, WebviewWindowBuild
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 use arboard::Clipboard;
 use std::sync::{Arc, Mutex};
-use crate::dictionary::DictionaryService;
+use crate::dictionary::{CaptureLookup, DictionaryService};
+use crate::language::detect_language;
+use crate::ai_client::{AiClient, AiContext, AiFeature};
 use crate::performance::PERF_TRACKER;
 use serde_json;
 
@@ -12,11 +14,11 @@ pub struct HotkeyManager {
 }
 
 impl HotkeyManager {
-    pub fn setup<R: Runtime>(app: &tauri::App<R>, dictionary_service: Arc<DictionaryService>) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn setup<R: Runtime>(app: &tauri::App<R>, dictionary_service: Arc<DictionaryService>, ai_client: Arc<AiClient>) -> Result<(), Box<dyn std::error::Error>> {
         let app_handle = app.handle().clone();
         
         // Try to register global shortcuts using the official plugin
-        match register_shortcuts(&app_handle, dictionary_service.clone()) {
+        match register_shortcuts(&app_handle, dictionary_service.clone(), ai_client.clone()) {
             Ok(_) => {
                 println!("✓ Global shortcuts registered successfully");
                 if std::env::var("XDG_SESSION_TYPE").unwrap_or_default() == "wayland" {
@@ -26,7 +28,7 @@ impl HotkeyManager {
                     
                     // Start clipboard monitoring as fallback
                     if let Ok(monitor) = ClipboardMonitor::new() {
-                        monitor.start_monitoring(app_handle.clone(), dictionary_service.clone());
+                        monitor.start_monitoring(app_handle.clone(), dictionary_service.clone(), ai_client.clone());
                         println!("✓ Started clipboard monitoring for Wayland");
                     }
                 }
@@ -38,7 +40,7 @@ impl HotkeyManager {
                 
                 // Start clipboard monitoring as fallback
                 if let Ok(monitor) = ClipboardMonitor::new() {
-                    monitor.start_monitoring(app_handle.clone(), dictionary_service.clone());
+                    monitor.start_monitoring(app_handle.clone(), dictionary_service.clone(), ai_client.clone());
                     println!("✓ Started clipboard monitoring as fallback");
                 }
             }
@@ -48,24 +50,26 @@ impl HotkeyManager {
     }
 }
 
-fn register_shortcuts<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<DictionaryService>) -> Result<(), Box<dyn std::error::Error>> {
+fn register_shortcuts<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<DictionaryService>, ai_client: Arc<AiClient>) -> Result<(), Box<dyn std::error::Error>> {
     // Register Alt+J
     let shortcut1 = Shortcut::new(Some(Modifiers::ALT), Code::KeyJ);
     let dict_service1 = dictionary_service.clone();
+    let ai_client1 = ai_client.clone();
     app.global_shortcut().on_shortcut(shortcut1.clone(), move |app, _shortcut, event| {
         if event.state == ShortcutState::Pressed {
             println!("Alt+J pressed!");
-            handle_hotkey_press(app, dict_service1.clone());
+            handle_hotkey_press(app, dict_service1.clone(), ai_client1.clone());
         }
     })?;
     
     // Register Ctrl+Shift+D as fallback
     let shortcut2 = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyD);
     let dict_service2 = dictionary_service.clone();
+    let ai_client2 = ai_client.clone();
     app.global_shortcut().on_shortcut(shortcut2.clone(), move |app, _shortcut, event| {
         if event.state == ShortcutState::Pressed {
             println!("Ctrl+Shift+D pressed!");
-            handle_hotkey_press(app, dict_service2.clone());
+            handle_hotkey_press(app, dict_service2.clone(), ai_client2.clone());
         }
     })?;
     
@@ -74,7 +78,7 @@ fn register_shortcuts<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<Di
     Ok(())
 }
 
-fn handle_hotkey_press<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<DictionaryService>) {
+fn handle_hotkey_press<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<DictionaryService>, ai_client: Arc<AiClient>) {
     PERF_TRACKER.mark("hotkey_pressed");
     let start_time = std::time::Instant::now();
     
@@ -83,23 +87,52 @@ fn handle_hotkey_press<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<D
     
     // Try to get selected text
     match get_selected_text() {
-        Ok(text) if !text.is_empty() => {
+        Ok(selection) if !selection.is_empty() => {
             PERF_TRACKER.mark("text_captured");
-            println!("Selected text: {}", text);
+            println!("Selected text: {}", selection);
             
-            // Look up word using dictionary service (cache + API fallback)
-            match dictionary_service.lookup_word(&text) {
+            // Route the lookup to sources for the language the selection is in
+            let language = detect_language(&selection);
+            let routed = dictionary_service.capture_language(language.as_ref());
+            
+            // Look up the most plausible word in the selection, not all of
+            // it, or the expression around that word
+            let Some(capture) = dictionary_service.lookup_capture(&selection, routed) else {
+                println!("No word in selection");
+                let _ = app.emit("no-selection", ());
+                return;
+            };
+            let text = capture.headword().to_string();
+            let CaptureLookup { phrase, sentence, result, .. } = capture;
+            
+            match result {
                 Ok(definition) => {
+                    dictionary_service.record_lookup(&text);
                     let lookup_time = start_time.elapsed();
                     println!("Word found! Lookup time: {:?}", lookup_time);
                     
                     // Emit definition with timing info
                     let _ = app.emit("word-definition", serde_json::json!({
                         "word": text,
+                        "phrase": phrase,
                         "definition": definition,
                         "from_cache": lookup_time.as_millis() < 5, // Assume cache hit if < 5ms
-                        "lookup_time_ms": lookup_time.as_millis()
+                        "lookup_time_ms": lookup_time.as_millis(),
+                        "language": language,
+                        "translation": dictionary_service.translate(&text, routed, None)
                     }));
+                    
+                    // Base definition is out; AI enrichment follows as separate events
+                    let context = AiContext {
+                        word: text.clone(),
+                        sentence,
+                    };
+                    let features = AiFeature::defaults_for(context.sentence.as_deref());
+                    ai_client.spawn_enrichment(app.clone(), context, features);
+                },
+                // Code identifiers nothing defines whole get a definition per word instead
+                Err(_) if dictionary_service.is_identifier_capture(&text) => {
+                    emit_identifier_lookup(app, &dictionary_service, &text, start_time);
                 },
                 Err(e) => {
                     println!("Error looking up word '{}': {}", text, e);
@@ -107,7 +140,10 @@ fn handle_hotkey_press<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<D
                     let _ = app.emit("word-lookup-error", serde_json::json!({
                         "word": text,
                         "error": e.user_message(),
-                        "lookup_time_ms": start_time.elapsed().as_millis()
+                        "suggestions": e.suggestions(),
+                        "lookup_time_ms": start_time.elapsed().as_millis(),
+                        "language": language,
+                        "translation": dictionary_service.translate(&text, routed, None)
                     }));
                 }
             }
@@ -124,6 +160,17 @@ fn handle_hotkey_press<R: Runtime>(app: &AppHandle<R>, dictionary_service: Arc<D
     }
 }
 
+/// Define each word of a captured identifier and send them in one event
+fn emit_identifier_lookup<R: Runtime>(app: &AppHandle<R>, dictionary_service: &DictionaryService, identifier: &str, start_time: std::time::Instant) {
+    let lookup = dictionary_service.lookup_identifier(identifier);
+    println!("Split identifier '{}' into {} words", identifier, lookup.components.len());
+    let _ = app.emit("identifier-definition", serde_json::json!({
+        "word": identifier,
+        "identifier": lookup,
+        "lookup_time_ms": start_time.elapsed().as_millis()
+    }));
+}
+
 fn get_selected_text() -> Result<String, Box<dyn std::error::Error>> {
     // Save current clipboard
     let mut clipboard = Clipboard::new()?;
@@ -167,7 +214,7 @@ impl ClipboardMonitor {
         })
     }
     
-    pub fn start_monitoring<R: Runtime>(&self, app_handle: AppHandle<R>, dictionary_service: Arc<DictionaryService>) {
+    pub fn start_monitoring<R: Runtime>(&self, app_handle: AppHandle<R>, dictionary_service: Arc<DictionaryService>, ai_client: Arc<AiClient>) {
         let clipboard = self.clipboard.clone();
         let last_content = self.last_content.clone();
         
@@ -193,34 +240,56 @@ impl ClipboardMonitor {
                         if current != *last {
                             println!("Clipboard changed to: '{}'", current);
                             
-                            if is_single_word(&current) {
-                                println!("Valid word detected: {}", current);
+                            let language = detect_language(&current);
+                            let routed = dictionary_service.capture_language(language.as_ref());
+                            
+                            if let Some(capture) = dictionary_service.lookup_capture(&current, routed) {
+                                let word = capture.headword().to_string();
+                                let CaptureLookup { phrase, sentence, result, .. } = capture;
+                                println!("Word detected in clipboard: {}", word);
                                 
                                 // Create popup window first
                                 create_popup_window(&app_handle);
                                 
-                                // Look up word using dictionary service
-                                match dictionary_service.lookup_word(&current) {
+                                match result {
                                     Ok(definition) => {
+                                        dictionary_service.record_lookup(&word);
                                         println!("Found definition for clipboard word!");
                                         let _ = app_handle.emit("word-definition", serde_json::json!({
-                                            "word": current,
+                                            "word": word,
+                                            "phrase": phrase,
                                             "definition": definition,
                                             "from_cache": true, // We'll assume cache hit for clipboard
-                                            "lookup_time_ms": 0
+                                            "lookup_time_ms": 0,
+                                            "language": language,
+                                            "translation": dictionary_service.translate(&word, routed, None)
                                         }));
+                                        
+                                        let context = AiContext {
+                                            word: word.clone(),
+                                            sentence,
+                                        };
+                                        let features = AiFeature::defaults_for(context.sentence.as_deref());
+                                        ai_client.spawn_enrichment(app_handle.clone(), context, features);
+                                    },
+                                    Err(_) if dictionary_service.is_identifier_capture(&word) => {
+                                        println!("Identifier detected: {}", word);
+                                        emit_identifier_lookup(&app_handle, &dictionary_service, &word, std::time::Instant::now());
                                     },
                                     Err(e) => {
-                                        println!("Error looking up clipboard word '{}': {}", current, e);
+                                        println!("Error looking up clipboard word '{}': {}", word, e);
                                         let _ = app_handle.emit("word-lookup-error", serde_json::json!({
-                                            "word": current,
+                                            "word": word,
                                             "error": e.user_message(),
-                                            "lookup_time_ms": 0
+                                            "suggestions": e.suggestions(),
+                                            "lookup_time_ms": 0,
+                                            "language": language,
+                                            "translation": dictionary_service.translate(&word, routed, None)
                                         }));
                                     }
                                 }
                             } else {
-                                println!("Not a single word, ignoring");
+                                println!("No word in clipboard text, ignoring");
                             }
                             
                             *last = current;
@@ -238,14 +307,6 @@ impl ClipboardMonitor {
     }
 }
 
-fn is_single_word(text: &str) -> bool {
-    let trimmed = text.trim();
-    !trimmed.is_empty() && 
-    trimmed.split_whitespace().count() == 1 && 
-    trimmed.len() < 50 &&
-    trimmed.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'')
-}
-
 fn create_popup_window<R: Runtime>(app: &AppHandle<R>) {
     // Check if popup window already exists
     if let Some(window) = app.get_webview_window("popup") {
@@ -0,0 +1,179 @@
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+
+// Longest selection treated as an identifier
+const MAX_IDENTIFIER_LEN: usize = 80;
+
+// Common shorthand in code and what to look up instead
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("addr", "address"),
+    ("arg", "argument"),
+    ("args", "arguments"),
+    ("async", "asynchronous"),
+    ("attr", "attribute"),
+    ("auth", "authentication"),
+    ("btn", "button"),
+    ("buf", "buffer"),
+    ("calc", "calculate"),
+    ("cb", "callback"),
+    ("cfg", "configuration"),
+    ("char", "character"),
+    ("cmd", "command"),
+    ("cnt", "count"),
+    ("config", "configuration"),
+    ("conn", "connection"),
+    ("ctx", "context"),
+    ("db", "database"),
+    ("dest", "destination"),
+    ("dir", "directory"),
+    ("doc", "document"),
+    ("dst", "destination"),
+    ("elem", "element"),
+    ("env", "environment"),
+    ("err", "error"),
+    ("fn", "function"),
+    ("func", "function"),
+    ("idx", "index"),
+    ("impl", "implementation"),
+    ("init", "initialize"),
+    ("len", "length"),
+    ("lib", "library"),
+    ("max", "maximum"),
+    ("min", "minimum"),
+    ("msg", "message"),
+    ("num", "number"),
+    ("obj", "object"),
+    ("param", "parameter"),
+    ("params", "parameters"),
+    ("pos", "position"),
+    ("prev", "previous"),
+    ("ptr", "pointer"),
+    ("ref", "reference"),
+    ("req", "request"),
+    ("res", "response"),
+    ("resp", "response"),
+    ("src", "source"),
+    ("str", "string"),
+    ("sync", "synchronous"),
+    ("tmp", "temporary"),
+    ("util", "utility"),
+    ("utils", "utilities"),
+    ("val", "value"),
+    ("var", "variable"),
+];
+
+/// One piece of a split identifier
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdentifierPart {
+    /// As written, lowercased: "cfg"
+    pub part: String,
+    /// What gets looked up: "configuration". `None` for numbers.
+    pub word: Option<String>,
+}
+
+/// A component word and what the dictionary says about it
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentDefinition {
+    pub part: String,
+    pub word: String,
+    pub definition: Option<Definition>,
+    pub error: Option<String>,
+}
+
+/// Definitions for every word in an identifier, in order
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentifierLookup {
+    pub identifier: String,
+    pub parts: Vec<IdentifierPart>,
+    pub components: Vec<ComponentDefinition>,
+}
+
+/// Split a code identifier at underscores, hyphens, case changes and
+/// digits: `getUserPreferencesAsync` -> get, user, preferences, async;
+/// `HTTPServer2` -> http, server, 2
+pub fn split_identifier(identifier: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for chunk in identifier.split(['_', '-']).filter(|c| !c.is_empty()) {
+        let chars: Vec<char> = chunk.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, c) = (chars[i - 1], chars[i]);
+            let boundary = (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_ascii_digit() != c.is_ascii_digit())
+                // The last capital of an acronym starts the next word: "HTTPServer"
+                || (prev.is_uppercase() && c.is_uppercase() && chars.get(i + 1).is_some_and(|n| n.is_lowercase()));
+            if boundary {
+                parts.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        parts.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    parts
+}
+
+/// Whether a selection is written like a code identifier rather than a
+/// word. Hyphenated words ("well-being") qualify too; callers check the
+/// dictionary doesn't know the whole first.
+pub fn is_identifier(text: &str) -> bool {
+    text.len() <= MAX_IDENTIFIER_LEN
+        && text.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && split_identifier(text).len() > 1
+}
+
+/// The parts of an identifier with abbreviations expanded
+pub fn identifier_parts(identifier: &str) -> Vec<IdentifierPart> {
+    split_identifier(identifier).into_iter()
+        .map(|part| {
+            let word = if part.chars().all(|c| c.is_ascii_digit()) {
+                None
+            } else {
+                let expanded = ABBREVIATIONS.iter().find(|(short, _)| *short == part).map(|(_, long)| *long);
+                Some(expanded.unwrap_or(&part).to_string())
+            };
+            IdentifierPart { part, word }
+        })
+        .collect()
+}
+
+/// Tauri command splitting an identifier and defining each of its words
+#[tauri::command]
+pub fn lookup_identifier(identifier: String, dictionary_service: State<'_, Arc<DictionaryService>>) -> IdentifierLookup {
+    dictionary_service.lookup_identifier(&identifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("getUserPreferencesAsync"), vec!["get", "user", "preferences", "async"]);
+        assert_eq!(split_identifier("max_retry_count"), vec!["max", "retry", "count"]);
+        assert_eq!(split_identifier("HTTPServer2Config"), vec!["http", "server", "2", "config"]);
+        assert_eq!(split_identifier("__init__"), vec!["init"]);
+        assert_eq!(split_identifier("x-request-id"), vec!["x", "request", "id"]);
+        assert_eq!(split_identifier("utf8"), vec!["utf", "8"]);
+
+        assert!(is_identifier("getUserPreferencesAsync"));
+        assert!(is_identifier("MAX_RETRY_COUNT"));
+        assert!(!is_identifier("hello"));
+        assert!(!is_identifier("Hello"));
+        assert!(!is_identifier("two words"));
+        assert!(!is_identifier("naïve_value"));
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let parts = identifier_parts("reqCtx_cfg2");
+        let words: Vec<Option<&str>> = parts.iter().map(|p| p.word.as_deref()).collect();
+        assert_eq!(words, vec![Some("request"), Some("context"), Some("configuration"), None]);
+        assert_eq!(parts[0].part, "req");
+        assert_eq!(identifier_parts("implFoo")[0].word.as_deref(), Some("implementation"));
+    }
+}
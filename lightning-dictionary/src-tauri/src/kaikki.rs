@@ -0,0 +1,216 @@
+use serde::Deserialize;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::local_store::{spawn_import, ImportProgress, LocalStore, StoreEntry, Translation, WordForm};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+// Only the parts of a Wiktextract entry we keep; serde skips the rest
+#[derive(Deserialize)]
+struct KaikkiEntry {
+    word: String,
+    #[serde(default)]
+    pos: String,
+    lang_code: Option<String>,
+    #[serde(default)]
+    senses: Vec<KaikkiSense>,
+    #[serde(default)]
+    sounds: Vec<KaikkiSound>,
+    etymology_text: Option<String>,
+    #[serde(default)]
+    forms: Vec<WordForm>,
+    #[serde(default)]
+    translations: Vec<KaikkiTranslation>,
+}
+
+#[derive(Deserialize)]
+struct KaikkiSense {
+    #[serde(default)]
+    glosses: Vec<String>,
+    #[serde(default)]
+    examples: Vec<KaikkiExample>,
+}
+
+#[derive(Deserialize)]
+struct KaikkiExample {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct KaikkiSound {
+    ipa: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct KaikkiTranslation {
+    lang: Option<String>,
+    code: Option<String>,
+    word: Option<String>,
+    sense: Option<String>,
+}
+
+impl KaikkiEntry {
+    fn into_store_entry(self) -> StoreEntry {
+        StoreEntry {
+            // Subsenses repeat their parent's gloss first; the last one is the specific meaning
+            definitions: self.senses.iter()
+                .filter_map(|s| s.glosses.last().cloned())
+                .collect(),
+            examples: self.senses.iter()
+                .flat_map(|s| s.examples.iter().filter_map(|e| e.text.clone()))
+                .collect(),
+            pronunciation: self.sounds.into_iter().find_map(|s| s.ipa),
+            etymology: self.etymology_text.filter(|e| !e.is_empty()),
+            forms: self.forms,
+            translations: self.translations.into_iter()
+                .filter_map(|t| Some(Translation {
+                    lang: t.lang?,
+                    code: t.code,
+                    word: t.word?,
+                    sense: t.sense,
+                }))
+                .collect(),
+            cross_references: Vec::new(),
+            word: self.word,
+            pos: self.pos,
+        }
+    }
+}
+
+/// Stream a kaikki.org JSONL dump into the store, one line at a time.
+/// Entries in other languages, and lines that don't parse, are skipped.
+pub fn import_jsonl<B: BufRead>(
+    mut reader: B,
+    store: &mut LocalStore,
+    lang_code: &str,
+    bytes_read: &AtomicU64,
+    progress: &mut ImportProgress,
+    mut on_progress: impl FnMut(&ImportProgress),
+) -> DictionaryResult<()> {
+    let mut import = store.import("kaikki")?;
+    let mut line = Vec::new();
+    let mut line_number = 0u64;
+    let mut last_report = Instant::now();
+
+    loop {
+        line.clear();
+        line_number += 1;
+        let n = reader.read_until(b'\n', &mut line).map_err(|e| DictionaryError::SourceError {
+            source: "kaikki".to_string(),
+            message: format!("read failed after {} entries: {}", progress.entries_imported, e),
+        })?;
+        if n == 0 {
+            break;
+        }
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+
+        match serde_json::from_slice::<KaikkiEntry>(&line) {
+            Ok(entry) if entry.lang_code.as_deref().unwrap_or(lang_code) == lang_code => {
+                let entry = entry.into_store_entry();
+                if entry.definitions.is_empty() {
+                    progress.entries_skipped += 1;
+                } else {
+                    import.insert(&entry)?;
+                    progress.entries_imported += 1;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => progress.report_issue(line_number, e.to_string()),
+        }
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+            on_progress(progress);
+            last_report = Instant::now();
+        }
+    }
+
+    import.finish()?;
+    progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+    Ok(())
+}
+
+/// Tauri command to import a kaikki.org dump (`.jsonl` or `.jsonl.gz`).
+/// Runs in the background and reports through `import-progress` events.
+#[tauri::command]
+pub fn import_kaikki(
+    path: String,
+    lang_code: Option<String>,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<(), String> {
+    let lang_code = lang_code.unwrap_or_else(|| "en".to_string());
+
+    spawn_import(app_handle, dictionary_service.inner().clone(), PathBuf::from(path), "kaikki",
+        move |reader, bytes_read, store, progress, on_progress| {
+            import_jsonl(reader, store, &lang_code, bytes_read, progress, on_progress)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::DictionarySource;
+    use crate::sqlite_source::SqliteSource;
+
+    const DUMP: &str = r#"{"word": "cat", "pos": "noun", "lang": "English", "lang_code": "en", "etymology_text": "From Old English catt.", "sounds": [{"audio": "cat.ogg"}, {"ipa": "/kæt/", "tags": ["US"]}], "forms": [{"form": "cats", "tags": ["plural"]}], "senses": [{"glosses": ["A domesticated feline."], "examples": [{"text": "The cat sat on the mat."}]}, {"glosses": ["A person.", "A cool person."]}], "translations": [{"lang": "French", "code": "fr", "word": "chat", "sense": "feline"}, {"lang": "German", "code": "de"}]}
+{"word": "cat", "pos": "verb", "lang_code": "en", "senses": [{"glosses": ["To hoist an anchor."]}]}
+not json at all
+
+{"word": "chat", "pos": "noun", "lang_code": "fr", "senses": [{"glosses": ["cat"]}]}
+{"word": "catt", "pos": "noun", "lang_code": "en", "senses": [{"tags": ["no-gloss"]}]}
+"#;
+
+    #[test]
+    fn test_import_and_lookup() {
+        let path = std::env::temp_dir().join(format!("kaikki-import-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = LocalStore::open(&path).unwrap();
+        let mut progress = ImportProgress::default();
+        let bytes = AtomicU64::new(0);
+        import_jsonl(DUMP.as_bytes(), &mut store, "en", &bytes, &mut progress, |_| {}).unwrap();
+
+        assert_eq!(progress.entries_imported, 2);
+        // The malformed line and the gloss-less entry
+        assert_eq!(progress.entries_skipped, 2);
+        assert_eq!(progress.issues.len(), 1);
+        assert_eq!(progress.issues[0].line, 3);
+
+        let details = store.details("CAT").unwrap();
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].definitions, vec!["A domesticated feline.", "A cool person."]);
+        assert_eq!(details[0].examples, vec!["The cat sat on the mat."]);
+        assert_eq!(details[0].pronunciation.as_deref(), Some("/kæt/"));
+        assert_eq!(details[0].etymology.as_deref(), Some("From Old English catt."));
+        assert_eq!(details[0].forms[0].form, "cats");
+        assert_eq!(details[0].translations.len(), 1);
+        assert_eq!(details[0].translations[0].word, "chat");
+
+        // Re-importing replaces rather than duplicates
+        import_jsonl(DUMP.as_bytes(), &mut store, "en", &bytes, &mut ImportProgress::default(), |_| {}).unwrap();
+        assert_eq!(store.details("cat").unwrap().len(), 2);
+
+        // An import abandoned midway leaves the previous one untouched
+        drop(store.import("kaikki").unwrap());
+        assert_eq!(store.details("cat").unwrap().len(), 2);
+
+        let source = SqliteSource::open(&path).unwrap();
+        let cat = source.lookup("cat").unwrap().unwrap();
+        assert_eq!(cat.pos, "noun");
+        assert_eq!(cat.definitions, vec!["A domesticated feline.", "A cool person.", "(verb) To hoist an anchor."]);
+        assert!(source.lookup("chat").unwrap().is_none());
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+}
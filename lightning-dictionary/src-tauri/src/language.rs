@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The language the API and the bundled English data are in
+pub const ENGLISH: &str = "en";
+// Below this, a capture is looked up as before rather than routed
+const ROUTING_CONFIDENCE: f32 = 0.7;
+// Captures are mostly English, so other Latin-script languages need
+// clearer evidence to win
+const ENGLISH_PRIOR: f64 = 0.4;
+// Add-k smoothing for n-grams a sample never saw
+const SMOOTHING: f64 = 0.5;
+// Rough count of distinct n-grams of each length (1, 2, 3) in a language
+const VOCABULARY: [f64; 3] = [40.0, 800.0, 8000.0];
+
+// Short samples of common words and running text per Latin-script
+// language; n-gram counts from these are the whole model
+const SAMPLES: &[(&str, &str)] = &[
+    ("en", "the of and to in is you that it he was for on are as with his they at be this have from or one had by \
+        word but not what all were we when your can said there use an each which she do how their if will up other \
+        about out many then them these so some her would make like him into time has look two more write go see \
+        number no way could people my than first water been call who oil its now find long down day did get come \
+        made may part. The quick brown fox jumps over the lazy dog. She thought the weather would be nice enough \
+        for a walk through the park, although the children wanted to stay inside and watch something. Knowledge \
+        is light, and nothing worth having comes without working through the night."),
+    ("es", "de la que el en y a los se del las un por con no una su para es al lo como más o pero sus le ha me si \
+        sin sobre este ya entre cuando todo esta ser son dos también fue había era muy años hasta desde está mi \
+        porque qué sólo han yo hay vez puede todos así nos ni parte tiene él uno donde bien tiempo mismo ese ahora \
+        cada. El niño pequeño corría por la calle mientras su madre compraba pan en la panadería. Mañana vamos a \
+        la montaña con nuestros amigos, y después comeremos juntos en el pueblo. ¿Dónde está la estación?"),
+    ("fr", "de la le et les des en un du une que est pour qui dans par plus pas au sur se ne ce il sont avec ou \
+        son aux été comme elle mais nous fait ses leur deux même aussi bien ont tout tous cette sans peut entre \
+        très être avait faire après où encore ces depuis autres dont ans donc quand ça. Le petit garçon mangeait \
+        une pomme près de la fenêtre pendant que sa sœur lisait un livre. Nous irons à la plage demain matin si \
+        le temps le permet, puis nous déjeunerons chez nos grands-parents. Où est la gare, s'il vous plaît?"),
+    ("de", "der die und in den von zu das mit sich des auf für ist im dem nicht ein eine als auch es an werden aus \
+        er hat dass sie nach wird bei einer um am sind noch wie einem über einen so zum war haben nur oder aber vor \
+        zur bis mehr durch man sein wurde sei schon wenn können gegen müssen zwischen. Der kleine Hund läuft \
+        schnell über die Straße, weil er seinen Ball sehen kann. Morgen fahren wir mit dem Zug nach München und \
+        besuchen unsere Großeltern. Das Wetter ist heute schön, aber ziemlich kühl."),
+    ("it", "di e il la che in un per è non una del con i le si da al lo come della ma più sono anche gli ha delle \
+        nel se mi ci questo essere alla ne suo dei sua hanno tutto già quando molto dopo cosa fatto nella cui \
+        perché tra stato ancora può loro così fare sempre altro quello solo ogni. Il piccolo gatto dormiva sulla \
+        sedia vicino alla finestra mentre la nonna cucinava gli gnocchi. Domani andremo al mare con gli amici e \
+        poi mangeremo una pizza insieme. Dov'è la stazione, per favore?"),
+    ("pt", "de a o que e do da em um para é com não uma os no se na por mais as dos como mas foi ao ele das tem à \
+        seu sua ou ser quando muito há nos já está eu também só pelo pela até isso ela entre era depois sem mesmo \
+        aos ter seus quem nas me esse eles estão você tinha foram essa num nem suas meu às minha têm numa pelos \
+        então. O menino pequeno brincava na rua enquanto a mãe comprava pão na padaria. Amanhã vamos à praia com \
+        os nossos amigos e depois almoçaremos juntos. Onde fica a estação, por favor? Não sei, irmão."),
+    ("nl", "de en van ik te dat die in een hij het niet zijn is was op aan met als voor had er maar om hem dan zou \
+        of wat mijn men dit zo door over ze zich bij ook tot je mij uit der daar haar naar heb hoe heeft hebben \
+        deze u want nog zal me zij nu ge geen omdat iets worden toch al waren veel meer doen. De kleine jongen \
+        fietst elke ochtend naar school, ook als het regent. Morgen gaan we met de trein naar Amsterdam om onze \
+        grootouders te bezoeken. Het weer is vandaag mooi, maar vrij koud."),
+];
+
+/// The language a capture is most likely in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code, e.g. "en"
+    pub code: String,
+    /// Probability of `code` among the languages considered, 0 to 1
+    pub confidence: f32,
+    /// Whether there was enough to go on for routing: the script, or
+    /// n-grams from more than one word
+    #[serde(skip)]
+    conclusive: bool,
+}
+
+impl DetectedLanguage {
+    /// The language to route the lookup to, when detection is sure enough.
+    /// A single Latin-script word is never routed: its n-grams alone make
+    /// plenty of English words look Spanish or French.
+    pub fn routing_language(&self) -> Option<&str> {
+        (self.conclusive && self.confidence >= ROUTING_CONFIDENCE).then_some(self.code.as_str())
+    }
+}
+
+/// A direction of translation, e.g. English to Vietnamese ("en-vi")
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LanguagePair {
+    /// ISO 639 code of the words looked up
+    pub source: String,
+    /// ISO 639 code of their translations
+    pub target: String,
+}
+
+impl fmt::Display for LanguagePair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.source, self.target)
+    }
+}
+
+impl FromStr for LanguagePair {
+    type Err = String;
+
+    /// Parse "en-vi". Codes are two or three letters, as in ISO 639-1/3.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_code = |code: &str| (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphabetic());
+        match s.trim().to_ascii_lowercase().split_once('-') {
+            Some((source, target)) if is_code(source) && is_code(target) && source != target => Ok(Self {
+                source: source.to_string(),
+                target: target.to_string(),
+            }),
+            _ => Err(format!("'{}' is not a language pair like 'en-vi'", s)),
+        }
+    }
+}
+
+struct Profile {
+    code: &'static str,
+    counts: HashMap<String, u32>,
+    totals: [f64; 3],
+}
+
+impl Profile {
+    fn new(code: &'static str, sample: &str) -> Self {
+        let mut counts = HashMap::new();
+        let mut totals = [0.0; 3];
+        for gram in ngrams(sample) {
+            totals[gram.chars().count() - 1] += 1.0;
+            *counts.entry(gram).or_insert(0) += 1;
+        }
+        Self { code, counts, totals }
+    }
+
+    fn log_likelihood(&self, grams: &[String]) -> f64 {
+        grams.iter()
+            .map(|gram| {
+                let n = gram.chars().count() - 1;
+                let count = self.counts.get(gram).copied().unwrap_or(0) as f64;
+                ((count + SMOOTHING) / (self.totals[n] + SMOOTHING * VOCABULARY[n])).ln()
+            })
+            .sum()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PROFILES: Vec<Profile> = SAMPLES.iter().map(|(code, sample)| Profile::new(code, sample)).collect();
+}
+
+/// Character 1- to 3-grams of each word, padded with spaces so word
+/// starts and ends count
+fn ngrams(text: &str) -> Vec<String> {
+    let mut grams = Vec::new();
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = format!(" {} ", word.to_lowercase()).chars().collect();
+        for n in 1..=3 {
+            for window in padded.windows(n) {
+                if n == 1 && window[0] == ' ' {
+                    continue;
+                }
+                grams.push(window.iter().collect());
+            }
+        }
+    }
+    grams
+}
+
+/// Languages told apart by script alone
+fn script_language(c: char) -> Option<&'static str> {
+    match c {
+        '\u{3040}'..='\u{30ff}' => Some("ja"),
+        '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' => Some("ko"),
+        '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => Some("zh"),
+        '\u{0400}'..='\u{04ff}' => Some("ru"),
+        '\u{0600}'..='\u{06ff}' => Some("ar"),
+        '\u{0370}'..='\u{03ff}' => Some("el"),
+        '\u{0590}'..='\u{05ff}' => Some("he"),
+        '\u{0900}'..='\u{097f}' => Some("hi"),
+        '\u{0e00}'..='\u{0e7f}' => Some("th"),
+        _ => None,
+    }
+}
+
+/// Guess the language of a short capture offline. Non-Latin scripts are
+/// decided by script; Latin text is scored against character n-gram
+/// profiles. `None` when the text has no letters.
+pub fn detect_language(text: &str) -> Option<DetectedLanguage> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+
+    let mut scripts: HashMap<&str, usize> = HashMap::new();
+    for c in &letters {
+        if let Some(language) = script_language(*c) {
+            *scripts.entry(language).or_insert(0) += 1;
+        }
+    }
+    // Japanese mixes kanji with kana; any kana settles it
+    if let Some(kana) = scripts.get("ja").copied() {
+        let kanji = scripts.remove("zh").unwrap_or(0);
+        scripts.insert("ja", kana + kanji);
+    }
+    if let Some((code, count)) = scripts.into_iter().max_by_key(|(_, count)| *count) {
+        if count * 2 >= letters.len() {
+            return Some(DetectedLanguage {
+                code: code.to_string(),
+                confidence: count as f32 / letters.len() as f32,
+                conclusive: true,
+            });
+        }
+    }
+
+    let grams = ngrams(text);
+    let other_prior = ((1.0 - ENGLISH_PRIOR) / (PROFILES.len() - 1) as f64).ln();
+    let scores: Vec<(&str, f64)> = PROFILES.iter()
+        .map(|profile| {
+            let prior = if profile.code == ENGLISH { ENGLISH_PRIOR.ln() } else { other_prior };
+            (profile.code, prior + profile.log_likelihood(&grams))
+        })
+        .collect();
+
+    // Softmax, shifted by the best score to stay in range
+    let best = scores.iter().map(|(_, score)| *score).fold(f64::NEG_INFINITY, f64::max);
+    let total: f64 = scores.iter().map(|(_, score)| (score - best).exp()).sum();
+    let (code, score) = scores.iter().find(|(_, score)| *score == best)?;
+    Some(DetectedLanguage {
+        code: code.to_string(),
+        confidence: ((score - best).exp() / total) as f32,
+        conclusive: text.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()).count() > 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(text: &str) -> String {
+        detect_language(text).unwrap().code
+    }
+
+    #[test]
+    fn test_latin_languages() {
+        assert_eq!(code("the weather is nice today"), "en");
+        assert_eq!(code("knowledge"), "en");
+        assert_eq!(code("¿dónde está el baño?"), "es");
+        assert_eq!(code("mañana"), "es");
+        assert_eq!(code("où est la boulangerie"), "fr");
+        assert_eq!(code("Straße"), "de");
+        assert_eq!(code("gli amici della nonna"), "it");
+        assert_eq!(code("não sei"), "pt");
+        assert_eq!(code("het is mooi weer vandaag"), "nl");
+        assert!(detect_language("42 !").is_none());
+
+        // A plain English word isn't routed away from English
+        let detected = detect_language("house").unwrap();
+        assert!(detected.code == ENGLISH || detected.routing_language().is_none());
+        assert_eq!(detect_language("où est la gare").unwrap().routing_language(), Some("fr"));
+        for word in ["table", "station", "experience", "mañana"] {
+            assert_eq!(detect_language(word).unwrap().routing_language(), None, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_language_pairs() {
+        let pair: LanguagePair = "EN-vi".parse().unwrap();
+        assert_eq!((pair.source.as_str(), pair.target.as_str()), ("en", "vi"));
+        assert_eq!(pair.to_string(), "en-vi");
+        assert!("en".parse::<LanguagePair>().is_err());
+        assert!("en-en".parse::<LanguagePair>().is_err());
+        assert!("../x-vi".parse::<LanguagePair>().is_err());
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(code("こんにちは"), "ja");
+        assert_eq!(code("日本語を話す"), "ja");
+        assert_eq!(code("你好世界"), "zh");
+        assert_eq!(code("안녕하세요"), "ko");
+        assert_eq!(code("привет"), "ru");
+        assert_eq!(code("مرحبا"), "ar");
+        assert_eq!(detect_language("привет").unwrap().confidence, 1.0);
+    }
+}
@@ -6,6 +6,31 @@ mod error;
 mod performance;
 mod settings;
 mod prefetch;
+mod ai_client;
+mod ai_jobs;
+mod health;
+mod source;
+mod sqlite_source;
+mod stardict;
+mod dict_protocol;
+mod local_store;
+mod kaikki;
+mod wordnet;
+mod xml_import;
+mod glossary;
+mod prefix_index;
+mod morphology;
+mod phrase;
+mod reverse_index;
+mod pattern;
+mod anagram;
+mod phonetic;
+mod cmudict;
+mod speech;
+mod language;
+mod bilingual;
+mod tokenizer;
+mod identifier;
 
 #[cfg(test)]
 mod cache_benchmark;
@@ -14,11 +39,32 @@ use hotkey_v2::HotkeyManager;
 use cache::{create_cache, ThreadSafeCache, Definition};
 use dictionary::DictionaryService;
 use performance::{PERF_TRACKER, PerformanceStats};
-use settings::{get_settings, save_settings};
+use settings::{get_settings, save_settings, Settings, SettingsManager};
 use prefetch::{PrefetchManager, queue_prefetch, get_prefetch_stats, clear_prefetch_queue};
+use ai_client::{create_ai_cache, AiClient, AiContext, AiEnhancement, AiFeature};
+use ai_jobs::{AiJobManager, submit_ai_batch, get_ai_jobs, cancel_ai_job, get_ai_job_stats};
+use health::{ApiHealthMonitor, get_api_status};
+use api_client::{DictionaryApiClient, EndpointStats};
+use sqlite_source::SqliteSource;
+use stardict::{register_stardict_sources, list_stardict_dictionaries, install_stardict_dictionaries, set_stardict_enabled};
+use dict_protocol::{DictClient, get_dict_databases, dict_define, dict_match};
+use local_store::{local_store_path, register_local_store, get_word_details};
+use kaikki::import_kaikki;
+use wordnet::{wordnet_synsets, wordnet_related};
+use xml_import::import_xml_dictionary;
+use phrase::lookup_in_context;
+use pattern::pattern_search;
+use anagram::find_anagrams;
+use phonetic::sounds_like;
+use speech::{Speaker, pronounce_word};
+use cmudict::{pronunciation_store_path, register_pronunciations, import_cmudict, get_pronunciations, find_rhymes};
+use bilingual::{register_bilingual_packs, list_bilingual_packs, import_bilingual_pack};
+use identifier::lookup_identifier;
+use glossary::{Glossary, get_glossary, add_glossary_entry, update_glossary_entry, delete_glossary_entry, import_glossary, export_glossary};
+use std::path::PathBuf;
 use std::sync::Arc;
 use serde::Serialize;
-use tauri::Manager;
+use tauri::{AppHandle, Manager, Runtime};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -31,20 +77,41 @@ struct LookupResult {
     success: bool,
     data: Option<Definition>,
     error: Option<String>,
+    /// "Did you mean" words when the lookup missed
+    suggestions: Vec<String>,
+    /// The word in the target language, from a bilingual pack
+    translation: Option<Definition>,
 }
 
+/// Look up a word, optionally in a given language. A translation is
+/// included from the bilingual pack for `target_language`, or for the
+/// preferred language pairs when none is given.
 #[tauri::command]
-fn lookup_word(word: &str, state: tauri::State<AppState>) -> LookupResult {
-    match state.dictionary_service.lookup_word(word) {
-        Ok(definition) => LookupResult {
-            success: true,
-            data: Some(definition),
-            error: None,
+fn lookup_word(
+    word: &str,
+    source_language: Option<String>,
+    target_language: Option<String>,
+    state: tauri::State<AppState>,
+) -> LookupResult {
+    let source_language = source_language.as_deref();
+    let translation = state.dictionary_service.translate(word, source_language, target_language.as_deref());
+    match state.dictionary_service.lookup_word_in(word, source_language) {
+        Ok(definition) => {
+            state.dictionary_service.record_lookup(word);
+            LookupResult {
+                success: true,
+                data: Some(definition),
+                error: None,
+                suggestions: vec![],
+                translation,
+            }
         },
         Err(e) => LookupResult {
             success: false,
             data: None,
             error: Some(e.user_message()),
+            suggestions: e.suggestions().to_vec(),
+            translation,
         }
     }
 }
@@ -64,8 +131,8 @@ struct SearchResult {
 }
 
 #[tauri::command]
-fn search_words(query: &str, state: tauri::State<AppState>) -> SearchResult {
-    match state.dictionary_service.search_words(query) {
+fn search_words(query: &str, fuzzy: Option<bool>, state: tauri::State<AppState>) -> SearchResult {
+    match state.dictionary_service.search_words(query, fuzzy.unwrap_or(false)) {
         Ok(results) => SearchResult {
             success: true,
             data: results,
@@ -79,6 +146,49 @@ fn search_words(query: &str, state: tauri::State<AppState>) -> SearchResult {
     }
 }
 
+/// Find words from their meaning, e.g. "fear of heights"
+#[tauri::command]
+fn reverse_search(query: &str, limit: Option<usize>, state: tauri::State<AppState>) -> Vec<reverse_index::ReverseMatch> {
+    state.dictionary_service.reverse_search(query, limit.unwrap_or(20))
+}
+
+#[derive(Serialize)]
+struct EnrichResult {
+    success: bool,
+    data: Option<AiEnhancement>,
+    from_cache: bool,
+    error: Option<String>,
+}
+
+#[tauri::command]
+fn enrich_word(word: &str, sentence: Option<String>, feature: Option<AiFeature>, state: tauri::State<AppState>) -> EnrichResult {
+    let context = AiContext {
+        word: word.to_string(),
+        sentence,
+    };
+    let feature = feature.unwrap_or(AiFeature::ContextDefinition);
+
+    match state.ai_client.enhance_blocking(&context, feature) {
+        Ok((enhancement, from_cache)) => EnrichResult {
+            success: true,
+            data: Some(enhancement),
+            from_cache,
+            error: None,
+        },
+        Err(e) => EnrichResult {
+            success: false,
+            data: None,
+            from_cache: false,
+            error: Some(e.user_message()),
+        }
+    }
+}
+
+#[tauri::command]
+fn get_endpoint_stats(api_client: tauri::State<Arc<DictionaryApiClient>>) -> Vec<EndpointStats> {
+    api_client.endpoint_stats()
+}
+
 #[tauri::command]
 fn get_performance_stats() -> PerformanceStats {
     PERF_TRACKER.get_stats()
@@ -89,10 +199,54 @@ fn reset_performance_stats() {
     PERF_TRACKER.reset();
 }
 
+/// Open whichever local sources are configured and hand them to the service
+fn register_local_sources<R: Runtime>(handle: &AppHandle<R>, dictionary_service: &DictionaryService, settings: &Settings) {
+    let app_data_dir = handle.path().app_data_dir().ok();
+    
+    if settings.sources.sqlite_enabled {
+        // DICTIONARY_DB_PATH wins over settings, mirroring DICTIONARY_API_URL
+        let db_path = std::env::var("DICTIONARY_DB_PATH").ok()
+            .or_else(|| settings.sources.sqlite_path.clone())
+            .map(PathBuf::from)
+            .or_else(|| app_data_dir.as_ref().map(|dir| dir.join("lightning-dictionary.db")));
+        
+        if let Some(path) = db_path.filter(|p| p.exists()) {
+            match SqliteSource::open(&path) {
+                Ok(source) => {
+                    println!("Opened SQLite dictionary at {}", source.path().display());
+                    dictionary_service.add_source(Arc::new(source));
+                }
+                Err(e) => e.log_error(),
+            }
+        }
+    }
+    
+    // Entries imported from Wiktionary and other dumps
+    if let Some(path) = local_store_path(handle).filter(|p| p.exists()) {
+        register_local_store(dictionary_service, &path);
+    }
+    
+    // CMU pronunciations, for rhymes and missing pronunciations
+    if let Some(path) = pronunciation_store_path(handle).filter(|p| p.exists()) {
+        register_pronunciations(dictionary_service, &path);
+    }
+    
+    register_stardict_sources(handle, dictionary_service, settings);
+    
+    // Translations for language pairs, one pack per pair
+    register_bilingual_packs(handle, dictionary_service);
+    
+    // Network sources go last so a slow server only costs time on a local miss
+    for server in settings.sources.dict_servers.iter().filter(|s| s.enabled) {
+        dictionary_service.add_source(Arc::new(DictClient::new(server.clone())));
+    }
+}
+
 struct AppState {
     cache: ThreadSafeCache,
     dictionary_service: Arc<DictionaryService>,
     prefetch_manager: Arc<PrefetchManager>,
+    ai_client: Arc<AiClient>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -111,6 +265,18 @@ pub fn run() {
         .unwrap_or_else(|_| "http://localhost:3001".to_string());
     let dictionary_service = Arc::new(DictionaryService::new(cache.clone(), api_base_url));
     
+    // AI enrichment uses its own cache so contextual answers never shadow base definitions,
+    // but the same endpoints as lookups so it fails over with them
+    let ai_cache = create_ai_cache(1_000);
+    let ai_client = Arc::new(AiClient::new(dictionary_service.api_client(), ai_cache.clone()));
+    
+    // Batch AI jobs feed the same AI cache once they complete
+    let ai_job_manager = Arc::new(AiJobManager::new(dictionary_service.api_client(), ai_cache));
+    
+    // Watch API health so lookups can switch to offline-first when it goes away
+    let health_monitor = Arc::new(ApiHealthMonitor::new(dictionary_service.clone()));
+    let api_client = dictionary_service.api_client();
+    
     // Create prefetch manager
     let prefetch_manager = Arc::new(PrefetchManager::new(dictionary_service.clone()));
     
@@ -124,25 +290,66 @@ pub fn run() {
     
     let app_state = AppState {
         cache: cache.clone(),
-        dictionary_service,
+        dictionary_service: dictionary_service.clone(),
         prefetch_manager,
+        ai_client,
     };
     
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(app_state)
-        .invoke_handler(tauri::generate_handler![greet, lookup_word, cache_stats, search_words, get_performance_stats, reset_performance_stats, get_settings, save_settings, queue_prefetch, get_prefetch_stats, clear_prefetch_queue])
+        .manage(ai_job_manager.clone())
+        .manage(health_monitor.clone())
+        .manage(api_client)
+        .manage(dictionary_service)
+        .invoke_handler(tauri::generate_handler![greet, lookup_word, cache_stats, search_words, reverse_search, pattern_search, find_anagrams, sounds_like, enrich_word, get_performance_stats, reset_performance_stats, get_settings, save_settings, queue_prefetch, get_prefetch_stats, clear_prefetch_queue, submit_ai_batch, get_ai_jobs, cancel_ai_job, get_ai_job_stats, get_api_status, get_endpoint_stats, list_stardict_dictionaries, install_stardict_dictionaries, set_stardict_enabled, get_dict_databases, dict_define, dict_match, import_kaikki, get_word_details, wordnet_synsets, wordnet_related, import_xml_dictionary, import_cmudict, get_pronunciations, find_rhymes, list_bilingual_packs, import_bilingual_pack, lookup_identifier, pronounce_word, get_glossary, add_glossary_entry, update_glossary_entry, delete_glossary_entry, import_glossary, export_glossary, lookup_in_context])
         .setup(move |app| {
             // Get the app handle and then the state
             let handle = app.handle();
             let dict_service = handle.state::<AppState>().dictionary_service.clone();
             
+            let settings = SettingsManager::new(handle)
+                .map(|manager| manager.get_settings().clone())
+                .unwrap_or_default();
+            
+            // Endpoints from settings replace the default, unless DICTIONARY_API_URL pins one
+            if std::env::var("DICTIONARY_API_URL").is_err() {
+                dict_service.api_client().set_endpoints(settings.api.endpoints.clone());
+            }
+            
+            register_local_sources(handle, &dict_service, &settings);
+            dict_service.set_language_pairs(settings.language_pairs.clone());
+            dict_service.set_identifier_capture(settings.behavior.identifier_capture);
+            dict_service.set_api_suggestions(settings.sources.api_suggestions);
+            
+            // The user's own entries, kept in glossary.json
+            let glossary = Arc::new(Glossary::load(handle.path().app_data_dir().ok().map(|dir| dir.join("glossary.json"))));
+            dict_service.set_glossary(glossary.clone(), settings.sources.glossary_precedence);
+            app.manage(glossary);
+            
+            // Synthesized pronunciations are cached; they can always be regenerated
+            let speech_cache = handle.path().app_cache_dir()
+                .unwrap_or_else(|_| std::env::temp_dir().join("lightning-dictionary"))
+                .join("pronunciations");
+            app.manage(Arc::new(Speaker::new(speech_cache, settings.speech.clone())));
+            if let Ok(dir) = handle.path().app_data_dir() {
+                dict_service.load_search_history(dir.join("search_history.json"));
+            }
+            wordnet::load_in_background(handle.clone(), dict_service.clone(), &settings);
+            let ai_client = handle.state::<AppState>().ai_client.clone();
+            
             // Setup hotkey manager with dictionary service
-            match HotkeyManager::setup(app, dict_service) {
+            match HotkeyManager::setup(app, dict_service, ai_client) {
                 Ok(_) => println!("Hotkey manager setup successfully"),
                 Err(e) => eprintln!("Failed to setup hotkey manager: {}", e),
             }
+            
+            // Resume persisted AI batch jobs and start polling
+            ai_job_manager.clone().start(handle.clone());
+            
+            // Start probing /health; emits api-status-changed on transitions
+            health_monitor.clone().start(handle.clone());
             Ok(())
         })
         .run(tauri::generate_context!())
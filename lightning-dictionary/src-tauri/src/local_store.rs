@@ -0,0 +1,351 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use flate2::read::GzDecoder;
+use rusqlite::{params, Connection, OpenFlags, Transaction};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::sqlite_source::SqliteSource;
+
+/// Source name the import store is registered under
+pub const LOCAL_STORE_SOURCE: &str = "local-store";
+// Issues beyond this are only counted, so progress events stay small
+const MAX_REPORTED_ISSUES: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WordForm {
+    pub form: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Translation {
+    pub lang: String,
+    pub code: Option<String>,
+    pub word: String,
+    pub sense: Option<String>,
+}
+
+/// One headword/part-of-speech pair as produced by an importer
+#[derive(Debug, Clone, Default)]
+pub struct StoreEntry {
+    pub word: String,
+    pub pos: String,
+    pub definitions: Vec<String>,
+    pub examples: Vec<String>,
+    pub pronunciation: Option<String>,
+    pub etymology: Option<String>,
+    pub forms: Vec<WordForm>,
+    pub translations: Vec<Translation>,
+    pub cross_references: Vec<String>,
+}
+
+/// Everything the store knows about one entry, for the details view
+#[derive(Debug, Clone, Serialize)]
+pub struct WordDetails {
+    pub word: String,
+    pub pos: String,
+    pub definitions: Vec<String>,
+    pub examples: Vec<String>,
+    pub pronunciation: Option<String>,
+    pub etymology: Option<String>,
+    pub forms: Vec<WordForm>,
+    pub translations: Vec<Translation>,
+    pub cross_references: Vec<String>,
+    pub origin: String,
+}
+
+/// An entry an importer had to skip, with where it was in the file
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportIssue {
+    pub line: u64,
+    pub message: String,
+}
+
+/// Payload of the `import-progress` event shared by all importers
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportProgress {
+    pub format: String,
+    pub path: String,
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub entries_imported: u64,
+    pub entries_skipped: u64,
+    pub issues: Vec<ImportIssue>,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+impl ImportProgress {
+    /// Skip a malformed entry, keeping the first few for the user to fix
+    pub fn report_issue(&mut self, line: u64, message: String) {
+        self.entries_skipped += 1;
+        if self.issues.len() < MAX_REPORTED_ISSUES {
+            self.issues.push(ImportIssue { line, message });
+        }
+    }
+}
+
+/// Writable SQLite store that importers fill. Its `words` table has the
+/// columns `SqliteSource` expects, so lookups go through that reader.
+pub struct LocalStore {
+    connection: Connection,
+    path: PathBuf,
+}
+
+impl LocalStore {
+    pub fn open(path: &Path) -> DictionaryResult<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| store_error(path, &e.to_string()))?;
+        }
+
+        let connection = Connection::open(path).map_err(|e| store_error(path, &e.to_string()))?;
+        // WAL lets the read-only lookup connection keep working mid-import
+        connection.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS words (
+                 id INTEGER PRIMARY KEY,
+                 word TEXT NOT NULL COLLATE NOCASE,
+                 rank INTEGER,
+                 pos TEXT,
+                 frequency INTEGER,
+                 definitions TEXT,
+                 pronunciation TEXT,
+                 examples TEXT,
+                 etymology TEXT,
+                 forms TEXT,
+                 translations TEXT,
+                 cross_references TEXT,
+                 origin TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_words_word ON words(word);
+             CREATE INDEX IF NOT EXISTS idx_words_origin ON words(origin);",
+        ).map_err(|e| store_error(path, &e.to_string()))?;
+
+        Ok(Self {
+            connection,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Start replacing everything previously imported from `origin`.
+    /// Old and new rows swap in one transaction when `finish` commits; an
+    /// import dropped before that leaves the old rows in place.
+    pub fn import(&mut self, origin: &str) -> DictionaryResult<StoreImport<'_>> {
+        let path = self.path.clone();
+        let transaction = self.connection.transaction().map_err(|e| store_error(&path, &e.to_string()))?;
+        transaction.execute("DELETE FROM words WHERE origin = ?1", params![origin])
+            .map_err(|e| store_error(&path, &e.to_string()))?;
+
+        Ok(StoreImport {
+            transaction,
+            path,
+            origin: origin.to_string(),
+        })
+    }
+
+    pub fn details(&self, word: &str) -> DictionaryResult<Vec<WordDetails>> {
+        let mut statement = self.connection
+            .prepare_cached(
+                "SELECT word, pos, definitions, examples, pronunciation, etymology, forms, translations, cross_references, origin
+                 FROM words WHERE word = ?1 ORDER BY id",
+            )
+            .map_err(|e| store_error(&self.path, &e.to_string()))?;
+
+        let rows = statement
+            .query_map(params![word.trim()], |row| {
+                Ok(WordDetails {
+                    word: row.get(0)?,
+                    pos: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    definitions: from_json(row.get(2)?),
+                    examples: from_json(row.get(3)?),
+                    pronunciation: row.get(4)?,
+                    etymology: row.get(5)?,
+                    forms: from_json(row.get(6)?),
+                    translations: from_json(row.get(7)?),
+                    cross_references: from_json(row.get(8)?),
+                    origin: row.get(9)?,
+                })
+            })
+            .map_err(|e| store_error(&self.path, &e.to_string()))?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(rows)
+    }
+}
+
+pub struct StoreImport<'a> {
+    transaction: Transaction<'a>,
+    path: PathBuf,
+    origin: String,
+}
+
+impl StoreImport<'_> {
+    pub fn insert(&mut self, entry: &StoreEntry) -> DictionaryResult<()> {
+        self.transaction
+            .prepare_cached(
+                "INSERT INTO words (word, pos, definitions, pronunciation, examples, etymology, forms, translations, cross_references, origin)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )
+            .and_then(|mut statement| statement.execute(params![
+                entry.word,
+                entry.pos,
+                to_json(&entry.definitions),
+                entry.pronunciation,
+                to_json(&entry.examples),
+                entry.etymology,
+                to_json(&entry.forms),
+                to_json(&entry.translations),
+                to_json(&entry.cross_references),
+                self.origin,
+            ]))
+            .map_err(|e| store_error(&self.path, &e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> DictionaryResult<()> {
+        self.transaction.commit()
+            .map_err(|e| store_error(&self.path, &e.to_string()))
+    }
+}
+
+fn to_json<T: Serialize>(values: &[T]) -> String {
+    serde_json::to_string(values).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn from_json<T: serde::de::DeserializeOwned + Default>(json: Option<String>) -> T {
+    json.and_then(|j| serde_json::from_str(&j).ok()).unwrap_or_default()
+}
+
+fn store_error(path: &Path, message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: LOCAL_STORE_SOURCE.to_string(),
+        message: format!("{}: {}", path.display(), message),
+    }
+}
+
+/// Counts bytes pulled from the underlying file, so progress stays
+/// accurate even when a decompressor sits on top of it.
+struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Open a dump for streaming, transparently gunzipping `.gz` files.
+/// Returns the reader, a live count of file bytes consumed and the file size.
+pub fn open_import_file(path: &Path) -> io::Result<(Box<dyn BufRead + Send>, Arc<AtomicU64>, u64)> {
+    let file = File::open(path)?;
+    let total = file.metadata()?.len();
+    let count = Arc::new(AtomicU64::new(0));
+    let counting = CountingReader { inner: file, count: count.clone() };
+
+    let reader: Box<dyn BufRead + Send> = if path.extension().and_then(|e| e.to_str()) == Some("gz") {
+        Box::new(BufReader::with_capacity(256 * 1024, GzDecoder::new(counting)))
+    } else {
+        Box::new(BufReader::with_capacity(256 * 1024, counting))
+    };
+
+    Ok((reader, count, total))
+}
+
+pub fn local_store_path<R: Runtime>(handle: &AppHandle<R>) -> Option<PathBuf> {
+    handle.path().app_data_dir().ok().map(|dir| dir.join("dictionaries").join("local.db"))
+}
+
+/// (Re)register the store as a lookup source, e.g. after an import
+pub fn register_local_store(dictionary_service: &DictionaryService, path: &Path) {
+    dictionary_service.remove_source(LOCAL_STORE_SOURCE);
+    match SqliteSource::open(path) {
+        Ok(source) => dictionary_service.add_source(Arc::new(source.with_name(LOCAL_STORE_SOURCE))),
+        Err(e) => e.log_error(),
+    }
+}
+
+/// Run an importer on a background thread: opens the file and the store,
+/// forwards progress as `import-progress` events, and registers the store
+/// as a lookup source once the import commits.
+pub fn spawn_import<F>(
+    app_handle: AppHandle,
+    dictionary_service: Arc<DictionaryService>,
+    source_path: PathBuf,
+    format: &str,
+    import: F,
+) -> Result<(), String>
+where
+    F: FnOnce(Box<dyn BufRead + Send>, &AtomicU64, &mut LocalStore, &mut ImportProgress, &mut dyn FnMut(&ImportProgress)) -> DictionaryResult<()>
+        + Send
+        + 'static,
+{
+    let store_path = local_store_path(&app_handle).ok_or("Failed to get app data directory")?;
+    if !source_path.is_file() {
+        return Err(format!("File not found: {}", source_path.display()));
+    }
+
+    let mut progress = ImportProgress {
+        format: format.to_string(),
+        path: source_path.display().to_string(),
+        ..Default::default()
+    };
+
+    std::thread::spawn(move || {
+        let mut emit = |p: &ImportProgress| {
+            let _ = app_handle.emit("import-progress", p.clone());
+        };
+
+        let result = open_import_file(&source_path)
+            .map_err(|e| store_error(&source_path, &e.to_string()))
+            .and_then(|(reader, bytes_read, total_bytes)| {
+                progress.total_bytes = total_bytes;
+                let mut store = LocalStore::open(&store_path)?;
+                import(reader, &bytes_read, &mut store, &mut progress, &mut emit)
+            });
+
+        match result {
+            Ok(()) => {
+                println!(
+                    "Imported {} {} entries from {} ({} skipped)",
+                    progress.entries_imported, progress.format, progress.path, progress.entries_skipped
+                );
+                register_local_store(&dictionary_service, &store_path);
+            }
+            Err(e) => {
+                e.log_error();
+                progress.error = Some(e.user_message());
+            }
+        }
+
+        progress.done = true;
+        emit(&progress);
+    });
+
+    Ok(())
+}
+
+/// Tauri command to read every stored entry for a word, including
+/// etymology, forms and translations that `Definition` doesn't carry
+#[tauri::command]
+pub fn get_word_details(word: String, app_handle: AppHandle) -> Result<Vec<WordDetails>, String> {
+    let path = local_store_path(&app_handle).ok_or("Failed to get app data directory")?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let connection = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| e.to_string())?;
+    LocalStore { connection, path }.details(&word).map_err(|e| e.user_message())
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+use crate::language::ENGLISH;
+
+/// How a surface form relates to its dictionary headword
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InflectionForm {
+    Plural,
+    Possessive,
+    ThirdPersonSingular,
+    PastTense,
+    PastParticiple,
+    PresentParticiple,
+    Comparative,
+    Superlative,
+}
+
+impl InflectionForm {
+    fn describe(&self) -> &'static str {
+        match self {
+            InflectionForm::Plural => "plural of",
+            InflectionForm::Possessive => "possessive of",
+            InflectionForm::ThirdPersonSingular => "third-person singular of",
+            InflectionForm::PastTense => "past tense of",
+            InflectionForm::PastParticiple => "past participle of",
+            InflectionForm::PresentParticiple => "present participle of",
+            InflectionForm::Comparative => "comparative of",
+            InflectionForm::Superlative => "superlative of",
+        }
+    }
+}
+
+/// A candidate headword for a surface form, e.g. "ran" -> "run"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inflection {
+    pub surface: String,
+    pub lemma: String,
+    pub form: InflectionForm,
+    /// Ready to show in the popup, e.g. "ran → past tense of run"
+    pub note: String,
+}
+
+impl Inflection {
+    fn new(surface: &str, lemma: &str, form: InflectionForm) -> Self {
+        Self {
+            surface: surface.to_string(),
+            lemma: lemma.to_string(),
+            form,
+            note: format!("{} → {} {}", surface, form.describe(), lemma),
+        }
+    }
+
+    /// "-s" is a plural on nouns but a verb ending on verbs; settle it
+    /// once the lemma's part of speech is known
+    pub fn resolve_pos(&mut self, pos: &str) {
+        let pos = pos.trim().to_lowercase();
+        if self.form == InflectionForm::Plural && (pos == "v" || pos.starts_with("verb")) {
+            *self = Inflection::new(&self.surface, &self.lemma, InflectionForm::ThirdPersonSingular);
+        }
+    }
+}
+
+/// Maps surface forms to the headwords a dictionary lists them under.
+/// One implementation per language; candidates are best first and
+/// unverified, so the caller checks each against its sources.
+pub trait Lemmatizer: Send + Sync {
+    /// ISO 639-1 code of the language whose words this handles
+    fn language(&self) -> &str;
+
+    fn candidates(&self, word: &str) -> Vec<Inflection>;
+}
+
+// (surface, lemma, form); consulted before the suffix rules
+const ENGLISH_EXCEPTIONS: &[(&str, &str, InflectionForm)] = {
+    use InflectionForm::*;
+    &[
+        // Verbs
+        ("is", "be", ThirdPersonSingular), ("was", "be", PastTense), ("were", "be", PastTense), ("been", "be", PastParticiple),
+        ("has", "have", ThirdPersonSingular), ("had", "have", PastTense), ("does", "do", ThirdPersonSingular),
+        ("did", "do", PastTense), ("done", "do", PastParticiple), ("goes", "go", ThirdPersonSingular),
+        ("went", "go", PastTense), ("gone", "go", PastParticiple), ("ran", "run", PastTense),
+        ("came", "come", PastTense), ("saw", "see", PastTense), ("seen", "see", PastParticiple),
+        ("took", "take", PastTense), ("taken", "take", PastParticiple), ("gave", "give", PastTense),
+        ("given", "give", PastParticiple), ("ate", "eat", PastTense), ("eaten", "eat", PastParticiple),
+        ("wrote", "write", PastTense), ("written", "write", PastParticiple), ("spoke", "speak", PastTense),
+        ("spoken", "speak", PastParticiple), ("broke", "break", PastTense), ("broken", "break", PastParticiple),
+        ("chose", "choose", PastTense), ("chosen", "choose", PastParticiple), ("drove", "drive", PastTense),
+        ("driven", "drive", PastParticiple), ("rode", "ride", PastTense), ("ridden", "ride", PastParticiple),
+        ("rose", "rise", PastTense), ("risen", "rise", PastParticiple), ("fell", "fall", PastTense),
+        ("fallen", "fall", PastParticiple), ("flew", "fly", PastTense), ("flown", "fly", PastParticiple),
+        ("knew", "know", PastTense), ("known", "know", PastParticiple), ("grew", "grow", PastTense),
+        ("grown", "grow", PastParticiple), ("threw", "throw", PastTense), ("thrown", "throw", PastParticiple),
+        ("drew", "draw", PastTense), ("drawn", "draw", PastParticiple), ("began", "begin", PastTense),
+        ("begun", "begin", PastParticiple), ("drank", "drink", PastTense), ("drunk", "drink", PastParticiple),
+        ("sang", "sing", PastTense), ("sung", "sing", PastParticiple), ("swam", "swim", PastTense),
+        ("swum", "swim", PastParticiple), ("forgot", "forget", PastTense), ("forgotten", "forget", PastParticiple),
+        ("got", "get", PastTense), ("gotten", "get", PastParticiple), ("wore", "wear", PastTense),
+        ("worn", "wear", PastParticiple), ("tore", "tear", PastTense), ("torn", "tear", PastParticiple),
+        ("hid", "hide", PastTense), ("hidden", "hide", PastParticiple), ("bit", "bite", PastTense),
+        ("bitten", "bite", PastParticiple), ("shook", "shake", PastTense), ("shaken", "shake", PastParticiple),
+        ("woke", "wake", PastTense), ("woken", "wake", PastParticiple), ("froze", "freeze", PastTense),
+        ("frozen", "freeze", PastParticiple), ("stole", "steal", PastTense), ("stolen", "steal", PastParticiple),
+        ("made", "make", PastTense), ("said", "say", PastTense), ("paid", "pay", PastTense),
+        ("laid", "lay", PastTense), ("thought", "think", PastTense), ("brought", "bring", PastTense),
+        ("bought", "buy", PastTense), ("caught", "catch", PastTense), ("taught", "teach", PastTense),
+        ("fought", "fight", PastTense), ("sought", "seek", PastTense), ("found", "find", PastTense),
+        ("told", "tell", PastTense), ("sold", "sell", PastTense), ("held", "hold", PastTense),
+        ("stood", "stand", PastTense), ("understood", "understand", PastTense), ("left", "leave", PastTense),
+        ("felt", "feel", PastTense), ("kept", "keep", PastTense), ("slept", "sleep", PastTense),
+        ("meant", "mean", PastTense), ("met", "meet", PastTense), ("sent", "send", PastTense),
+        ("spent", "spend", PastTense), ("built", "build", PastTense), ("lost", "lose", PastTense),
+        ("led", "lead", PastTense), ("fed", "feed", PastTense), ("fled", "flee", PastTense),
+        ("heard", "hear", PastTense), ("sat", "sit", PastTense), ("won", "win", PastTense),
+        ("struck", "strike", PastTense), ("stuck", "stick", PastTense), ("hung", "hang", PastTense),
+        ("dug", "dig", PastTense), ("spun", "spin", PastTense), ("lit", "light", PastTense),
+        // Nouns
+        ("men", "man", Plural), ("women", "woman", Plural), ("children", "child", Plural),
+        ("people", "person", Plural), ("feet", "foot", Plural), ("teeth", "tooth", Plural),
+        ("geese", "goose", Plural), ("mice", "mouse", Plural), ("lice", "louse", Plural),
+        ("oxen", "ox", Plural), ("dice", "die", Plural), ("criteria", "criterion", Plural),
+        ("phenomena", "phenomenon", Plural), ("cacti", "cactus", Plural), ("fungi", "fungus", Plural),
+        ("nuclei", "nucleus", Plural), ("radii", "radius", Plural), ("analyses", "analysis", Plural),
+        ("crises", "crisis", Plural), ("theses", "thesis", Plural), ("indices", "index", Plural),
+        ("matrices", "matrix", Plural), ("appendices", "appendix", Plural), ("data", "datum", Plural),
+        // Adjectives and adverbs
+        ("better", "good", Comparative), ("best", "good", Superlative), ("worse", "bad", Comparative),
+        ("worst", "bad", Superlative), ("less", "little", Comparative), ("least", "little", Superlative),
+        ("more", "much", Comparative), ("most", "much", Superlative), ("further", "far", Comparative),
+        ("furthest", "far", Superlative), ("farther", "far", Comparative), ("farthest", "far", Superlative),
+        ("elder", "old", Comparative), ("eldest", "old", Superlative),
+    ]
+};
+
+// (suffix, replacement, form), tried in order. A silent e or doubled
+// consonant before "-ed", "-ing", "-er" and "-est" is worked out from the
+// stem instead.
+const ENGLISH_SUFFIXES: &[(&str, &str, InflectionForm)] = {
+    use InflectionForm::*;
+    &[
+        ("'s", "", Possessive), ("s'", "s", Possessive),
+        ("ies", "y", Plural), ("ves", "f", Plural), ("ves", "fe", Plural),
+        ("s", "", Plural), ("es", "", Plural),
+        ("ied", "y", PastTense), ("ed", "", PastTense),
+        ("ying", "ie", PresentParticiple), ("ing", "", PresentParticiple),
+        ("ier", "y", Comparative), ("er", "", Comparative),
+        ("iest", "y", Superlative), ("est", "", Superlative),
+    ]
+};
+
+// Lemmas shorter than this are almost always false hits ("bed" -> "be")
+const MIN_LEMMA: usize = 3;
+
+fn is_vowel(c: char) -> bool {
+    "aeiou".contains(c)
+}
+
+/// Lemmas for a stem left by "-ed", "-ing", "-er" or "-est", best first:
+/// hopped -> hop, hoped -> hope, walked -> walk
+fn stem_lemmas(stem: &str) -> Vec<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    match chars[..] {
+        // Doubled final consonant: stopped -> stop, running -> run, bigger -> big
+        [.., a, b] if a == b && !"aeiouls".contains(a) => {
+            vec![stem[..stem.len() - a.len_utf8()].to_string(), stem.to_string()]
+        }
+        // One syllable ending consonant-vowel-consonant lost a silent e:
+        // hoped -> hope, taping -> tape, but opened -> open
+        [.., a, b, c] if !is_vowel(a) && is_vowel(b) && !is_vowel(c) && !"wxy".contains(c)
+            && chars.windows(2).filter(|w| is_vowel(w[0]) && !is_vowel(w[1])).count() == 1 => {
+            vec![format!("{}e", stem), stem.to_string()]
+        }
+        _ => vec![stem.to_string(), format!("{}e", stem)],
+    }
+}
+
+pub struct EnglishLemmatizer;
+
+impl Lemmatizer for EnglishLemmatizer {
+    fn language(&self) -> &str {
+        ENGLISH
+    }
+
+    fn candidates(&self, word: &str) -> Vec<Inflection> {
+        let word = word.trim();
+
+        // Phrasal verbs inflect their first word: "gave up" -> "give up"
+        if let Some((head, rest)) = word.split_once(char::is_whitespace) {
+            return self.candidates(head).into_iter()
+                .map(|c| Inflection::new(word, &format!("{} {}", c.lemma, rest.trim()), c.form))
+                .collect();
+        }
+
+        let lower = word.to_lowercase().replace('’', "'");
+        let mut candidates: Vec<Inflection> = ENGLISH_EXCEPTIONS.iter()
+            .filter(|(surface, _, _)| *surface == lower)
+            .map(|(_, lemma, form)| Inflection::new(word, lemma, *form))
+            .collect();
+
+        for (suffix, replacement, form) in ENGLISH_SUFFIXES {
+            let Some(stem) = lower.strip_suffix(suffix) else { continue };
+            if stem.is_empty() || stem.ends_with('\'') || (stem.ends_with('s') && *suffix == "s") {
+                continue;
+            }
+            // Only lie, die, tie and vie: "playing" isn't "plaie"
+            if *suffix == "ying" && stem.chars().any(is_vowel) {
+                continue;
+            }
+
+            let lemmas = if matches!(*suffix, "ed" | "ing" | "er" | "est") {
+                stem_lemmas(stem)
+            } else {
+                vec![format!("{}{}", stem, replacement)]
+            };
+            candidates.extend(lemmas.iter()
+                .filter(|lemma| lemma.chars().count() >= MIN_LEMMA)
+                .map(|lemma| Inflection::new(word, lemma, *form)));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        candidates.retain(|c| c.lemma != lower && seen.insert(c.lemma.clone()));
+        candidates
+    }
+}
+
+/// Lemmatizers for every supported language, English first
+pub fn lemmatizers() -> Vec<Box<dyn Lemmatizer>> {
+    vec![Box::new(EnglishLemmatizer)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lemmas(word: &str) -> Vec<String> {
+        EnglishLemmatizer.candidates(word).into_iter().map(|c| c.lemma).collect()
+    }
+
+    #[test]
+    fn test_english_candidates() {
+        assert_eq!(lemmas("ran"), vec!["run"]);
+        assert_eq!(lemmas("geese")[0], "goose");
+        assert_eq!(lemmas("better")[0], "good");
+        assert_eq!(lemmas("studies")[0], "study");
+        assert!(lemmas("stopped").contains(&"stop".to_string()));
+        assert!(lemmas("running").contains(&"run".to_string()));
+        assert!(lemmas("baking").contains(&"bake".to_string()));
+        assert!(lemmas("wolves").contains(&"wolf".to_string()));
+        assert!(lemmas("boxes").contains(&"box".to_string()));
+        assert_eq!(lemmas("cat's"), vec!["cat"]);
+        assert_eq!(lemmas("hoped")[0], "hope");
+        assert_eq!(lemmas("hopped")[0], "hop");
+        assert_eq!(lemmas("hoping")[0], "hope");
+        assert_eq!(lemmas("taped")[0], "tape");
+        assert_eq!(lemmas("planed")[0], "plane");
+        assert_eq!(lemmas("opened")[0], "open");
+        assert_eq!(lemmas("walked")[0], "walk");
+        assert_eq!(lemmas("lying")[0], "lie");
+        assert_eq!(lemmas("dying")[0], "die");
+        assert_eq!(lemmas("tied")[0], "tie");
+        assert_eq!(lemmas("playing")[0], "play");
+        assert!(lemmas("glass").is_empty());
+        assert!(lemmas("bed").is_empty());
+
+        assert_eq!(lemmas("gave up"), vec!["give up"]);
+        assert!(lemmas("in spite of").is_empty());
+
+        let ran = &EnglishLemmatizer.candidates("Ran")[0];
+        assert_eq!(ran.note, "Ran → past tense of run");
+
+        let mut walks = EnglishLemmatizer.candidates("walks").remove(0);
+        walks.resolve_pos("verb");
+        assert_eq!(walks.note, "walks → third-person singular of walk");
+    }
+}
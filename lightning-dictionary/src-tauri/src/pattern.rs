@@ -0,0 +1,232 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// A crossword-style search, e.g. `c?t`, `*ology`, `[bc]at`, `s[^aeiou]*`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PatternQuery {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression matched against the whole word
+    pub regex: bool,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// Letters every result must contain
+    pub include: String,
+    /// Letters no result may contain
+    pub exclude: String,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of results, most frequent first
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternPage {
+    pub words: Vec<String>,
+    pub total: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Letter(char),
+    AnyLetter,
+    AnyRun,
+    Set { letters: Vec<char>, negated: bool },
+}
+
+impl Element {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Element::Letter(letter) => *letter == c,
+            Element::AnyLetter | Element::AnyRun => true,
+            Element::Set { letters, negated } => letters.contains(&c) != *negated,
+        }
+    }
+}
+
+/// A compiled wildcard pattern, matched one letter at a time so it can
+/// follow the trie and drop whole subtrees as soon as they can't match.
+/// States are positions in the pattern.
+#[derive(Debug)]
+pub struct Wildcard {
+    elements: Vec<Element>,
+}
+
+impl Wildcard {
+    pub fn parse(pattern: &str) -> DictionaryResult<Self> {
+        let mut elements = Vec::new();
+        let mut chars = pattern.trim().chars().flat_map(char::to_lowercase);
+        while let Some(c) = chars.next() {
+            let element = match c {
+                '?' | '.' => Element::AnyLetter,
+                '*' => Element::AnyRun,
+                '[' => {
+                    let mut letters = Vec::new();
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if letters.is_empty() && !negated => negated = true,
+                            Some(letter) => letters.push(letter),
+                            None => return Err(DictionaryError::InvalidInput {
+                                message: format!("Unclosed '[' in pattern '{}'", pattern),
+                            }),
+                        }
+                    }
+                    Element::Set { letters, negated }
+                }
+                letter => Element::Letter(letter),
+            };
+            // "**" is the same as "*"
+            if !(element == Element::AnyRun && elements.last() == Some(&Element::AnyRun)) {
+                elements.push(element);
+            }
+        }
+
+        if elements.is_empty() {
+            return Err(DictionaryError::InvalidInput { message: "Pattern is empty".to_string() });
+        }
+        Ok(Self { elements })
+    }
+
+    /// Fewest letters a match can have
+    pub fn min_length(&self) -> usize {
+        self.elements.iter().filter(|e| **e != Element::AnyRun).count()
+    }
+
+    /// Most letters a match can have, if bounded
+    pub fn max_length(&self) -> Option<usize> {
+        (!self.elements.contains(&Element::AnyRun)).then_some(self.elements.len())
+    }
+
+    fn close(&self, states: &mut Vec<usize>) {
+        // A run may be empty, so being before one means being after it too
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if self.elements.get(state) == Some(&Element::AnyRun) && !states.contains(&(state + 1)) {
+                states.push(state + 1);
+            }
+            i += 1;
+        }
+    }
+
+    pub fn start(&self) -> Vec<usize> {
+        let mut states = vec![0];
+        self.close(&mut states);
+        states
+    }
+
+    /// States after reading `c`; empty when no match is possible any more
+    pub fn step(&self, states: &[usize], c: char) -> Vec<usize> {
+        let mut next = Vec::new();
+        for &state in states {
+            let Some(element) = self.elements.get(state) else { continue };
+            if element.matches(c) {
+                let to = if *element == Element::AnyRun { state } else { state + 1 };
+                if !next.contains(&to) {
+                    next.push(to);
+                }
+            }
+        }
+        self.close(&mut next);
+        next
+    }
+
+    pub fn is_match(&self, states: &[usize]) -> bool {
+        states.contains(&self.elements.len())
+    }
+}
+
+/// Filters applied to every candidate word, whatever the pattern kind
+pub struct WordFilter {
+    pub min_length: usize,
+    pub max_length: usize,
+    include: Vec<char>,
+    exclude: Vec<char>,
+}
+
+impl WordFilter {
+    pub fn accepts(&self, word: &str) -> bool {
+        let length = word.chars().count();
+        length >= self.min_length &&
+        length <= self.max_length &&
+        self.include.iter().all(|c| word.contains(*c)) &&
+        !self.exclude.iter().any(|c| word.contains(*c))
+    }
+}
+
+/// How a query is matched: walking the trie for wildcards, or a scan of
+/// every headword for regular expressions
+pub enum Matcher {
+    Wildcard(Wildcard),
+    Regex(Regex),
+}
+
+impl PatternQuery {
+    pub fn compile(&self) -> DictionaryResult<(Matcher, WordFilter)> {
+        let matcher = if self.regex {
+            let regex = Regex::new(&format!("^(?:{})$", self.pattern.trim()))
+                .map_err(|e| DictionaryError::InvalidInput { message: format!("Invalid regular expression: {}", e) })?;
+            Matcher::Regex(regex)
+        } else {
+            Matcher::Wildcard(Wildcard::parse(&self.pattern)?)
+        };
+
+        let filter = WordFilter {
+            min_length: self.min_length.unwrap_or(0),
+            max_length: self.max_length.unwrap_or(usize::MAX),
+            include: self.include.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect(),
+            exclude: self.exclude.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect(),
+        };
+        Ok((matcher, filter))
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// Tauri command for crossword-style pattern search over local headwords
+#[tauri::command]
+pub fn pattern_search(query: PatternQuery, dictionary_service: State<'_, Arc<DictionaryService>>) -> Result<PatternPage, String> {
+    dictionary_service.pattern_search(&query).map_err(|e| e.user_message())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, word: &str) -> bool {
+        let wildcard = Wildcard::parse(pattern).unwrap();
+        let states = word.chars().fold(wildcard.start(), |states, c| wildcard.step(&states, c));
+        wildcard.is_match(&states)
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(matches("c?t", "cat"));
+        assert!(!matches("c?t", "cart"));
+        assert!(matches("*ology", "biology"));
+        assert!(matches("*ology", "ology"));
+        assert!(matches("C*T", "cat"));
+        assert!(matches("c**t", "ct"));
+        assert!(matches("[bc]at", "bat"));
+        assert!(!matches("[bc]at", "rat"));
+        assert!(matches("s[^aeiou]*", "sky"));
+        assert!(!matches("s[^aeiou]*", "sea"));
+        assert!(Wildcard::parse("[ab").is_err());
+
+        let wildcard = Wildcard::parse("a?*b").unwrap();
+        assert_eq!((wildcard.min_length(), wildcard.max_length()), (3, None));
+        assert_eq!(Wildcard::parse("c?t").unwrap().max_length(), Some(3));
+    }
+}
@@ -33,6 +33,8 @@ mod tests {
                     pos: pos.to_string(),
                     definitions: defs.iter().map(|s| s.to_string()).collect(),
                     frequency: None,
+                    inflection: None,
+                    language: None,
                 });
             }
         }
@@ -0,0 +1,604 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::dictionary::DictionaryService;
+
+// Length of Double Metaphone keys, as in the original algorithm
+const METAPHONE_LENGTH: usize = 4;
+
+const L_R_N_M_B_H_F_V_W_SPACE: &[&str] = &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "];
+const ES_EP_EB_EL_EY_IB_IL_IN_IE_EI_ER: &[&str] = &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"];
+const L_T_K_S_N_M_B_Z: &[&str] = &["L", "T", "K", "S", "N", "M", "B", "Z"];
+
+/// Primary and alternate Double Metaphone keys of `word`. The alternate
+/// covers a second plausible pronunciation ("Smith": `SM0` / `XMT`) and is
+/// the same as the primary when there is none. Both are empty for words
+/// without Latin letters.
+pub fn double_metaphone(word: &str) -> (String, String) {
+    let value: Vec<char> = word.trim()
+        .chars()
+        .flat_map(char::to_uppercase)
+        .filter(|c| c.is_alphabetic() || *c == ' ')
+        .collect();
+    let mut metaphone = Metaphone { value, primary: String::new(), alternate: String::new() };
+    metaphone.encode();
+
+    let Metaphone { mut primary, mut alternate, .. } = metaphone;
+    primary.truncate(METAPHONE_LENGTH);
+    alternate.truncate(METAPHONE_LENGTH);
+    (primary, alternate)
+}
+
+/// American Soundex code of `word`, e.g. `R163` for "Robert"
+pub fn soundex(word: &str) -> String {
+    let code = |c: char| match c {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        _ => '0',
+    };
+    let mut letters = word.chars().filter(char::is_ascii_alphabetic).map(|c| c.to_ascii_uppercase());
+    let Some(first) = letters.next() else { return String::new() };
+
+    let mut key = first.to_string();
+    let mut last = code(first);
+    for c in letters {
+        match c {
+            // H and W don't separate letters with the same code; vowels do
+            'H' | 'W' => {}
+            'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => last = '0',
+            _ if code(c) != last => {
+                last = code(c);
+                key.push(last);
+            }
+            _ => {}
+        }
+        if key.len() == 4 {
+            return key;
+        }
+    }
+    format!("{:0<4}", key)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+}
+
+/// Lawrence Philips' Double Metaphone, following the reference
+/// implementation case by case
+struct Metaphone {
+    value: Vec<char>,
+    primary: String,
+    alternate: String,
+}
+
+impl Metaphone {
+    fn len(&self) -> isize {
+        self.value.len() as isize
+    }
+
+    fn at(&self, index: isize) -> char {
+        usize::try_from(index).ok().and_then(|i| self.value.get(i)).copied().unwrap_or('\0')
+    }
+
+    /// Whether the `length` letters at `start` are one of `options`
+    fn contains(&self, start: isize, length: isize, options: &[&str]) -> bool {
+        if start < 0 || start + length > self.len() {
+            return false;
+        }
+        let part: String = self.value[start as usize..(start + length) as usize].iter().collect();
+        options.contains(&part.as_str())
+    }
+
+    fn is_slavo_germanic(&self) -> bool {
+        let value: String = self.value.iter().collect();
+        value.contains('W') || value.contains('K') || value.contains("CZ") || value.contains("WITZ")
+    }
+
+    fn add(&mut self, both: &str) {
+        self.primary.push_str(both);
+        self.alternate.push_str(both);
+    }
+
+    fn add_pair(&mut self, primary: &str, alternate: &str) {
+        self.primary.push_str(primary);
+        self.alternate.push_str(alternate);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.len() >= METAPHONE_LENGTH && self.alternate.len() >= METAPHONE_LENGTH
+    }
+
+    fn encode(&mut self) {
+        let slavo_germanic = self.is_slavo_germanic();
+        let mut index: isize = 0;
+        if self.contains(0, 2, &["GN", "KN", "PN", "WR", "PS"]) {
+            index = 1;
+        }
+        if self.at(0) == 'X' {
+            self.add("S");
+            index = 1;
+        }
+
+        while !self.is_complete() && index < self.len() {
+            index = match self.at(index) {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if index == 0 {
+                        self.add("A");
+                    }
+                    index + 1
+                }
+                'B' => {
+                    self.add("P");
+                    if self.at(index + 1) == 'B' { index + 2 } else { index + 1 }
+                }
+                'Ç' => {
+                    self.add("S");
+                    index + 1
+                }
+                'C' => self.c(index),
+                'D' => self.d(index),
+                'F' => {
+                    self.add("F");
+                    if self.at(index + 1) == 'F' { index + 2 } else { index + 1 }
+                }
+                'G' => self.g(index, slavo_germanic),
+                'H' => self.h(index),
+                'J' => self.j(index, slavo_germanic),
+                'K' => {
+                    self.add("K");
+                    if self.at(index + 1) == 'K' { index + 2 } else { index + 1 }
+                }
+                'L' => self.l(index),
+                'M' => {
+                    self.add("M");
+                    if self.m_is_doubled(index) { index + 2 } else { index + 1 }
+                }
+                'N' => {
+                    self.add("N");
+                    if self.at(index + 1) == 'N' { index + 2 } else { index + 1 }
+                }
+                'Ñ' => {
+                    self.add("N");
+                    index + 1
+                }
+                'P' => self.p(index),
+                'Q' => {
+                    self.add("K");
+                    if self.at(index + 1) == 'Q' { index + 2 } else { index + 1 }
+                }
+                'R' => self.r(index, slavo_germanic),
+                'S' => self.s(index, slavo_germanic),
+                'T' => self.t(index),
+                'V' => {
+                    self.add("F");
+                    if self.at(index + 1) == 'V' { index + 2 } else { index + 1 }
+                }
+                'W' => self.w(index),
+                'X' => self.x(index),
+                'Z' => self.z(index, slavo_germanic),
+                _ => index + 1,
+            };
+        }
+    }
+
+    fn is_germanic_name(&self) -> bool {
+        self.contains(0, 4, &["VAN ", "VON "]) || self.contains(0, 3, &["SCH"])
+    }
+
+    fn c(&mut self, index: isize) -> isize {
+        if self.c_is_k(index) {
+            self.add("K");
+            index + 2
+        } else if index == 0 && self.contains(index, 6, &["CAESAR"]) {
+            self.add("S");
+            index + 2
+        } else if self.contains(index, 2, &["CH"]) {
+            self.ch(index)
+        } else if self.contains(index, 2, &["CZ"]) && !self.contains(index - 2, 4, &["WICZ"]) {
+            self.add_pair("S", "X");
+            index + 2
+        } else if self.contains(index + 1, 3, &["CIA"]) {
+            self.add("X");
+            index + 3
+        } else if self.contains(index, 2, &["CC"]) && !(index == 1 && self.at(0) == 'M') {
+            self.cc(index)
+        } else if self.contains(index, 2, &["CK", "CG", "CQ"]) {
+            self.add("K");
+            index + 2
+        } else if self.contains(index, 2, &["CI", "CE", "CY"]) {
+            if self.contains(index, 3, &["CIO", "CIE", "CIA"]) {
+                self.add_pair("S", "X");
+            } else {
+                self.add("S");
+            }
+            index + 2
+        } else {
+            self.add("K");
+            if self.contains(index + 1, 2, &[" C", " Q", " G"]) {
+                index + 3
+            } else if self.contains(index + 1, 1, &["C", "K", "Q"]) && !self.contains(index + 1, 2, &["CE", "CI"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    // "ACH" in words like "bacher", and "CHIA"
+    fn c_is_k(&self, index: isize) -> bool {
+        if self.contains(index, 4, &["CHIA"]) {
+            true
+        } else if index <= 1 || is_vowel(self.at(index - 2)) || !self.contains(index - 1, 3, &["ACH"]) {
+            false
+        } else {
+            let next = self.at(index + 2);
+            (next != 'I' && next != 'E') || self.contains(index - 2, 6, &["BACHER", "MACHER"])
+        }
+    }
+
+    fn cc(&mut self, index: isize) -> isize {
+        if self.contains(index + 2, 1, &["I", "E", "H"]) && !self.contains(index + 2, 2, &["HU"]) {
+            if (index == 1 && self.at(index - 1) == 'A') || self.contains(index - 1, 5, &["UCCEE", "UCCES"]) {
+                self.add("KS");
+            } else {
+                self.add("X");
+            }
+            index + 3
+        } else {
+            self.add("K");
+            index + 2
+        }
+    }
+
+    fn ch(&mut self, index: isize) -> isize {
+        if index > 0 && self.contains(index, 4, &["CHAE"]) {
+            self.add_pair("K", "X");
+        } else if self.ch_is_greek(index) || self.ch_is_k(index) {
+            self.add("K");
+        } else if index > 0 {
+            if self.contains(0, 2, &["MC"]) {
+                self.add("K");
+            } else {
+                self.add_pair("X", "K");
+            }
+        } else {
+            self.add("X");
+        }
+        index + 2
+    }
+
+    fn ch_is_greek(&self, index: isize) -> bool {
+        index == 0 &&
+        (self.contains(index + 1, 5, &["HARAC", "HARIS"]) || self.contains(index + 1, 3, &["HOR", "HYM", "HIA", "HEM"])) &&
+        !self.contains(0, 5, &["CHORE"])
+    }
+
+    fn ch_is_k(&self, index: isize) -> bool {
+        self.is_germanic_name() ||
+        self.contains(index - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"]) ||
+        self.contains(index + 2, 1, &["T", "S"]) ||
+        ((self.contains(index - 1, 1, &["A", "O", "U", "E"]) || index == 0) &&
+         (self.contains(index + 2, 1, L_R_N_M_B_H_F_V_W_SPACE) || index + 1 == self.len() - 1))
+    }
+
+    fn d(&mut self, index: isize) -> isize {
+        if self.contains(index, 2, &["DG"]) {
+            if self.contains(index + 2, 1, &["I", "E", "Y"]) {
+                self.add("J");
+                index + 3
+            } else {
+                self.add("TK");
+                index + 2
+            }
+        } else if self.contains(index, 2, &["DT", "DD"]) {
+            self.add("T");
+            index + 2
+        } else {
+            self.add("T");
+            index + 1
+        }
+    }
+
+    fn g(&mut self, index: isize, slavo_germanic: bool) -> isize {
+        let next = self.at(index + 1);
+        if next == 'H' {
+            self.gh(index)
+        } else if next == 'N' {
+            if index == 1 && is_vowel(self.at(0)) && !slavo_germanic {
+                self.add_pair("KN", "N");
+            } else if !self.contains(index + 2, 2, &["EY"]) && !slavo_germanic {
+                self.add_pair("N", "KN");
+            } else {
+                self.add("KN");
+            }
+            index + 2
+        } else if self.contains(index + 1, 2, &["LI"]) && !slavo_germanic {
+            self.add_pair("KL", "L");
+            index + 2
+        } else if (index == 0 && (next == 'Y' || self.contains(index + 1, 2, ES_EP_EB_EL_EY_IB_IL_IN_IE_EI_ER))) ||
+                  ((self.contains(index + 1, 2, &["ER"]) || next == 'Y') &&
+                   !self.contains(0, 6, &["DANGER", "RANGER", "MANGER"]) &&
+                   !self.contains(index - 1, 1, &["E", "I"]) &&
+                   !self.contains(index - 1, 3, &["RGY", "OGY"])) {
+            self.add_pair("K", "J");
+            index + 2
+        } else if self.contains(index + 1, 1, &["E", "I", "Y"]) || self.contains(index - 1, 4, &["AGGI", "OGGI"]) {
+            if self.is_germanic_name() || self.contains(index + 1, 2, &["ET"]) {
+                self.add("K");
+            } else if self.contains(index + 1, 3, &["IER"]) {
+                self.add("J");
+            } else {
+                self.add_pair("J", "K");
+            }
+            index + 2
+        } else if next == 'G' {
+            self.add("K");
+            index + 2
+        } else {
+            self.add("K");
+            index + 1
+        }
+    }
+
+    fn gh(&mut self, index: isize) -> isize {
+        if index > 0 && !is_vowel(self.at(index - 1)) {
+            self.add("K");
+        } else if index == 0 {
+            self.add(if self.at(index + 2) == 'I' { "J" } else { "K" });
+        } else if (index > 1 && self.contains(index - 2, 1, &["B", "H", "D"])) ||
+                  (index > 2 && self.contains(index - 3, 1, &["B", "H", "D"])) ||
+                  (index > 3 && self.contains(index - 4, 1, &["B", "H"])) {
+            // Silent, as in "bough", "broughton"
+        } else if index > 2 && self.at(index - 1) == 'U' && self.contains(index - 3, 1, &["C", "G", "L", "R", "T"]) {
+            // "laugh", "cough", "rough"
+            self.add("F");
+        } else if index > 0 && self.at(index - 1) != 'I' {
+            self.add("K");
+        }
+        index + 2
+    }
+
+    fn h(&mut self, index: isize) -> isize {
+        // Only kept between vowels or at the start before a vowel
+        if (index == 0 || is_vowel(self.at(index - 1))) && is_vowel(self.at(index + 1)) {
+            self.add("H");
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn j(&mut self, index: isize, slavo_germanic: bool) -> isize {
+        if self.contains(index, 4, &["JOSE"]) || self.contains(0, 4, &["SAN "]) {
+            if (index == 0 && self.at(index + 4) == ' ') || self.len() == 4 || self.contains(0, 4, &["SAN "]) {
+                self.add("H");
+            } else {
+                self.add_pair("J", "H");
+            }
+            return index + 1;
+        }
+
+        if index == 0 {
+            self.add_pair("J", "A");
+        } else if is_vowel(self.at(index - 1)) && !slavo_germanic && matches!(self.at(index + 1), 'A' | 'O') {
+            self.add_pair("J", "H");
+        } else if index == self.len() - 1 {
+            self.add_pair("J", "");
+        } else if !self.contains(index + 1, 1, L_T_K_S_N_M_B_Z) && !self.contains(index - 1, 1, &["S", "K", "L"]) {
+            self.add("J");
+        }
+        if self.at(index + 1) == 'J' { index + 2 } else { index + 1 }
+    }
+
+    fn l(&mut self, index: isize) -> isize {
+        if self.at(index + 1) != 'L' {
+            self.add("L");
+            return index + 1;
+        }
+
+        // Spanish "-illo", "-illa", "-alle"
+        let length = self.len();
+        let spanish = (index == length - 3 && self.contains(index - 1, 4, &["ILLO", "ILLA", "ALLE"])) ||
+            ((self.contains(length - 2, 2, &["AS", "OS"]) || self.contains(length - 1, 1, &["A", "O"])) &&
+             self.contains(index - 1, 4, &["ALLE"]));
+        if spanish {
+            self.add_pair("L", "");
+        } else {
+            self.add("L");
+        }
+        index + 2
+    }
+
+    fn m_is_doubled(&self, index: isize) -> bool {
+        self.at(index + 1) == 'M' ||
+        (self.contains(index - 1, 3, &["UMB"]) && (index + 1 == self.len() - 1 || self.contains(index + 2, 2, &["ER"])))
+    }
+
+    fn p(&mut self, index: isize) -> isize {
+        if self.at(index + 1) == 'H' {
+            self.add("F");
+            index + 2
+        } else {
+            self.add("P");
+            if self.contains(index + 1, 1, &["P", "B"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn r(&mut self, index: isize, slavo_germanic: bool) -> isize {
+        // French final "-ier", as in "Rogier"
+        if index == self.len() - 1 && !slavo_germanic &&
+           self.contains(index - 2, 2, &["IE"]) && !self.contains(index - 4, 2, &["ME", "MA"]) {
+            self.add_pair("", "R");
+        } else {
+            self.add("R");
+        }
+        if self.at(index + 1) == 'R' { index + 2 } else { index + 1 }
+    }
+
+    fn s(&mut self, index: isize, slavo_germanic: bool) -> isize {
+        if self.contains(index - 1, 3, &["ISL", "YSL"]) {
+            // Silent, as in "island", "carlisle"
+            index + 1
+        } else if index == 0 && self.contains(index, 5, &["SUGAR"]) {
+            self.add_pair("X", "S");
+            index + 1
+        } else if self.contains(index, 2, &["SH"]) {
+            self.add(if self.contains(index + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) { "S" } else { "X" });
+            index + 2
+        } else if self.contains(index, 3, &["SIO", "SIA"]) || self.contains(index, 4, &["SIAN"]) {
+            if slavo_germanic {
+                self.add("S");
+            } else {
+                self.add_pair("S", "X");
+            }
+            index + 3
+        } else if (index == 0 && self.contains(index + 1, 1, &["M", "N", "L", "W"])) || self.contains(index + 1, 1, &["Z"]) {
+            self.add_pair("S", "X");
+            if self.contains(index + 1, 1, &["Z"]) { index + 2 } else { index + 1 }
+        } else if self.contains(index, 2, &["SC"]) {
+            self.sc(index)
+        } else {
+            // French final "-ais", "-ois"
+            if index == self.len() - 1 && self.contains(index - 2, 2, &["AI", "OI"]) {
+                self.add_pair("", "S");
+            } else {
+                self.add("S");
+            }
+            if self.contains(index + 1, 1, &["S", "Z"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn sc(&mut self, index: isize) -> isize {
+        if self.at(index + 2) == 'H' {
+            if self.contains(index + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                if self.contains(index + 3, 2, &["ER", "EN"]) {
+                    self.add_pair("X", "SK");
+                } else {
+                    self.add("SK");
+                }
+            } else if index == 0 && !is_vowel(self.at(3)) && self.at(3) != 'W' {
+                self.add_pair("X", "S");
+            } else {
+                self.add("X");
+            }
+        } else if self.contains(index + 2, 1, &["I", "E", "Y"]) {
+            self.add("S");
+        } else {
+            self.add("SK");
+        }
+        index + 3
+    }
+
+    fn t(&mut self, index: isize) -> isize {
+        if self.contains(index, 4, &["TION"]) || self.contains(index, 3, &["TIA", "TCH"]) {
+            self.add("X");
+            index + 3
+        } else if self.contains(index, 2, &["TH"]) || self.contains(index, 3, &["TTH"]) {
+            if self.contains(index + 2, 2, &["OM", "AM"]) || self.is_germanic_name() {
+                self.add("T");
+            } else {
+                self.add_pair("0", "T");
+            }
+            index + 2
+        } else {
+            self.add("T");
+            if self.contains(index + 1, 1, &["T", "D"]) { index + 2 } else { index + 1 }
+        }
+    }
+
+    fn w(&mut self, index: isize) -> isize {
+        if self.contains(index, 2, &["WR"]) {
+            self.add("R");
+            return index + 2;
+        }
+
+        let next_is_vowel = is_vowel(self.at(index + 1));
+        if index == 0 && (next_is_vowel || self.contains(index, 2, &["WH"])) {
+            if next_is_vowel {
+                self.add_pair("A", "F");
+            } else {
+                self.add("A");
+            }
+        } else if (index == self.len() - 1 && is_vowel(self.at(index - 1))) ||
+                  self.contains(index - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"]) ||
+                  self.contains(0, 3, &["SCH"]) {
+            self.add_pair("", "F");
+        } else if self.contains(index, 4, &["WICZ", "WITZ"]) {
+            self.add_pair("TS", "FX");
+            return index + 4;
+        }
+        index + 1
+    }
+
+    fn x(&mut self, index: isize) -> isize {
+        if index == 0 {
+            self.add("S");
+            return index + 1;
+        }
+
+        // Silent in French endings like "breaux"
+        let french = index == self.len() - 1 &&
+            (self.contains(index - 3, 3, &["IAU", "EAU"]) || self.contains(index - 2, 2, &["AU", "OU"]));
+        if !french {
+            self.add("KS");
+        }
+        if self.contains(index + 1, 1, &["C", "X"]) { index + 2 } else { index + 1 }
+    }
+
+    fn z(&mut self, index: isize, slavo_germanic: bool) -> isize {
+        if self.at(index + 1) == 'H' {
+            self.add("J");
+            return index + 2;
+        }
+
+        if self.contains(index + 1, 2, &["ZO", "ZI", "ZA"]) || (slavo_germanic && index > 0 && self.at(index - 1) != 'T') {
+            self.add_pair("S", "TS");
+        } else {
+            self.add("S");
+        }
+        if self.at(index + 1) == 'Z' { index + 2 } else { index + 1 }
+    }
+}
+
+/// Tauri command to find local headwords that sound like `word`
+#[tauri::command]
+pub fn sounds_like(word: String, limit: Option<usize>, dictionary_service: State<'_, Arc<DictionaryService>>) -> Vec<String> {
+    dictionary_service.sounds_like(&word, limit.unwrap_or(20))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(primary: &str, alternate: &str) -> (String, String) {
+        (primary.to_string(), alternate.to_string())
+    }
+
+    #[test]
+    fn test_double_metaphone() {
+        assert_eq!(double_metaphone("Smith"), keys("SM0", "XMT"));
+        assert_eq!(double_metaphone("Schmidt"), keys("XMT", "SMT"));
+        assert_eq!(double_metaphone("knight"), keys("NT", "NT"));
+        assert_eq!(double_metaphone("Thompson"), keys("TMPS", "TMPS"));
+        assert_eq!(double_metaphone("phonetic"), double_metaphone("fonetik"));
+        assert_eq!(double_metaphone("laugh"), keys("LF", "LF"));
+        assert_eq!(double_metaphone("Xavier"), keys("SF", "SFR"));
+        assert_eq!(double_metaphone("Caesar"), keys("SSR", "SSR"));
+        assert_eq!(double_metaphone("日本"), keys("", ""));
+    }
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), "R163");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(soundex("Pfister"), "P236");
+        assert_eq!(soundex("Lee"), "L000");
+        assert_eq!(soundex(""), "");
+    }
+}
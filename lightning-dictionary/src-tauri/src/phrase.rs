@@ -0,0 +1,174 @@
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+use crate::tokenizer;
+
+/// Longest expression we try to look up ("on the other hand")
+pub const MAX_PHRASE_WORDS: usize = 5;
+
+/// An expression found around a selection
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhraseMatch {
+    /// The text as it appears in the context, e.g. "gave up"
+    pub phrase: String,
+    /// The form to look up, e.g. "give up"
+    pub headword: String,
+    /// Byte range of `phrase` in the context
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find the longest known expression of two or more words in `context`
+/// that covers the selection. `offset` is where the selection starts in
+/// `context`; without it the first occurrence of the selection's words,
+/// in any case, is used. Each word may
+/// also be swapped for one of its `lemmas`, so "gave up" finds "give up".
+pub fn longest_match(
+    selection: &str,
+    context: &str,
+    offset: Option<usize>,
+    is_known: impl Fn(&str) -> bool,
+    lemmas: impl Fn(&str) -> Vec<String>,
+) -> Option<PhraseMatch> {
+    let selection = selection.trim();
+    let tokens = tokenizer::word_ranges(context);
+    let words = lowercase_words(context, &tokens);
+
+    let (first, last) = match offset.filter(|o| context.get(*o..).is_some_and(|rest| rest.starts_with(selection))) {
+        Some(start) => {
+            let end = start + selection.len();
+            let first = tokens.iter().position(|(_, e)| *e > start)?;
+            let last = tokens.iter().rposition(|(s, _)| *s < end)?;
+            (first, last)
+        }
+        // Compared word by word, so byte positions stay those of `context`
+        // even where lowercasing changes a character's length ('İ')
+        None => {
+            let wanted = lowercase_words(selection, &tokenizer::word_ranges(selection));
+            if wanted.is_empty() {
+                return None;
+            }
+            let first = words.windows(wanted.len()).position(|window| window == wanted.as_slice())?;
+            (first, first + wanted.len() - 1)
+        }
+    };
+    if last < first {
+        return None;
+    }
+    let covered = last - first + 1;
+
+    // Longest first, then leftmost, then the surface form before lemma swaps
+    for len in (covered.max(2)..=MAX_PHRASE_WORDS).rev() {
+        let lowest = (last + 1).saturating_sub(len);
+        for from in lowest..=first {
+            let to = from + len;
+            if to > words.len() {
+                continue;
+            }
+
+            let span = &words[from..to];
+            let mut variants = vec![span.join(" ")];
+            for (i, word) in span.iter().enumerate() {
+                for lemma in lemmas(word) {
+                    let mut swapped = span.to_vec();
+                    swapped[i] = lemma;
+                    variants.push(swapped.join(" "));
+                }
+            }
+
+            if let Some(headword) = variants.into_iter().find(|v| is_known(v)) {
+                let (start, end) = (tokens[from].0, tokens[to - 1].1);
+                return Some(PhraseMatch {
+                    phrase: context[start..end].to_string(),
+                    headword,
+                    start,
+                    end,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// The words at `ranges` of `text`, normalized and lowercased for matching
+fn lowercase_words(text: &str, ranges: &[(usize, usize)]) -> Vec<String> {
+    ranges.iter().map(|(s, e)| tokenizer::normalize(&text[*s..*e]).to_lowercase()).collect()
+}
+
+#[derive(Serialize)]
+pub struct PhraseLookup {
+    /// The expression that was looked up instead of the bare selection
+    pub phrase: Option<PhraseMatch>,
+    pub definition: Definition,
+}
+
+/// Tauri command to look up a selection in the context it was taken
+/// from, preferring the longest known expression that contains it
+#[tauri::command]
+pub fn lookup_in_context(
+    selection: String,
+    context: String,
+    offset: Option<usize>,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<PhraseLookup, String> {
+    let (phrase, result) = dictionary_service.lookup_in_context(&selection, &context, offset, None);
+    let definition = result.map_err(|e| e.user_message())?;
+    dictionary_service.record_lookup(phrase.as_ref().map_or(&selection, |phrase| &phrase.headword));
+    Ok(PhraseLookup { phrase, definition })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN: &[&str] = &["give up", "in spite of", "ad hoc", "spite", "on the other hand"];
+
+    fn find(selection: &str, context: &str) -> Option<PhraseMatch> {
+        longest_match(selection, context, None, |p| KNOWN.contains(&p), |word| match word {
+            "gave" => vec!["give".to_string()],
+            _ => vec![],
+        })
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let found = find("spite", "He came in spite of the rain.").unwrap();
+        assert_eq!(found.headword, "in spite of");
+        assert_eq!(found.phrase, "in spite of");
+        assert_eq!(found.start, 8);
+
+        let found = find("gave", "She finally Gave  up on it").unwrap();
+        assert_eq!(found.headword, "give up");
+        assert_eq!(found.phrase, "Gave  up");
+
+        assert_eq!(find("hoc", "an ad hoc fix").unwrap().headword, "ad hoc");
+        assert_eq!(find("other", "On the other hand, no.").unwrap().headword, "on the other hand");
+        // A single known word is not a phrase match
+        assert!(find("rain", "in the rain").is_none());
+        assert!(find("snow", "in the rain").is_none());
+    }
+
+    #[test]
+    fn test_offset_picks_occurrence() {
+        let context = "give it up, then give up";
+        let found = longest_match("give", context, Some(17), |p| KNOWN.contains(&p), |_| vec![]).unwrap();
+        assert_eq!((found.start, found.end), (17, 24));
+        assert!(longest_match("give", context, Some(0), |p| KNOWN.contains(&p), |_| vec![]).is_none());
+    }
+
+    #[test]
+    fn test_match_without_offset_keeps_byte_positions() {
+        // Lowercasing 'İ' adds a byte, which used to shift every position after it
+        let context = "İstanbul: she GAVE UP there";
+        let found = find("gave up", context).unwrap();
+        assert_eq!(found.headword, "give up");
+        assert_eq!(&context[found.start..found.end], "GAVE UP");
+        assert_eq!(found.phrase, "GAVE UP");
+
+        assert!(find("give", "a forgive up").is_none());
+    }
+}
@@ -0,0 +1,871 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::anagram::{signature, Anagrams, LetterBag};
+use crate::phonetic::{double_metaphone, soundex};
+use crate::pattern::{Matcher, Wildcard, WordFilter};
+use crate::source::DictionarySource;
+
+// Nodes this shallow keep a precomputed best-first list, since their
+// subtrees can hold most of the vocabulary. Deeper subtrees are small
+// enough to walk on every keystroke.
+const TOP_DEPTH: usize = 3;
+const TOP_SIZE: usize = 50;
+// Oldest-looked-up words are dropped past this
+const HISTORY_LIMIT: usize = 2000;
+// Sources are usually registered in bursts; wait for the burst to finish
+const REBUILD_DELAY: Duration = Duration::from_millis(250);
+// Lookups this close together are written to the history file in one save
+const HISTORY_SAVE_DELAY: Duration = Duration::from_secs(1);
+// Sound-alike words may be this many edits further away than typos
+const PHONETIC_SLACK: usize = 1;
+// Letter groups OCR commonly mistakes for one another; each swap counts as one edit
+const OCR_CONFUSIONS: &[(&str, &str)] = &[("rn", "m"), ("m", "rn"), ("cl", "d"), ("d", "cl"), ("vv", "w"), ("w", "vv")];
+
+struct Term {
+    word: String,
+    rank: Option<u32>,
+}
+
+impl Term {
+    // Frequency rank first (unranked last), then shorter words, then alphabetical
+    fn static_key(&self) -> (u32, usize, &str) {
+        (self.rank.unwrap_or(u32::MAX), self.word.len(), &self.word)
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    children: Vec<(char, u32)>,
+    term: Option<u32>,
+    top: Vec<u32>,
+}
+
+#[derive(Clone, Copy)]
+struct FuzzyQuery<'a> {
+    chars: &'a [char],
+    max: usize,
+    prefix: bool,
+    limit: usize,
+}
+
+#[derive(Default)]
+struct Trie {
+    nodes: Vec<Node>,
+    terms: Vec<Term>,
+    // Sorted letters -> terms spelled with exactly those letters
+    signatures: HashMap<String, Vec<u32>>,
+    // Double Metaphone key (primary and alternate) -> terms
+    metaphone: HashMap<String, Vec<u32>>,
+    soundex: HashMap<String, Vec<u32>>,
+}
+
+impl Trie {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            terms: Vec::new(),
+            signatures: HashMap::new(),
+            metaphone: HashMap::new(),
+            soundex: HashMap::new(),
+        }
+    }
+
+    fn child(&self, node: u32, c: char) -> Option<u32> {
+        let children = &self.nodes[node as usize].children;
+        children.binary_search_by_key(&c, |(k, _)| *k).ok().map(|i| children[i].1)
+    }
+
+    fn find(&self, key: &str) -> Option<u32> {
+        key.chars().try_fold(0, |node, c| self.child(node, c))
+    }
+
+    fn insert(&mut self, word: &str, rank: Option<u32>) {
+        let word = word.split_whitespace().collect::<Vec<_>>().join(" ");
+        if word.is_empty() {
+            return;
+        }
+
+        let mut path = vec![0u32];
+        let mut node = 0u32;
+        for c in normalize(&word).chars() {
+            node = match self.child(node, c) {
+                Some(next) => next,
+                None => {
+                    let next = self.nodes.len() as u32;
+                    self.nodes.push(Node::default());
+                    let children = &mut self.nodes[node as usize].children;
+                    let at = children.partition_point(|(k, _)| *k < c);
+                    children.insert(at, (c, next));
+                    next
+                }
+            };
+            path.push(node);
+        }
+
+        // The first spelling seen is kept; a better rank from another source wins
+        let term = match self.nodes[node as usize].term {
+            Some(term) => {
+                let existing = &mut self.terms[term as usize];
+                match (existing.rank, rank) {
+                    (Some(old), Some(new)) if new >= old => return,
+                    (_, None) => return,
+                    _ => existing.rank = rank,
+                }
+                term
+            }
+            None => {
+                let term = self.terms.len() as u32;
+                let letters = signature(&word);
+                if !letters.is_empty() {
+                    self.signatures.entry(letters).or_default().push(term);
+                }
+                let (primary, alternate) = double_metaphone(&word);
+                for key in [primary.clone(), alternate].into_iter().filter(|k| !k.is_empty()) {
+                    let terms = self.metaphone.entry(key).or_default();
+                    if terms.last() != Some(&term) {
+                        terms.push(term);
+                    }
+                }
+                let code = soundex(&word);
+                if !code.is_empty() {
+                    self.soundex.entry(code).or_default().push(term);
+                }
+                self.terms.push(Term { word, rank });
+                self.nodes[node as usize].term = Some(term);
+                term
+            }
+        };
+
+        for &node in path.iter().take(TOP_DEPTH + 1) {
+            let mut top = std::mem::take(&mut self.nodes[node as usize].top);
+            top.retain(|t| *t != term);
+            let key = self.terms[term as usize].static_key();
+            let at = top.partition_point(|t| self.terms[*t as usize].static_key() < key);
+            if at < TOP_SIZE {
+                top.insert(at, term);
+                top.truncate(TOP_SIZE);
+            }
+            self.nodes[node as usize].top = top;
+        }
+    }
+
+    /// Terms under `prefix`, best first by static rank
+    fn candidates(&self, prefix: &str, limit: usize) -> Vec<u32> {
+        match self.find(prefix) {
+            Some(node) => self.top_under(node, prefix.chars().count(), limit),
+            None => vec![],
+        }
+    }
+
+    fn top_under(&self, start: u32, depth: usize, limit: usize) -> Vec<u32> {
+        if depth <= TOP_DEPTH {
+            return self.nodes[start as usize].top.clone();
+        }
+
+        let mut found = Vec::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node as usize];
+            found.extend(node.term);
+            stack.extend(node.children.iter().map(|(_, child)| *child));
+        }
+        found.sort_by(|a, b| self.terms[*a as usize].static_key().cmp(&self.terms[*b as usize].static_key()));
+        found.truncate(limit.max(TOP_SIZE));
+        found
+    }
+
+    /// Terms within `max` edits of `query`, with their distance. Edits are
+    /// Damerau-Levenshtein (a swap of neighbours counts once), computed one
+    /// row per trie level so whole subtrees are pruned at once. With
+    /// `prefix`, a node within reach contributes its best few terms.
+    fn fuzzy(&self, query: &FuzzyQuery) -> HashMap<u32, usize> {
+        let mut found = HashMap::new();
+        let mut rows = vec![(0..=query.chars.len()).collect::<Vec<_>>()];
+        self.fuzzy_walk(0, query, &mut rows, &mut Vec::new(), &mut found);
+        found
+    }
+
+    fn fuzzy_walk(
+        &self,
+        node: u32,
+        query: &FuzzyQuery,
+        rows: &mut Vec<Vec<usize>>,
+        path: &mut Vec<char>,
+        found: &mut HashMap<u32, usize>,
+    ) {
+        let FuzzyQuery { chars, max, prefix, limit } = *query;
+        for &(c, child) in &self.nodes[node as usize].children {
+            let prev = &rows[rows.len() - 1];
+            let mut row = Vec::with_capacity(chars.len() + 1);
+            row.push(prev[0] + 1);
+            for j in 1..=chars.len() {
+                let cost = usize::from(chars[j - 1] != c);
+                let mut distance = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+                if j > 1 && path.last() == Some(&chars[j - 1]) && chars[j - 2] == c {
+                    distance = distance.min(rows[rows.len() - 2][j - 2] + 1);
+                }
+                row.push(distance);
+            }
+
+            let distance = row[chars.len()];
+            if distance <= max {
+                let matched = if prefix {
+                    self.top_under(child, path.len() + 1, limit)
+                } else {
+                    self.nodes[child as usize].term.into_iter().collect()
+                };
+                for term in matched {
+                    let best = found.entry(term).or_insert(distance);
+                    *best = (*best).min(distance);
+                }
+            }
+
+            if row.iter().min().is_some_and(|d| *d <= max) {
+                rows.push(row);
+                path.push(c);
+                self.fuzzy_walk(child, query, rows, path, found);
+                path.pop();
+                rows.pop();
+            }
+        }
+    }
+
+    /// Terms matching `wildcard` no longer than `max_depth`. Subtrees are
+    /// skipped as soon as no pattern position is reachable, so a pattern
+    /// with a fixed first letter only visits that letter's branch.
+    fn pattern(&self, wildcard: &Wildcard, max_depth: usize) -> Vec<u32> {
+        let mut found = Vec::new();
+        let mut stack = vec![(0u32, wildcard.start(), 0usize)];
+        while let Some((node, states, depth)) = stack.pop() {
+            let node = &self.nodes[node as usize];
+            if wildcard.is_match(&states) {
+                found.extend(node.term);
+            }
+            if depth == max_depth {
+                continue;
+            }
+            for &(c, child) in &node.children {
+                let next = wildcard.step(&states, c);
+                if !next.is_empty() {
+                    stack.push((child, next, depth + 1));
+                }
+            }
+        }
+        found
+    }
+
+    /// Terms that can be spelled from letters in `bag`, each used at most
+    /// once, with how many letters they use. Spaces and punctuation in
+    /// phrases are free.
+    fn buildable(&self, node: u32, bag: &mut LetterBag, used: usize, found: &mut Vec<(u32, usize)>) {
+        for &(c, child) in &self.nodes[node as usize].children {
+            let letter = c.is_alphabetic();
+            if letter && !bag.take(c) {
+                continue;
+            }
+            let used = used + usize::from(letter);
+            if let Some(term) = self.nodes[child as usize].term {
+                found.push((term, used));
+            }
+            self.buildable(child, bag, used, found);
+            if letter {
+                bag.put_back(c);
+            }
+        }
+    }
+
+    /// Terms that sound like `word`, with how close: 0 when they share its
+    /// primary Double Metaphone key, 1 for its alternate key only, and 2
+    /// for a Soundex match, which is only tried when Metaphone finds nothing
+    fn sound_alikes(&self, word: &str) -> HashMap<u32, usize> {
+        let mut found = HashMap::new();
+        let (primary, alternate) = double_metaphone(word);
+        for (strength, key) in [(0, primary), (1, alternate)] {
+            for term in self.metaphone.get(&key).into_iter().flatten() {
+                found.entry(*term).or_insert(strength);
+            }
+        }
+        if found.is_empty() {
+            for term in self.soundex.get(&soundex(word)).into_iter().flatten() {
+                found.insert(*term, 2);
+            }
+        }
+        found
+    }
+
+    fn sort_by_rank(&self, terms: &mut [u32]) {
+        terms.sort_by(|a, b| self.terms[*a as usize].static_key().cmp(&self.terms[*b as usize].static_key()));
+    }
+}
+
+/// Index key: lowercase, with phrases single-spaced
+fn normalize(word: &str) -> String {
+    word.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Undo line-break hyphenation ("exam-\nple") and drop soft hyphens
+fn dehyphenate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{ad}' => {}
+            '-' if chars.peek().is_some_and(|next| next.is_whitespace()) => {
+                while chars.peek().is_some_and(|next| next.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i];
+        for j in 1..=b.len() {
+            let prev = &rows[i - 1];
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            row.push(distance);
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Every spelling one OCR confusion away from `word`
+fn ocr_variants(word: &str) -> Vec<String> {
+    let mut variants = Vec::new();
+    for (from, to) in OCR_CONFUSIONS {
+        for (at, _) in word.match_indices(from) {
+            variants.push(format!("{}{}{}", &word[..at], to, &word[at + from.len()..]));
+        }
+    }
+    variants
+}
+
+/// Prefix autocomplete over every headword the local sources know, so
+/// `search_words` can answer without the network. Suggestions are ranked
+/// by how often the user has looked a word up, then by frequency rank.
+pub struct PrefixIndex {
+    trie: RwLock<Trie>,
+    // Names of the sources whose headwords are in the trie
+    indexed_sources: RwLock<HashSet<String>>,
+    // Lowercased word -> (lookup count, last lookup sequence number)
+    history: RwLock<BTreeMap<String, (u32, u64)>>,
+    history_path: RwLock<Option<PathBuf>>,
+    // Sequence number of the latest lookup
+    history_sequence: AtomicU64,
+    save_generation: AtomicU64,
+    generation: AtomicU64,
+}
+
+impl Default for PrefixIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrefixIndex {
+    pub fn new() -> Self {
+        Self {
+            trie: RwLock::new(Trie::new()),
+            indexed_sources: RwLock::new(HashSet::new()),
+            history: RwLock::new(BTreeMap::new()),
+            history_path: RwLock::new(None),
+            history_sequence: AtomicU64::new(0),
+            save_generation: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Load lookup history from `path` and keep saving it there
+    pub fn load_history(&self, path: PathBuf) {
+        if path.exists() {
+            match fs::read_to_string(&path).map(|json| serde_json::from_str::<BTreeMap<String, (u32, u64)>>(&json)) {
+                Ok(Ok(saved)) => {
+                    let latest = saved.values().map(|(_, seq)| *seq).max().unwrap_or(0);
+                    self.history_sequence.fetch_max(latest, Ordering::SeqCst);
+                    if let Ok(mut history) = self.history.write() {
+                        history.extend(saved);
+                    }
+                }
+                Ok(Err(e)) => eprintln!("Failed to parse search history {}: {}", path.display(), e),
+                Err(e) => eprintln!("Failed to read search history {}: {}", path.display(), e),
+            }
+        }
+
+        if let Ok(mut history_path) = self.history_path.write() {
+            *history_path = Some(path);
+        }
+    }
+
+    /// Count a lookup the user asked for; the word is indexed if it wasn't already
+    pub fn record_lookup(self: &Arc<Self>, word: &str) {
+        let key = word.trim().to_lowercase();
+        if key.is_empty() {
+            return;
+        }
+        self.insert(word, None);
+
+        let sequence = self.history_sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Ok(mut history) = self.history.write() {
+            let entry = history.entry(key).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 = sequence;
+
+            if history.len() > HISTORY_LIMIT {
+                if let Some(oldest) = history.iter().min_by_key(|(_, (_, seq))| *seq).map(|(k, _)| k.clone()) {
+                    history.remove(&oldest);
+                }
+            }
+        }
+        self.schedule_history_save();
+    }
+
+    /// Write the history file once lookups pause, off the lookup path
+    fn schedule_history_save(self: &Arc<Self>) {
+        let Some(path) = self.history_path.read().ok().and_then(|p| p.clone()) else { return };
+        let generation = self.save_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let index = self.clone();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(HISTORY_SAVE_DELAY);
+            if index.save_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let saved = index.history.read()
+                .map_err(|e| e.to_string())
+                .and_then(|history| serde_json::to_string(&*history).map_err(|e| e.to_string()))
+                .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+            if let Err(e) = saved {
+                eprintln!("Failed to save search history {}: {}", path.display(), e);
+            }
+        });
+    }
+
+    pub fn insert(&self, word: &str, rank: Option<u32>) {
+        if let Ok(mut trie) = self.trie.write() {
+            trie.insert(word, rank);
+        }
+    }
+
+    /// Whether `word` is a known headword
+    pub fn contains(&self, word: &str) -> bool {
+        self.trie.read()
+            .map(|trie| trie.find(&normalize(word)).is_some_and(|node| trie.nodes[node as usize].term.is_some()))
+            .unwrap_or(false)
+    }
+
+    /// Frequency rank of `word`, if it's a headword that has one
+    pub fn rank(&self, word: &str) -> Option<u32> {
+        let trie = self.trie.read().ok()?;
+        let term = trie.nodes[trie.find(&normalize(word))? as usize].term?;
+        trie.terms[term as usize].rank
+    }
+
+    /// Whether `source`'s headwords are answered from the index
+    pub fn covers(&self, source: &str) -> bool {
+        self.indexed_sources.read().map(|s| s.contains(source)).unwrap_or(false)
+    }
+
+    /// Up to `limit` words starting with `prefix`, best first
+    pub fn search(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = normalize(prefix);
+        if prefix.is_empty() {
+            return vec![];
+        }
+        let Ok(trie) = self.trie.read() else { return vec![] };
+        let history = self.history.read().map(|h| h.clone()).unwrap_or_default();
+
+        let mut candidates = trie.candidates(&prefix, limit);
+        // Looked-up words may have fallen outside the precomputed list
+        candidates.extend(history.range(prefix.clone()..)
+            .take_while(|(word, _)| word.starts_with(&prefix))
+            .filter_map(|(word, _)| trie.find(word).and_then(|node| trie.nodes[node as usize].term)));
+        candidates.sort();
+        candidates.dedup();
+
+        let score = |term: u32| {
+            let term = &trie.terms[term as usize];
+            let key = term.word.to_lowercase();
+            let (count, _) = history.get(&key).copied().unwrap_or((0, 0));
+            (key != prefix, Reverse(count), term.static_key())
+        };
+        candidates.sort_by(|a, b| score(*a).cmp(&score(*b)));
+
+        candidates.into_iter()
+            .take(limit)
+            .map(|term| trie.terms[term as usize].word.clone())
+            .collect()
+    }
+
+    /// Words a few typos away from `query`, closest first, for "did you
+    /// mean" and typo-tolerant search. With `prefix`, `query` may be the
+    /// start of a word. The query itself is never suggested.
+    pub fn fuzzy_search(&self, query: &str, limit: usize, prefix: bool) -> Vec<String> {
+        let original = query.trim().to_lowercase();
+        let normalized = dehyphenate(&original);
+        let chars: Vec<char> = normalized.chars().collect();
+        let max = match chars.len() {
+            0..=2 => return vec![],
+            3..=4 => 1,
+            _ => 2,
+        };
+        let Ok(trie) = self.trie.read() else { return vec![] };
+        let history = self.history.read().map(|h| h.clone()).unwrap_or_default();
+
+        let mut found = trie.fuzzy(&FuzzyQuery { chars: &chars, max, prefix, limit });
+        for variant in ocr_variants(&normalized) {
+            let matched = match trie.find(&variant) {
+                Some(node) if prefix => trie.top_under(node, variant.chars().count(), limit),
+                Some(node) => trie.nodes[node as usize].term.into_iter().collect(),
+                None => vec![],
+            };
+            for term in matched {
+                found.entry(term).or_insert(1);
+            }
+        }
+
+        // Sound-alikes count as one edit closer, and may be a little further
+        // off than plain typos ("fonetik" for "phonetic")
+        if !prefix {
+            for (term, strength) in trie.sound_alikes(&normalized) {
+                let distance = edit_distance(&chars, &normalize(&trie.terms[term as usize].word).chars().collect::<Vec<_>>());
+                if strength < 2 && distance <= max + PHONETIC_SLACK {
+                    let blended = distance.saturating_sub(1);
+                    let best = found.entry(term).or_insert(blended);
+                    *best = (*best).min(blended);
+                }
+            }
+        }
+
+        let mut found: Vec<(u32, usize)> = found.into_iter()
+            .filter(|(term, _)| trie.terms[*term as usize].word.to_lowercase() != original)
+            .collect();
+        let score = |(term, distance): &(u32, usize)| {
+            let term = &trie.terms[*term as usize];
+            let (count, _) = history.get(&term.word.to_lowercase()).copied().unwrap_or((0, 0));
+            (*distance, Reverse(count), term.static_key())
+        };
+        found.sort_by(|a, b| score(a).cmp(&score(b)));
+
+        found.into_iter()
+            .take(limit)
+            .map(|(term, _)| trie.terms[term as usize].word.clone())
+            .collect()
+    }
+
+    /// One page of the headwords matching a crossword-style pattern, most
+    /// frequent first, with the total number of matches
+    pub fn pattern_search(&self, matcher: &Matcher, filter: &WordFilter, offset: usize, limit: usize) -> (Vec<String>, usize) {
+        let Ok(trie) = self.trie.read() else { return (vec![], 0) };
+
+        let mut found: Vec<u32> = match matcher {
+            Matcher::Wildcard(wildcard) => {
+                let max_depth = wildcard.max_length().unwrap_or(usize::MAX).min(filter.max_length);
+                if wildcard.min_length() > max_depth {
+                    return (vec![], 0);
+                }
+                trie.pattern(wildcard, max_depth)
+            }
+            Matcher::Regex(regex) => (0..trie.terms.len() as u32)
+                .filter(|term| regex.is_match(&normalize(&trie.terms[*term as usize].word)))
+                .collect(),
+        };
+        found.retain(|term| filter.accepts(&normalize(&trie.terms[*term as usize].word)));
+        trie.sort_by_rank(&mut found);
+
+        let total = found.len();
+        let words = found.into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|term| trie.terms[term as usize].word.clone())
+            .collect();
+        (words, total)
+    }
+
+    /// Headwords that sound like `word`, closest pronunciation first, then
+    /// closest spelling, then most looked up and most frequent
+    pub fn sounds_like(&self, word: &str, limit: usize) -> Vec<String> {
+        let original = normalize(word);
+        let chars: Vec<char> = original.chars().collect();
+        let Ok(trie) = self.trie.read() else { return vec![] };
+        let history = self.history.read().map(|h| h.clone()).unwrap_or_default();
+
+        let mut found: Vec<(u32, usize, usize)> = trie.sound_alikes(&original).into_iter()
+            .filter(|(term, _)| normalize(&trie.terms[*term as usize].word) != original)
+            .map(|(term, strength)| {
+                let spelling: Vec<char> = normalize(&trie.terms[term as usize].word).chars().collect();
+                (term, strength, edit_distance(&chars, &spelling))
+            })
+            .collect();
+        let score = |(term, strength, distance): &(u32, usize, usize)| {
+            let term = &trie.terms[*term as usize];
+            let (count, _) = history.get(&term.word.to_lowercase()).copied().unwrap_or((0, 0));
+            (*strength, *distance, Reverse(count), term.static_key())
+        };
+        found.sort_by(|a, b| score(a).cmp(&score(b)));
+
+        found.into_iter()
+            .take(limit)
+            .map(|(term, _, _)| trie.terms[term as usize].word.clone())
+            .collect()
+    }
+
+    /// Headwords spelled with exactly `letters` (other than `letters`
+    /// itself), and shorter ones buildable from them, most frequent first.
+    /// Words with fewer than `min_length` letters are left out.
+    pub fn anagrams(&self, letters: &str, min_length: usize, limit: usize) -> Anagrams {
+        let Ok(trie) = self.trie.read() else { return Anagrams::default() };
+        let key = signature(letters);
+        if key.is_empty() || key.chars().count() < min_length {
+            return Anagrams::default();
+        }
+        let original = normalize(letters);
+        let words = |terms: Vec<u32>| terms.into_iter()
+            .take(limit)
+            .map(|term| trie.terms[term as usize].word.clone())
+            .collect();
+
+        let mut exact: Vec<u32> = trie.signatures.get(&key).cloned().unwrap_or_default();
+        exact.retain(|term| normalize(&trie.terms[*term as usize].word) != original);
+        trie.sort_by_rank(&mut exact);
+
+        let mut bag = LetterBag::new(letters);
+        let total = bag.remaining();
+        let mut found = Vec::new();
+        trie.buildable(0, &mut bag, 0, &mut found);
+        let mut shorter: Vec<u32> = found.into_iter()
+            .filter(|(_, used)| *used >= min_length && *used < total)
+            .map(|(term, _)| term)
+            .collect();
+        trie.sort_by_rank(&mut shorter);
+
+        Anagrams { anagrams: words(exact), sub_anagrams: words(shorter) }
+    }
+
+    /// Rebuild from scratch on a background thread. Calls made in quick
+    /// succession collapse into one rebuild, and a stale rebuild never
+    /// replaces a newer one.
+    pub fn schedule_rebuild(self: &Arc<Self>, sources: Vec<Arc<dyn DictionarySource>>, extra_words: Vec<String>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let index = self.clone();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(REBUILD_DELAY);
+            if index.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let started = std::time::Instant::now();
+            let mut trie = Trie::new();
+            let mut indexed = HashSet::new();
+            for source in &sources {
+                match source.headwords(&mut |word, rank| trie.insert(word, rank)) {
+                    Ok(true) => {
+                        indexed.insert(source.name().to_string());
+                    }
+                    Ok(false) => {}
+                    Err(e) => e.log_error(),
+                }
+            }
+            for word in &extra_words {
+                trie.insert(word, None);
+            }
+            let history_words: Vec<String> = index.history.read().map(|h| h.keys().cloned().collect()).unwrap_or_default();
+            for word in &history_words {
+                trie.insert(word, None);
+            }
+
+            if index.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            println!("Indexed {} headwords from {} sources in {:?}", trie.terms.len(), indexed.len(), started.elapsed());
+            if let (Ok(mut current), Ok(mut current_sources)) = (index.trie.write(), index.indexed_sources.write()) {
+                *current = trie;
+                *current_sources = indexed;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranking() {
+        let index = Arc::new(PrefixIndex::new());
+        index.insert("cathedral", Some(900));
+        index.insert("cat", Some(50));
+        index.insert("catalog", Some(400));
+        index.insert("Caterpillar", None);
+        index.insert("category", Some(200));
+        // A worse rank from a second source doesn't demote the word
+        index.insert("cat", Some(5000));
+
+        assert_eq!(index.search("ca", 10), vec!["cat", "category", "catalog", "cathedral", "Caterpillar"]);
+        assert_eq!(index.search("CATE", 10), vec!["category", "Caterpillar"]);
+        assert!(index.search("dog", 10).is_empty());
+
+        index.record_lookup("cathedral");
+        index.record_lookup("Caterpillar");
+        index.record_lookup("caterpillar");
+        assert_eq!(index.search("cat", 3), vec!["cat", "Caterpillar", "cathedral"]);
+        assert_eq!(index.search("cate", 10), vec!["Caterpillar", "category"]);
+
+        // Phrases are single-spaced, whatever the source had
+        index.insert("give  up", None);
+        assert!(index.contains("Give up"));
+        assert_eq!(index.search("give u", 10), vec!["give up"]);
+    }
+
+    #[test]
+    fn test_deep_prefixes_and_top_lists() {
+        let index = Arc::new(PrefixIndex::new());
+        for i in 0..200u32 {
+            index.insert(&format!("word{:03}", i), Some(1000 - i));
+        }
+
+        // Shallow prefixes read the precomputed list
+        assert_eq!(index.search("wor", 2), vec!["word199", "word198"]);
+        // Deeper ones walk the subtree
+        assert_eq!(index.search("word01", 3), vec!["word019", "word018", "word017"]);
+
+        // A looked-up word is found even when it's outside the shallow list
+        index.record_lookup("word000");
+        assert_eq!(index.search("w", 1), vec!["word000"]);
+    }
+
+    #[test]
+    fn test_history_saved_once_lookups_pause() {
+        let path = std::env::temp_dir().join(format!("search-history-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let index = Arc::new(PrefixIndex::new());
+        index.load_history(path.clone());
+
+        index.record_lookup("cat");
+        index.record_lookup("cathedral");
+        index.record_lookup("cat");
+        assert!(!path.exists());
+
+        std::thread::sleep(HISTORY_SAVE_DELAY * 2);
+        let reloaded = Arc::new(PrefixIndex::new());
+        reloaded.load_history(path.clone());
+        assert_eq!(reloaded.history.read().unwrap()["cat"], (2, 3));
+        assert_eq!(reloaded.history.read().unwrap()["cathedral"], (1, 2));
+
+        // Sequence numbers carry on from the saved ones
+        assert_eq!(reloaded.history_sequence.load(Ordering::SeqCst), 3);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_fuzzy_suggestions() {
+        let index = PrefixIndex::new();
+        for (word, rank) in [("receive", 300), ("relieve", 900), ("modern", 500), ("example", 200),
+                             ("dictionary", 400), ("diction", 2000), ("cat", 10), ("cot", 20), ("cut", 30)] {
+            index.insert(word, Some(rank));
+        }
+
+        // Swapped letters are one edit, so the closer word wins over the more frequent one
+        assert_eq!(index.fuzzy_search("recieve", 5, false), vec!["receive", "relieve"]);
+        // OCR reading "m" as "rn"
+        assert_eq!(index.fuzzy_search("rnodern", 5, false), vec!["modern"]);
+        // Hyphenated across a line break
+        assert_eq!(index.fuzzy_search("exam-\n ple", 5, false), vec!["example"]);
+        // Equal distance falls back to frequency, and the word itself isn't suggested
+        assert_eq!(index.fuzzy_search("cat", 5, false), vec!["cot", "cut"]);
+        assert!(index.fuzzy_search("zzzzzz", 5, false).is_empty());
+
+        // Typo-tolerant prefix search; both are one swap away, so rank decides
+        assert_eq!(index.fuzzy_search("dictoin", 5, true), vec!["dictionary", "diction"]);
+    }
+
+    #[test]
+    fn test_sound_alikes() {
+        let index = PrefixIndex::new();
+        for (word, rank) in [("phonetic", Some(700)), ("fanatic", Some(900)), ("knight", Some(600)), ("night", Some(100)),
+                             ("Smith", Some(300)), ("Schmidt", None), ("Ashcraft", None)] {
+            index.insert(word, rank);
+        }
+
+        assert_eq!(index.sounds_like("fonetik", 5), vec!["phonetic", "fanatic"]);
+        assert_eq!(index.sounds_like("nite", 5), vec!["night", "knight"]);
+        // "Smith" has "XMT" as its alternate key, Schmidt's primary
+        assert_eq!(index.sounds_like("smith", 5), vec!["Schmidt"]);
+        // Soundex when Metaphone has nothing
+        assert_eq!(index.sounds_like("askraft", 5), vec!["Ashcraft"]);
+
+        // Fuzzy suggestions let sound-alikes reach a little further than typos
+        assert_eq!(index.fuzzy_search("fonetik", 5, false), vec!["phonetic", "fanatic"]);
+        assert_eq!(edit_distance(&['a', 'b'], &['b', 'a']), 1);
+    }
+
+    #[test]
+    fn test_pattern_search() {
+        use crate::pattern::PatternQuery;
+
+        let index = PrefixIndex::new();
+        for (word, rank) in [("cat", Some(10)), ("cot", Some(30)), ("Cut", Some(20)), ("cart", Some(40)),
+                             ("biology", Some(500)), ("geology", None), ("apology", Some(800)), ("coat", None)] {
+            index.insert(word, rank);
+        }
+        let search = |query: PatternQuery| {
+            let (matcher, filter) = query.compile().unwrap();
+            index.pattern_search(&matcher, &filter, query.offset, query.page_size())
+        };
+        let wildcard = |pattern: &str| PatternQuery { pattern: pattern.to_string(), ..Default::default() };
+
+        assert_eq!(search(wildcard("c?t")), (vec!["cat".to_string(), "Cut".to_string(), "cot".to_string()], 3));
+        assert_eq!(search(wildcard("*ology")).0, vec!["biology", "apology", "geology"]);
+        assert_eq!(search(wildcard("c[ao]*t")).0, vec!["cat", "cot", "cart", "coat"]);
+
+        // Length and letter constraints
+        assert_eq!(search(PatternQuery { min_length: Some(4), ..wildcard("c*") }).0, vec!["cart", "coat"]);
+        assert_eq!(search(PatternQuery { include: "ar".to_string(), ..wildcard("c*") }).0, vec!["cart"]);
+        assert_eq!(search(PatternQuery { exclude: "a".to_string(), ..wildcard("c?t") }).0, vec!["Cut", "cot"]);
+
+        // Pages share the total
+        let page = search(PatternQuery { offset: 1, limit: Some(1), ..wildcard("c*t") });
+        assert_eq!(page, (vec!["Cut".to_string()], 5));
+
+        let regex = PatternQuery { regex: true, ..wildcard("(bi|ge)o.*") };
+        assert_eq!(search(regex).0, vec!["biology", "geology"]);
+        assert!(PatternQuery { regex: true, ..wildcard("(") }.compile().is_err());
+    }
+
+    #[test]
+    fn test_anagrams() {
+        let index = PrefixIndex::new();
+        for (word, rank) in [("listen", Some(300)), ("silent", Some(100)), ("enlist", Some(900)), ("tinsel", None),
+                             ("inlets", Some(5000)), ("list", Some(200)), ("tile", Some(400)), ("ten", Some(50)),
+                             ("net", Some(80)), ("lens", None), ("tiles", Some(600)), ("dormitory", Some(7000)),
+                             ("dirty room", None), ("tell", Some(70))] {
+            index.insert(word, rank);
+        }
+
+        let found = index.anagrams("Listen", 3, 10);
+        assert_eq!(found.anagrams, vec!["silent", "enlist", "inlets", "tinsel"]);
+        assert_eq!(found.sub_anagrams, vec!["ten", "net", "list", "tile", "tiles", "lens"]);
+
+        assert_eq!(index.anagrams("listen", 4, 2).sub_anagrams, vec!["list", "tile"]);
+        assert_eq!(index.anagrams("dormitory", 3, 10).anagrams, vec!["dirty room"]);
+        assert_eq!(index.anagrams("ab", 3, 10), Anagrams::default());
+    }
+}
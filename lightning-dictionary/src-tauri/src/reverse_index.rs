@@ -0,0 +1,353 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::source::DictionarySource;
+
+// Standard BM25 parameters
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+// Snippets longer than this are cut down to a window around the first match
+const SNIPPET_CHARS: usize = 160;
+const SNIPPET_LEAD: usize = 50;
+// Same debounce as the prefix index; sources arrive in bursts
+const REBUILD_DELAY: Duration = Duration::from_millis(250);
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "being", "between", "both", "but", "by", "can", "could", "did", "do",
+    "does", "each", "for", "from", "had", "has", "have", "he", "her", "his", "how", "i", "if",
+    "in", "into", "is", "it", "its", "may", "more", "most", "no", "nor", "not", "of", "on",
+    "one", "or", "other", "our", "out", "over", "she", "should", "so", "some", "such", "than",
+    "that", "the", "their", "them", "then", "there", "these", "they", "this", "those", "through",
+    "to", "under", "up", "very", "was", "we", "were", "what", "when", "where", "which", "while",
+    "who", "whom", "why", "will", "with", "would", "you", "your",
+];
+
+/// Part of a snippet; `matched` parts are the query words to highlight
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnippetSpan {
+    pub text: String,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReverseMatch {
+    pub word: String,
+    pub source: String,
+    pub score: f32,
+    pub snippet: Vec<SnippetSpan>,
+}
+
+struct Document {
+    word: String,
+    source: usize,
+    length: u32,
+}
+
+#[derive(Default)]
+struct Corpus {
+    // Text isn't kept; snippets come from a fresh lookup of the few hits
+    sources: Vec<Arc<dyn DictionarySource>>,
+    documents: Vec<Document>,
+    // Stemmed term -> (document, term frequency)
+    postings: HashMap<String, Vec<(u32, u32)>>,
+    total_length: u64,
+}
+
+impl Corpus {
+    fn build(sources: Vec<Arc<dyn DictionarySource>>) -> Self {
+        let mut corpus = Corpus::default();
+        for (source_index, source) in sources.iter().enumerate() {
+            let result = source.documents(&mut |word, texts| {
+                let mut counts: HashMap<String, u32> = HashMap::new();
+                for text in texts {
+                    for term in terms(text) {
+                        *counts.entry(term).or_insert(0) += 1;
+                    }
+                }
+                if counts.is_empty() {
+                    return;
+                }
+
+                let id = corpus.documents.len() as u32;
+                let length = counts.values().sum();
+                corpus.total_length += length as u64;
+                corpus.documents.push(Document { word: word.to_string(), source: source_index, length });
+                for (term, count) in counts {
+                    corpus.postings.entry(term).or_default().push((id, count));
+                }
+            });
+            if let Err(e) = result {
+                e.log_error();
+            }
+        }
+        corpus.sources = sources;
+        corpus
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Vec<ReverseMatch> {
+        let query_terms: HashSet<String> = terms(query).collect();
+        if query_terms.is_empty() || self.documents.is_empty() {
+            return vec![];
+        }
+
+        let count = self.documents.len() as f32;
+        let average_length = self.total_length as f32 / count;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        for term in &query_terms {
+            let Some(postings) = self.postings.get(term) else { continue };
+            let frequency = postings.len() as f32;
+            let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+            for &(document, tf) in postings {
+                let length = self.documents[document as usize].length as f32;
+                let tf = tf as f32;
+                *scores.entry(document).or_insert(0.0) +=
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length));
+            }
+        }
+
+        let mut ranked: Vec<(u32, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        // One result per headword, from whichever source matched it best
+        let mut seen = HashSet::new();
+        ranked.into_iter()
+            .filter(|(document, _)| seen.insert(self.documents[*document as usize].word.to_lowercase()))
+            .take(limit)
+            .map(|(document, score)| {
+                let document = &self.documents[document as usize];
+                let source = &self.sources[document.source];
+                let texts = source.lookup(&document.word).ok().flatten()
+                    .map(|definition| definition.definitions)
+                    .unwrap_or_default();
+                ReverseMatch {
+                    word: document.word.clone(),
+                    source: source.name().to_string(),
+                    score,
+                    snippet: snippet(&texts, &query_terms),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Searches definition text to find words from their meaning, ranked
+/// with BM25 over stemmed, stop-word-filtered terms
+pub struct ReverseIndex {
+    corpus: RwLock<Corpus>,
+    generation: AtomicU64,
+}
+
+impl Default for ReverseIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReverseIndex {
+    pub fn new() -> Self {
+        Self {
+            corpus: RwLock::new(Corpus::default()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ReverseMatch> {
+        self.corpus.read().map(|corpus| corpus.search(query, limit)).unwrap_or_default()
+    }
+
+    /// Rebuild on a background thread, collapsing bursts of calls into one
+    pub fn schedule_rebuild(self: &Arc<Self>, sources: Vec<Arc<dyn DictionarySource>>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let index = self.clone();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(REBUILD_DELAY);
+            if index.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let started = std::time::Instant::now();
+            let corpus = Corpus::build(sources);
+            if index.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            println!("Indexed {} definitions for reverse search in {:?}", corpus.documents.len(), started.elapsed());
+            if let Ok(mut current) = index.corpus.write() {
+                *current = corpus;
+            }
+        });
+    }
+}
+
+/// Stemmed, stop-word-free terms of `text`
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+}
+
+/// A light suffix-stripping stemmer. It only needs to map a word and its
+/// inflections to the same key, not to produce a real word.
+fn stem(word: &str) -> String {
+    const SUFFIXES: &[(&str, &str)] = &[
+        ("ational", "ate"), ("ization", "ize"), ("fulness", "ful"), ("iveness", "ive"),
+        ("ingly", ""), ("edly", ""), ("ments", ""), ("ment", ""), ("ness", ""),
+        ("ies", "y"), ("ied", "y"), ("sses", "ss"), ("ing", ""), ("ed", ""), ("ly", ""), ("es", ""), ("s", ""),
+    ];
+
+    let mut stem = word.to_string();
+    for (suffix, replacement) in SUFFIXES {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.chars().count() >= 3 && !(*suffix == "s" && (base.ends_with('s') || base.ends_with('u'))) {
+                stem = format!("{}{}", base, replacement);
+                break;
+            }
+        }
+    }
+
+    // "bake"/"baked"/"baking" and "run"/"running" share a key
+    if stem.chars().count() > 3 && stem.ends_with('e') {
+        stem.pop();
+    }
+    let chars: Vec<char> = stem.chars().collect();
+    if let [.., a, b] = chars[..] {
+        if chars.len() > 3 && a == b && !"lsz".contains(a) && !"aeiou".contains(a) {
+            stem.pop();
+        }
+    }
+    stem
+}
+
+/// Each alphanumeric run in `text` with its byte offset
+fn word_spans(text: &str) -> Vec<(usize, &str)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// The definition with the most query terms, cut to a window around the
+/// first match and split into highlighted and plain parts
+fn snippet(texts: &[String], query_terms: &HashSet<String>) -> Vec<SnippetSpan> {
+    let matches = |text: &str| terms(text).filter(|t| query_terms.contains(t)).collect::<HashSet<_>>().len();
+    let Some(text) = texts.iter().max_by_key(|text| (matches(text), Reverse(text.len()))) else { return vec![] };
+
+    let mut spans: Vec<SnippetSpan> = Vec::new();
+    let mut push = |text: &str, matched: bool| match spans.last_mut() {
+        Some(last) if last.matched == matched => last.text.push_str(text),
+        _ if !text.is_empty() => spans.push(SnippetSpan { text: text.to_string(), matched }),
+        _ => {}
+    };
+
+    let words = word_spans(text);
+    let is_match = |word: &str| {
+        let word = word.to_lowercase();
+        !STOP_WORDS.contains(&word.as_str()) && query_terms.contains(&stem(&word))
+    };
+
+    // Window around the first match for long definitions
+    let (mut from, mut to) = (0, text.len());
+    if text.chars().count() > SNIPPET_CHARS {
+        let first = words.iter().find(|(_, w)| is_match(w)).map(|(i, _)| *i).unwrap_or(0);
+        from = words.iter().map(|(i, _)| *i).rfind(|i| *i <= first.saturating_sub(SNIPPET_LEAD)).unwrap_or(0);
+        to = text[from..].char_indices().nth(SNIPPET_CHARS).map(|(i, _)| from + i).unwrap_or(text.len());
+        // Don't end mid-word
+        to = words.iter().find(|(i, w)| *i < to && i + w.len() > to).map(|(i, _)| *i).unwrap_or(to);
+    }
+
+    if from > 0 {
+        push("…", false);
+    }
+    let mut position = from;
+    for (start, word) in words.iter().filter(|(i, _)| *i >= from && *i < to) {
+        push(&text[position..*start], false);
+        push(word, is_match(word));
+        position = start + word.len();
+    }
+    push(&text[position..to], false);
+    if to < text.len() {
+        push("…", false);
+    }
+    spans
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glossary::{Glossary, GlossaryFormat};
+
+    #[test]
+    fn test_stemming() {
+        assert_eq!(stem("cats"), stem("cat"));
+        assert_eq!(stem("running"), stem("run"));
+        assert_eq!(stem("baked"), stem("bake"));
+        assert_eq!(stem("studies"), stem("study"));
+        assert_eq!(stem("glass"), "glass");
+        assert_eq!(stem("felines"), stem("feline"));
+    }
+
+    #[test]
+    fn test_bm25_ranking_and_snippets() {
+        let glossary = Glossary::load(None);
+        let csv = "word,definition\n\
+            cat,A small domesticated feline kept as a pet.\n\
+            lion,A large feline of Africa and India.\n\
+            dog,A domesticated carnivorous mammal that barks.\n\
+            kitten,A young cat.\n\
+            kitten,\"A very young domesticated feline, in its first months.\"\n";
+        glossary.import(csv, GlossaryFormat::Csv, false).unwrap();
+        let corpus = Corpus::build(vec![Arc::new(glossary)]);
+
+        let results = corpus.search("small domestic felines", 10);
+        assert_eq!(results[0].word, "cat");
+        assert_eq!(results[0].source, "glossary");
+        assert!(results.iter().any(|r| r.word == "lion"));
+        assert!(results.iter().all(|r| r.word != "dog"));
+
+        assert_eq!(results[0].snippet, vec![
+            SnippetSpan { text: "A ".to_string(), matched: false },
+            SnippetSpan { text: "small".to_string(), matched: true },
+            SnippetSpan { text: " domesticated ".to_string(), matched: false },
+            SnippetSpan { text: "feline".to_string(), matched: true },
+            SnippetSpan { text: " kept as a pet.".to_string(), matched: false },
+        ]);
+
+        // The snippet is the definition that matched, not just the first one
+        let kitten = corpus.search("young feline", 10);
+        assert_eq!(kitten[0].word, "kitten");
+        assert!(kitten[0].snippet.iter().any(|s| s.matched && s.text == "feline"));
+
+        // Stop words alone match nothing
+        assert!(corpus.search("the of a", 10).is_empty());
+    }
+
+    #[test]
+    fn test_long_snippet_window() {
+        let text = format!("{} the feline {}", "word ".repeat(40), "more ".repeat(40));
+        let query: HashSet<String> = terms("feline").collect();
+        let spans = snippet(&[text], &query);
+
+        assert!(spans[0].text.starts_with('…'));
+        assert!(spans.last().unwrap().text.ends_with('…'));
+        assert_eq!(spans.iter().filter(|s| s.matched).count(), 1);
+        let length: usize = spans.iter().map(|s| s.text.chars().count()).sum();
+        assert!(length <= SNIPPET_CHARS + 2);
+    }
+}
@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::{AppHandle, Manager};
+use crate::api_client::{ApiEndpointConfig, DictionaryApiClient};
+use crate::dict_protocol::DictServerConfig;
+use crate::dictionary::DictionaryService;
+use crate::glossary::GlossaryPrecedence;
+use crate::language::LanguagePair;
+use crate::speech::{Speaker, SpeechSettings};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -10,6 +17,15 @@ pub struct Settings {
     pub cache: CacheSettings,
     pub behavior: BehaviorSettings,
     pub performance: PerformanceSettings,
+    #[serde(default)]
+    pub api: ApiSettings,
+    #[serde(default)]
+    pub sources: SourceSettings,
+    #[serde(default)]
+    pub speech: SpeechSettings,
+    /// Translation directions to show alongside definitions, best first
+    #[serde(default)]
+    pub language_pairs: Vec<LanguagePair>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +58,9 @@ pub struct BehaviorSettings {
     pub show_frequency: bool,
     pub auto_search: bool,
     pub search_delay: u32,
+    /// Split captured code identifiers like `maxRetryCount` into words
+    #[serde(default = "default_identifier_capture")]
+    pub identifier_capture: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +70,63 @@ pub struct PerformanceSettings {
     pub gpu_acceleration: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSettings {
+    pub endpoints: Vec<ApiEndpointConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSettings {
+    pub sqlite_enabled: bool,
+    /// Defaults to `lightning-dictionary.db` in the app data dir
+    pub sqlite_path: Option<String>,
+    /// Ids of installed StarDict dictionaries the user has switched off
+    #[serde(default)]
+    pub stardict_disabled: Vec<String>,
+    /// WordNet `dict` directory; defaults to `dictionaries/wordnet` in the app data dir
+    #[serde(default)]
+    pub wordnet_path: Option<String>,
+    /// DICT protocol servers, consulted after the on-disk sources
+    #[serde(default)]
+    pub dict_servers: Vec<DictServerConfig>,
+    /// Whether glossary entries override other sources or only fill gaps
+    #[serde(default)]
+    pub glossary_precedence: GlossaryPrecedence,
+    /// Merge API suggestions into local search results while online
+    #[serde(default = "default_api_suggestions")]
+    pub api_suggestions: bool,
+}
+
+fn default_api_suggestions() -> bool {
+    true
+}
+
+fn default_identifier_capture() -> bool {
+    true
+}
+
+impl Default for SourceSettings {
+    fn default() -> Self {
+        Self {
+            sqlite_enabled: true,
+            sqlite_path: None,
+            stardict_disabled: Vec::new(),
+            wordnet_path: None,
+            dict_servers: Vec::new(),
+            glossary_precedence: GlossaryPrecedence::default(),
+            api_suggestions: true,
+        }
+    }
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![ApiEndpointConfig::new("http://localhost:3001".to_string())],
+        }
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -77,12 +153,17 @@ impl Default for Settings {
                 show_frequency: true,
                 auto_search: true,
                 search_delay: 300,
+                identifier_capture: true,
             },
             performance: PerformanceSettings {
                 enable_metrics: true,
                 low_power_mode: false,
                 gpu_acceleration: true,
             },
+            api: ApiSettings::default(),
+            sources: SourceSettings::default(),
+            speech: SpeechSettings::default(),
+            language_pairs: Vec::new(),
         }
     }
 }
@@ -124,7 +205,10 @@ impl SettingsManager {
     pub fn save_settings(&mut self, settings_json: &str) -> Result<(), Box<dyn std::error::Error>> {
         // Parse and validate settings
         let new_settings: Settings = serde_json::from_str(settings_json)?;
-        
+        self.update_settings(new_settings)
+    }
+    
+    pub fn update_settings(&mut self, new_settings: Settings) -> Result<(), Box<dyn std::error::Error>> {
         // Save to file
         let contents = serde_json::to_string_pretty(&new_settings)?;
         fs::write(&self.settings_path, contents)?;
@@ -156,5 +240,23 @@ pub fn save_settings(app_handle: AppHandle, settings: String) -> Result<(), Stri
         .map_err(|e| e.to_string())?;
     
     manager.save_settings(&settings)
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    
+    // Apply endpoint changes without a restart, unless DICTIONARY_API_URL pins one
+    if let Some(api_client) = app_handle.try_state::<Arc<DictionaryApiClient>>() {
+        if std::env::var("DICTIONARY_API_URL").is_err() {
+            api_client.set_endpoints(manager.get_settings().api.endpoints.clone());
+        }
+    }
+    if let Some(dictionary_service) = app_handle.try_state::<Arc<DictionaryService>>() {
+        dictionary_service.set_glossary_precedence(manager.get_settings().sources.glossary_precedence);
+        dictionary_service.set_api_suggestions(manager.get_settings().sources.api_suggestions);
+        dictionary_service.set_language_pairs(manager.get_settings().language_pairs.clone());
+        dictionary_service.set_identifier_capture(manager.get_settings().behavior.identifier_capture);
+    }
+    if let Some(speaker) = app_handle.try_state::<Arc<Speaker>>() {
+        speaker.apply_settings(manager.get_settings().speech.clone());
+    }
+    
+    Ok(())
 }
\ No newline at end of file
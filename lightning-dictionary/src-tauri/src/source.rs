@@ -0,0 +1,47 @@
+use crate::cache::Definition;
+use crate::error::DictionaryResult;
+
+/// A local dictionary that `DictionaryService` can consult alongside the API.
+/// Implementations are synchronous; those that block on the network say so
+/// with `is_network` and are kept out of offline lookups and the indexes.
+pub trait DictionarySource: Send + Sync {
+    /// Short identifier used in logs and settings
+    fn name(&self) -> &str;
+
+    /// ISO 639-1 code of the language this source defines, e.g. "en".
+    /// `None` when unknown; such sources are asked for any language.
+    fn language(&self) -> Option<&str> {
+        None
+    }
+
+    /// Language the definitions are written in, for bilingual sources
+    /// that translate `language` into another. `None` for monolingual ones.
+    fn target_language(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether lookups go over the network, e.g. to a DICT server
+    fn is_network(&self) -> bool {
+        false
+    }
+
+    /// Exact lookup. `Ok(None)` means the source doesn't know the word.
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>>;
+
+    /// Headwords starting with `prefix`, best first.
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>>;
+
+    /// Feed every headword, with its frequency rank if known, to `visit`
+    /// for the autocomplete index. Returns `false` for sources that can't
+    /// list their words (e.g. network servers) or whose words aren't
+    /// definitions (translation packs); those are searched directly.
+    fn headwords(&self, _visit: &mut dyn FnMut(&str, Option<u32>)) -> DictionaryResult<bool> {
+        Ok(false)
+    }
+
+    /// Feed each entry's definition and example text to `visit`, for the
+    /// reverse (meaning to word) index. Returns `false` like `headwords`.
+    fn documents(&self, _visit: &mut dyn FnMut(&str, &[String])) -> DictionaryResult<bool> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+use tauri::State;
+
+use crate::error::{DictionaryError, DictionaryResult};
+
+// Engines tried on PATH when none is configured, best first
+const ENGINES: &[&str] = &["espeak-ng", "espeak"];
+// Longest text we synthesize; enough for a phrase, not a paragraph
+const MAX_SPEECH_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeechVoice {
+    #[default]
+    Us,
+    Uk,
+}
+
+impl SpeechVoice {
+    fn espeak_voice(self) -> &'static str {
+        match self {
+            SpeechVoice::Us => "en-us",
+            SpeechVoice::Uk => "en-gb",
+        }
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            SpeechVoice::Us => "us",
+            SpeechVoice::Uk => "uk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeechSettings {
+    #[serde(default)]
+    pub voice: SpeechVoice,
+    /// espeak-ng executable; found on PATH when unset
+    #[serde(default)]
+    pub engine_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pronounced {
+    /// The cached WAV file, for the frontend to replay
+    pub path: String,
+    pub voice: SpeechVoice,
+    /// Whether the audio came from the cache rather than the engine
+    pub cached: bool,
+}
+
+/// Speaks words through a locally installed espeak-ng. Audio is cached
+/// on disk per word and voice, so each is only synthesized once.
+pub struct Speaker {
+    cache_dir: PathBuf,
+    settings: RwLock<SpeechSettings>,
+}
+
+impl Speaker {
+    pub fn new(cache_dir: PathBuf, settings: SpeechSettings) -> Self {
+        Self {
+            cache_dir,
+            settings: RwLock::new(settings),
+        }
+    }
+
+    pub fn apply_settings(&self, settings: SpeechSettings) {
+        if let Ok(mut current) = self.settings.write() {
+            *current = settings;
+        }
+    }
+
+    fn settings(&self) -> SpeechSettings {
+        self.settings.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Where the audio for `word` in `voice` is cached
+    fn cache_path(&self, word: &str, voice: SpeechVoice) -> PathBuf {
+        // Percent-encoding keeps phrases and accented words valid file names
+        let name = urlencoding::encode(&word.to_lowercase()).into_owned();
+        self.cache_dir.join(voice.id()).join(format!("{}.wav", name))
+    }
+
+    fn engine(&self) -> DictionaryResult<PathBuf> {
+        if let Some(path) = self.settings().engine_path.filter(|p| !p.trim().is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+
+        let mut candidates: Vec<PathBuf> = std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        if cfg!(target_os = "windows") {
+            candidates.push(PathBuf::from(r"C:\Program Files\eSpeak NG"));
+        }
+
+        ENGINES.iter()
+            .flat_map(|engine| candidates.iter().map(move |dir| dir.join(format!("{}{}", engine, std::env::consts::EXE_SUFFIX))))
+            .find(|path| path.is_file())
+            .ok_or_else(|| speech_error("espeak-ng is not installed; install it or set its path in settings"))
+    }
+
+    /// The audio for `word`, synthesizing it on a cache miss. Returns the
+    /// file and whether it was already cached.
+    pub fn audio_file(&self, word: &str, voice: Option<SpeechVoice>) -> DictionaryResult<(PathBuf, bool)> {
+        let word = word.split_whitespace().collect::<Vec<_>>().join(" ");
+        if word.is_empty() || word.chars().count() > MAX_SPEECH_CHARS {
+            return Err(DictionaryError::InvalidInput {
+                message: format!("Can only pronounce text of 1 to {} characters", MAX_SPEECH_CHARS),
+            });
+        }
+
+        let voice = voice.unwrap_or(self.settings().voice);
+        let path = self.cache_path(&word, voice);
+        if path.is_file() {
+            return Ok((path, true));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| speech_error(&format!("{}: {}", dir.display(), e)))?;
+        }
+        // Written beside the final file and renamed, so a failed run never
+        // leaves a truncated file to be served from the cache
+        let partial = path.with_extension("wav.partial");
+        let output = Command::new(self.engine()?)
+            .args(["-v", voice.espeak_voice(), "-w"])
+            .arg(&partial)
+            .arg("--")
+            .arg(&word)
+            .output()
+            .map_err(|e| speech_error(&format!("failed to run espeak-ng: {}", e)))?;
+
+        if !output.status.success() || !partial.is_file() {
+            let _ = fs::remove_file(&partial);
+            return Err(speech_error(&format!(
+                "espeak-ng failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        fs::rename(&partial, &path).map_err(|e| speech_error(&format!("{}: {}", path.display(), e)))?;
+        println!("Synthesized '{}' ({}) to {}", word, voice.id(), path.display());
+        Ok((path, false))
+    }
+}
+
+fn speech_error(message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "espeak-ng".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Play a WAV file with the platform's own player, without waiting for
+/// it to finish
+fn play(path: &Path) -> DictionaryResult<()> {
+    let players: Vec<Command> = if cfg!(target_os = "macos") {
+        let mut afplay = Command::new("afplay");
+        afplay.arg(path);
+        vec![afplay]
+    } else if cfg!(target_os = "windows") {
+        let mut powershell = Command::new("powershell");
+        let script = format!("(New-Object Media.SoundPlayer '{}').PlaySync()", path.display().to_string().replace('\'', "''"));
+        powershell.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        vec![powershell]
+    } else {
+        ["paplay", "aplay", "pw-play"].iter()
+            .map(|player| {
+                let mut command = Command::new(player);
+                command.arg(path);
+                command
+            })
+            .collect()
+    };
+
+    for mut player in players {
+        match player.spawn() {
+            Ok(mut child) => {
+                // Reap the player once it's done so it doesn't linger as a zombie
+                std::thread::spawn(move || {
+                    let _ = child.wait();
+                });
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(speech_error(&format!("failed to play audio: {}", e))),
+        }
+    }
+    Err(speech_error("no audio player found"))
+}
+
+/// Tauri command to speak a word offline, in the configured voice unless
+/// one is given
+#[tauri::command]
+pub fn pronounce_word(word: String, voice: Option<SpeechVoice>, speaker: State<'_, Arc<Speaker>>) -> Result<Pronounced, String> {
+    let (path, cached) = speaker.audio_file(&word, voice).map_err(|e| e.user_message())?;
+    play(&path).map_err(|e| e.user_message())?;
+    Ok(Pronounced {
+        path: path.display().to_string(),
+        voice: voice.unwrap_or(speaker.settings().voice),
+        cached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_paths_and_hits() {
+        let dir = std::env::temp_dir().join(format!("speech-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let speaker = Speaker::new(dir.clone(), SpeechSettings {
+            voice: SpeechVoice::Uk,
+            engine_path: Some(dir.join("missing-espeak-ng").display().to_string()),
+        });
+
+        assert_eq!(speaker.cache_path("Give up", SpeechVoice::Us), dir.join("us").join("give%20up.wav"));
+        assert_eq!(speaker.cache_path("café", SpeechVoice::Uk), dir.join("uk").join("caf%C3%A9.wav"));
+
+        // A cached file is served without running the engine
+        fs::create_dir_all(dir.join("uk")).unwrap();
+        fs::write(dir.join("uk").join("hello.wav"), b"RIFF").unwrap();
+        assert_eq!(speaker.audio_file(" Hello ", None).unwrap(), (dir.join("uk").join("hello.wav"), true));
+
+        // A miss needs the engine, which isn't there
+        assert!(speaker.audio_file("hello", Some(SpeechVoice::Us)).is_err());
+        assert!(!dir.join("us").join("hello.wav").exists());
+        assert!(speaker.audio_file("   ", None).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_synthesizes_once() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("speech-engine-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // Stands in for espeak-ng: writes "<voice> <text>" to the -w file
+        let engine = dir.join("fake-espeak");
+        fs::write(&engine, "#!/bin/sh\nprintf '%s %s' \"$2\" \"$6\" > \"$4\"\n").unwrap();
+        fs::set_permissions(&engine, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let speaker = Speaker::new(dir.join("cache"), SpeechSettings {
+            voice: SpeechVoice::Us,
+            engine_path: Some(engine.display().to_string()),
+        });
+        let (path, cached) = speaker.audio_file("tomato", Some(SpeechVoice::Uk)).unwrap();
+        assert!(!cached);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "en-gb tomato");
+        assert_eq!(speaker.audio_file("tomato", Some(SpeechVoice::Uk)).unwrap(), (path, true));
+        assert!(!speaker.audio_file("tomato", None).unwrap().1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
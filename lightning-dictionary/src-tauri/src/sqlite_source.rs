@@ -0,0 +1,254 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use rusqlite::{params, Connection, OpenFlags};
+use crate::cache::Definition;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::source::DictionarySource;
+
+/// Reads word data straight from a SQLite database, so a single-machine
+/// install can answer lookups without the Node API running.
+///
+/// Expects a `words` table shaped like the API's `WordDefinition`:
+/// `word TEXT, rank INTEGER, pos TEXT, frequency INTEGER, definitions TEXT,
+/// pronunciation TEXT, examples TEXT`, with the list columns stored as JSON
+/// arrays and one row per part of speech. `build_sqlite_dictionary.py`
+/// writes the API's word data in this shape.
+pub struct SqliteSource {
+    connection: Mutex<Connection>,
+    path: PathBuf,
+    name: String,
+}
+
+impl SqliteSource {
+    pub fn open(path: &Path) -> DictionaryResult<Self> {
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        ).map_err(|e| sqlite_error(path, e))?;
+
+        let has_words: bool = connection
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'words')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| sqlite_error(path, e))?;
+
+        if !has_words {
+            return Err(DictionaryError::SourceError {
+                source: "sqlite".to_string(),
+                message: format!("{} has no 'words' table", path.display()),
+            });
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            path: path.to_path_buf(),
+            name: "sqlite".to_string(),
+        })
+    }
+
+    /// Register under a different source name, e.g. for the import store
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl DictionarySource for SqliteSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        let connection = self.connection.lock().map_err(|e| DictionaryError::SourceError {
+            source: "sqlite".to_string(),
+            message: format!("Failed to acquire connection lock: {}", e),
+        })?;
+
+        type Row = (String, Option<String>, Option<i64>, Option<String>, Option<String>, Option<i64>);
+        let rows: Vec<Row> = connection
+            .prepare_cached(
+                "SELECT word, pos, frequency, definitions, pronunciation, rank
+                 FROM words WHERE word = ?1 COLLATE NOCASE
+                 ORDER BY rank, rowid",
+            )
+            .and_then(|mut statement| {
+                statement.query_map(params![word.trim()], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+                })?
+                .collect()
+            })
+            .map_err(|e| sqlite_error(&self.path, e))?;
+        let Some((headword, pos, frequency, _, _, rank)) = rows.first().cloned() else { return Ok(None) };
+        let pos = pos.unwrap_or_default();
+
+        // Senses for another part of speech say which one they are
+        let definitions: Vec<String> = rows.iter()
+            .flat_map(|(_, row_pos, _, json, _, _)| {
+                let row_pos = row_pos.clone().unwrap_or_default();
+                let other_pos = !row_pos.is_empty() && row_pos != pos;
+                json.as_deref()
+                    .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |d| if other_pos { format!("({}) {}", row_pos, d) } else { d })
+            })
+            .collect();
+
+        Ok(Some(Definition {
+            word: headword,
+            // Same placeholder the API conversion uses for definition-less rows
+            definitions: if definitions.is_empty() {
+                vec![format!("{} (rank: {}, frequency: {})", pos, rank.unwrap_or(0), frequency.unwrap_or(0))]
+            } else {
+                definitions
+            },
+            pos,
+            pronunciation: rows.iter().find_map(|(_, _, _, _, p, _)| p.clone().filter(|p| !p.is_empty())),
+            frequency: frequency.map(|f| f.clamp(0, u32::MAX as i64) as u32),
+            inflection: None,
+            language: None,
+        }))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let connection = self.connection.lock().map_err(|e| DictionaryError::SourceError {
+            source: "sqlite".to_string(),
+            message: format!("Failed to acquire connection lock: {}", e),
+        })?;
+
+        // Escape LIKE wildcards so user input is matched literally
+        let pattern = format!(
+            "{}%",
+            prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+
+        let mut statement = connection
+            .prepare_cached(
+                "SELECT word FROM words WHERE word LIKE ?1 ESCAPE '\\'
+                 GROUP BY word ORDER BY MIN(rank), MIN(rowid) LIMIT ?2",
+            )
+            .map_err(|e| sqlite_error(&self.path, e))?;
+
+        let words = statement
+            .query_map(params![pattern, limit as i64], |row| row.get::<_, String>(0))
+            .map_err(|e| sqlite_error(&self.path, e))?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(words)
+    }
+
+    fn headwords(&self, visit: &mut dyn FnMut(&str, Option<u32>)) -> DictionaryResult<bool> {
+        let connection = self.connection.lock().map_err(|e| DictionaryError::SourceError {
+            source: "sqlite".to_string(),
+            message: format!("Failed to acquire connection lock: {}", e),
+        })?;
+
+        let mut statement = connection
+            .prepare("SELECT word, MIN(rank) FROM words GROUP BY word")
+            .map_err(|e| sqlite_error(&self.path, e))?;
+        let mut rows = statement.query([]).map_err(|e| sqlite_error(&self.path, e))?;
+        while let Some(row) = rows.next().map_err(|e| sqlite_error(&self.path, e))? {
+            if let Ok(word) = row.get::<_, String>(0) {
+                let rank = row.get::<_, Option<i64>>(1).ok().flatten();
+                visit(&word, rank.map(|r| r.clamp(0, u32::MAX as i64) as u32));
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn documents(&self, visit: &mut dyn FnMut(&str, &[String])) -> DictionaryResult<bool> {
+        let connection = self.connection.lock().map_err(|e| DictionaryError::SourceError {
+            source: "sqlite".to_string(),
+            message: format!("Failed to acquire connection lock: {}", e),
+        })?;
+
+        let mut statement = connection
+            .prepare("SELECT word, definitions, examples FROM words")
+            .map_err(|e| sqlite_error(&self.path, e))?;
+        let mut rows = statement.query([]).map_err(|e| sqlite_error(&self.path, e))?;
+        while let Some(row) = rows.next().map_err(|e| sqlite_error(&self.path, e))? {
+            let Ok(word) = row.get::<_, String>(0) else { continue };
+            let mut texts: Vec<String> = Vec::new();
+            for column in 1..=2 {
+                if let Ok(Some(json)) = row.get::<_, Option<String>>(column) {
+                    texts.extend(serde_json::from_str::<Vec<String>>(&json).unwrap_or_default());
+                }
+            }
+            visit(&word, &texts);
+        }
+
+        Ok(true)
+    }
+}
+
+fn sqlite_error(path: &Path, e: rusqlite::Error) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "sqlite".to_string(),
+        message: format!("{}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_db(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let connection = Connection::open(&path).unwrap();
+        connection.execute_batch(
+            "CREATE TABLE words (word TEXT, rank INTEGER, pos TEXT, frequency INTEGER,
+                                 definitions TEXT, pronunciation TEXT, examples TEXT);
+             INSERT INTO words VALUES ('house', 200, 'n', 5000, '[\"a building for living in\"]', '/haʊs/', '[]');
+             INSERT INTO words VALUES ('household', 900, 'n', 800, '[]', '', '[]');
+             INSERT INTO words VALUES ('hous_e', 5000, 'n', 1, '[]', '', '[]');
+             INSERT INTO words VALUES ('house', 200, 'v', 5000, '[\"to provide shelter for\"]', '', '[]');",
+        ).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_lookup_and_search() {
+        let path = create_test_db("sqlite-source-lookup");
+        let source = SqliteSource::open(&path).unwrap();
+
+        let definition = source.lookup("HOUSE").unwrap().unwrap();
+        assert_eq!(definition.word, "house");
+        // Rows for other parts of speech are merged in, labelled
+        assert_eq!(definition.definitions, vec!["a building for living in", "(v) to provide shelter for"]);
+        assert_eq!(definition.pronunciation.as_deref(), Some("/haʊs/"));
+        assert!(source.lookup("castle").unwrap().is_none());
+
+        // Ordered by rank, and '_' in the query is literal rather than a wildcard
+        assert_eq!(source.search("hous", 10).unwrap(), vec!["house", "household", "hous_e"]);
+        assert_eq!(source.search("hous_", 10).unwrap(), vec!["hous_e"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rejects_database_without_words_table() {
+        let path = std::env::temp_dir().join(format!("sqlite-source-empty-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Connection::open(&path).unwrap().execute_batch("CREATE TABLE users (id INTEGER);").unwrap();
+
+        assert!(SqliteSource::open(&path).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
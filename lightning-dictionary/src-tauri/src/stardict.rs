@@ -0,0 +1,882 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use flate2::read::GzDecoder;
+use flate2::{Decompress, FlushDecompress};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::settings::{Settings, SettingsManager};
+use crate::source::DictionarySource;
+
+/// Metadata from a dictionary's `.ifo` file
+#[derive(Debug, Clone, Serialize)]
+pub struct StarDictInfo {
+    pub id: String,
+    pub bookname: String,
+    pub wordcount: usize,
+    pub synwordcount: usize,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub enabled: bool,
+    #[serde(skip)]
+    idxoffsetbits: u32,
+    #[serde(skip)]
+    sametypesequence: Option<String>,
+}
+
+impl StarDictInfo {
+    fn parse(id: &str, contents: &str) -> DictionaryResult<Self> {
+        let mut lines = contents.lines();
+        if lines.next().map(|l| l.trim_start_matches('\u{feff}').trim()) != Some("StarDict's dict ifo file") {
+            return Err(stardict_error(id, "not a StarDict .ifo file"));
+        }
+
+        let fields: HashMap<&str, &str> = lines
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .collect();
+
+        let bookname = fields.get("bookname")
+            .ok_or_else(|| stardict_error(id, ".ifo is missing bookname"))?;
+
+        Ok(Self {
+            id: id.to_string(),
+            bookname: bookname.to_string(),
+            wordcount: fields.get("wordcount").and_then(|v| v.parse().ok()).unwrap_or(0),
+            synwordcount: fields.get("synwordcount").and_then(|v| v.parse().ok()).unwrap_or(0),
+            author: fields.get("author").map(|v| v.to_string()),
+            description: fields.get("description").map(|v| v.to_string()),
+            enabled: true,
+            idxoffsetbits: fields.get("idxoffsetbits").and_then(|v| v.parse().ok()).unwrap_or(32),
+            sametypesequence: fields.get("sametypesequence").map(|v| v.to_string()).filter(|v| !v.is_empty()),
+        })
+    }
+}
+
+struct IdxEntry {
+    word: String,
+    offset: u64,
+    size: u32,
+}
+
+/// Random access into a `.dict.dz` file. dictzip is gzip with a table of
+/// independently-deflated chunk sizes in the header's "RA" extra field.
+struct DictZip {
+    file: File,
+    chunk_len: usize,
+    // Start of each chunk in the file, plus the end of the last one
+    chunk_offsets: Vec<u64>,
+    last_chunk: Option<(usize, Vec<u8>)>,
+}
+
+impl DictZip {
+    /// Returns `Ok(None)` when the file is plain gzip without a chunk table.
+    fn open(path: &Path) -> io::Result<Option<Self>> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; 10];
+        file.read_exact(&mut header)?;
+        if header[0] != 0x1f || header[1] != 0x8b || header[2] != 8 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a gzip file"));
+        }
+
+        let flags = header[3];
+        if flags & 0x04 == 0 {
+            return Ok(None);
+        }
+
+        let mut xlen = [0u8; 2];
+        file.read_exact(&mut xlen)?;
+        let mut extra = vec![0u8; u16::from_le_bytes(xlen) as usize];
+        file.read_exact(&mut extra)?;
+
+        // Optional file name and comment, then header CRC
+        for flag in [0x08u8, 0x10] {
+            if flags & flag != 0 {
+                let mut byte = [0u8; 1];
+                loop {
+                    file.read_exact(&mut byte)?;
+                    if byte[0] == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+        if flags & 0x02 != 0 {
+            file.seek(SeekFrom::Current(2))?;
+        }
+        let data_start = file.stream_position()?;
+
+        let mut pos = 0;
+        while pos + 4 <= extra.len() {
+            let id = &extra[pos..pos + 2];
+            let len = u16::from_le_bytes([extra[pos + 2], extra[pos + 3]]) as usize;
+            let body = &extra[(pos + 4).min(extra.len())..(pos + 4 + len).min(extra.len())];
+            pos += 4 + len;
+
+            if id != b"RA" || body.len() < 6 {
+                continue;
+            }
+
+            let chunk_len = u16::from_le_bytes([body[2], body[3]]) as usize;
+            let chunk_count = u16::from_le_bytes([body[4], body[5]]) as usize;
+            if chunk_len == 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "dictzip chunk length is zero"));
+            }
+            if body.len() < 6 + chunk_count * 2 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated dictzip chunk table"));
+            }
+
+            let mut chunk_offsets = Vec::with_capacity(chunk_count + 1);
+            let mut offset = data_start;
+            chunk_offsets.push(offset);
+            for i in 0..chunk_count {
+                offset += u16::from_le_bytes([body[6 + i * 2], body[7 + i * 2]]) as u64;
+                chunk_offsets.push(offset);
+            }
+
+            return Ok(Some(Self {
+                file,
+                chunk_len,
+                chunk_offsets,
+                last_chunk: None,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    fn chunk(&mut self, index: usize) -> io::Result<&[u8]> {
+        let cached = matches!(&self.last_chunk, Some((i, _)) if *i == index);
+        if !cached {
+            let (start, end) = match (self.chunk_offsets.get(index), self.chunk_offsets.get(index + 1)) {
+                (Some(start), Some(end)) => (*start, *end),
+                _ => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "offset past end of dictzip data")),
+            };
+
+            let mut compressed = vec![0u8; (end - start) as usize];
+            self.file.seek(SeekFrom::Start(start))?;
+            self.file.read_exact(&mut compressed)?;
+
+            // Chunks end on a full flush, so each inflates on its own as raw deflate
+            let mut output = Vec::with_capacity(self.chunk_len);
+            Decompress::new(false)
+                .decompress_vec(&compressed, &mut output, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            self.last_chunk = Some((index, output));
+        }
+
+        Ok(self.last_chunk.as_ref().map(|(_, data)| data.as_slice()).unwrap_or(&[]))
+    }
+
+    fn read(&mut self, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(size);
+        let mut position = offset as usize;
+        let end = position + size;
+
+        while position < end {
+            let index = position / self.chunk_len;
+            let within = position % self.chunk_len;
+            let chunk = self.chunk(index)?;
+            if within >= chunk.len() {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "entry runs past end of dictzip data"));
+            }
+            let take = (chunk.len() - within).min(end - position);
+            result.extend_from_slice(&chunk[within..within + take]);
+            position += take;
+        }
+
+        Ok(result)
+    }
+}
+
+enum DictData {
+    Plain(File),
+    DictZip(DictZip),
+    // Plain gzip without a chunk table has to be inflated up front
+    Memory(Vec<u8>),
+}
+
+impl DictData {
+    fn open(ifo_path: &Path) -> io::Result<Self> {
+        let plain = sibling(ifo_path, "dict");
+        if plain.exists() {
+            return Ok(DictData::Plain(File::open(plain)?));
+        }
+
+        let compressed = sibling(ifo_path, "dict.dz");
+        match DictZip::open(&compressed)? {
+            Some(dictzip) => Ok(DictData::DictZip(dictzip)),
+            None => {
+                let mut data = Vec::new();
+                GzDecoder::new(File::open(&compressed)?).read_to_end(&mut data)?;
+                Ok(DictData::Memory(data))
+            }
+        }
+    }
+
+    fn read(&mut self, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        match self {
+            DictData::Plain(file) => {
+                let mut buffer = vec![0u8; size];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut buffer)?;
+                Ok(buffer)
+            }
+            DictData::DictZip(dictzip) => dictzip.read(offset, size),
+            DictData::Memory(data) => data
+                .get(offset as usize..offset as usize + size)
+                .map(|slice| slice.to_vec())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "entry runs past end of dictionary data")),
+        }
+    }
+}
+
+/// One installed StarDict dictionary, served as a `DictionarySource`.
+pub struct StarDict {
+    info: StarDictInfo,
+    source_name: String,
+    entries: Vec<IdxEntry>,
+    // Lowercased headwords and synonyms, sorted, pointing into `entries`
+    keys: Vec<(String, u32)>,
+    data: Mutex<DictData>,
+    // Every entry's definition text, read on the first reverse-index build
+    // so later rebuilds don't decompress the whole dictionary again
+    documents: OnceLock<Vec<Vec<String>>>,
+}
+
+impl StarDict {
+    /// Open a dictionary from its `.ifo` path; the other files share its stem.
+    pub fn open(ifo_path: &Path) -> DictionaryResult<Self> {
+        let id = dictionary_id(ifo_path);
+        let contents = fs::read_to_string(ifo_path).map_err(|e| stardict_error(&id, &e.to_string()))?;
+        let info = StarDictInfo::parse(&id, &contents)?;
+
+        let idx_bytes = read_maybe_gz(&sibling(ifo_path, "idx"), &sibling(ifo_path, "idx.gz"))
+            .map_err(|e| stardict_error(&id, &format!("failed to read index: {}", e)))?;
+        let entries = parse_idx(&idx_bytes, info.idxoffsetbits)
+            .ok_or_else(|| stardict_error(&id, "index file is truncated"))?;
+
+        let mut keys: Vec<(String, u32)> = entries.iter()
+            .enumerate()
+            .map(|(i, e)| (e.word.to_lowercase(), i as u32))
+            .collect();
+
+        if let Ok(syn_bytes) = read_maybe_gz(&sibling(ifo_path, "syn"), &sibling(ifo_path, "syn.gz")) {
+            let synonyms = parse_syn(&syn_bytes)
+                .ok_or_else(|| stardict_error(&id, "synonym file is truncated"))?;
+            keys.extend(synonyms.into_iter()
+                .filter(|(_, index)| (*index as usize) < entries.len())
+                .map(|(word, index)| (word.to_lowercase(), index)));
+        }
+        keys.sort();
+        keys.dedup();
+
+        let data = DictData::open(ifo_path)
+            .map_err(|e| stardict_error(&id, &format!("failed to open dictionary data: {}", e)))?;
+
+        Ok(Self {
+            source_name: format!("stardict:{}", id),
+            info,
+            entries,
+            keys,
+            data: Mutex::new(data),
+            documents: OnceLock::new(),
+        })
+    }
+
+    fn key_range(&self, key: &str) -> &[(String, u32)] {
+        let start = self.keys.partition_point(|(k, _)| k.as_str() < key);
+        let end = start + self.keys[start..].partition_point(|(k, _)| k == key);
+        &self.keys[start..end]
+    }
+
+    /// Pronunciation and definition lines of one entry, as plain text
+    fn entry_text(&self, index: u32) -> DictionaryResult<(Option<String>, Vec<String>)> {
+        let mut definitions = Vec::new();
+        let mut pronunciation = None;
+        for (kind, data) in self.read_entry(index)? {
+            let text = String::from_utf8_lossy(&data);
+            match kind {
+                't' => pronunciation = pronunciation.or_else(|| Some(text.trim().to_string())),
+                'm' | 'l' | 'y' | 'k' | 'w' => definitions.extend(text_lines(&text)),
+                'g' | 'h' | 'x' => definitions.extend(text_lines(&strip_markup(&text))),
+                // Resources, audio and pictures can't be shown in the popup
+                _ => {}
+            }
+        }
+        Ok((pronunciation, definitions))
+    }
+
+    fn read_entry(&self, index: u32) -> DictionaryResult<Vec<(char, Vec<u8>)>> {
+        let entry = &self.entries[index as usize];
+        let raw = {
+            let mut data = self.data.lock().map_err(|e| stardict_error(&self.info.id, &e.to_string()))?;
+            data.read(entry.offset, entry.size as usize)
+                .map_err(|e| stardict_error(&self.info.id, &format!("failed to read '{}': {}", entry.word, e)))?
+        };
+
+        Ok(parse_fields(&raw, self.info.sametypesequence.as_deref()))
+    }
+}
+
+impl DictionarySource for StarDict {
+    fn name(&self) -> &str {
+        &self.source_name
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        let key = word.trim().to_lowercase();
+        let mut indices: Vec<u32> = self.key_range(&key).iter().map(|(_, i)| *i).collect();
+        if indices.is_empty() {
+            return Ok(None);
+        }
+        indices.sort();
+        indices.dedup();
+
+        let mut definitions = Vec::new();
+        let mut pronunciation = None;
+        for index in &indices {
+            let (entry_pronunciation, entry_definitions) = self.entry_text(*index)?;
+            pronunciation = pronunciation.or(entry_pronunciation);
+            definitions.extend(entry_definitions);
+        }
+
+        Ok(Some(Definition {
+            word: self.entries[indices[0] as usize].word.clone(),
+            pronunciation,
+            pos: String::new(),
+            definitions,
+            frequency: None,
+            inflection: None,
+            language: None,
+        }))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let start = self.keys.partition_point(|(k, _)| k.as_str() < prefix.as_str());
+        let mut results: Vec<String> = Vec::new();
+        for (key, index) in &self.keys[start..] {
+            if !key.starts_with(&prefix) || results.len() >= limit {
+                break;
+            }
+            let word = &self.entries[*index as usize].word;
+            // Synonyms resolve to their main entry; don't list it twice
+            if !results.contains(word) {
+                results.push(word.clone());
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn headwords(&self, visit: &mut dyn FnMut(&str, Option<u32>)) -> DictionaryResult<bool> {
+        for entry in &self.entries {
+            visit(&entry.word, None);
+        }
+        Ok(true)
+    }
+
+    fn documents(&self, visit: &mut dyn FnMut(&str, &[String])) -> DictionaryResult<bool> {
+        if self.documents.get().is_none() {
+            let texts = (0..self.entries.len() as u32)
+                .map(|index| self.entry_text(index).map(|(_, definitions)| definitions))
+                .collect::<DictionaryResult<Vec<_>>>()?;
+            let _ = self.documents.set(texts);
+        }
+        for (entry, definitions) in self.entries.iter().zip(self.documents.get().into_iter().flatten()) {
+            visit(&entry.word, definitions);
+        }
+        Ok(true)
+    }
+}
+
+fn stardict_error(id: &str, message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: format!("stardict:{}", id),
+        message: message.to_string(),
+    }
+}
+
+/// A dictionary's id is the name of the directory it's installed in
+fn dictionary_id(ifo_path: &Path) -> String {
+    ifo_path.parent()
+        .and_then(|p| p.file_name())
+        .or_else(|| ifo_path.file_stem())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Another file of the dictionary at `ifo_path`: same stem, which may
+/// itself contain dots ("en.wiktionary.ifo"), with extension `ext`
+fn sibling(ifo_path: &Path, ext: &str) -> PathBuf {
+    let stem = ifo_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    ifo_path.with_file_name(format!("{}.{}", stem, ext))
+}
+
+fn read_maybe_gz(plain: &Path, gzipped: &Path) -> io::Result<Vec<u8>> {
+    if plain.exists() {
+        return fs::read(plain);
+    }
+    let mut data = Vec::new();
+    GzDecoder::new(File::open(gzipped)?).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Split off a NUL-terminated UTF-8 string, returning it and the rest
+fn take_cstr(bytes: &[u8]) -> Option<(String, &[u8])> {
+    let end = bytes.iter().position(|b| *b == 0)?;
+    Some((String::from_utf8_lossy(&bytes[..end]).to_string(), &bytes[end + 1..]))
+}
+
+fn parse_idx(mut bytes: &[u8], offset_bits: u32) -> Option<Vec<IdxEntry>> {
+    let offset_len = if offset_bits == 64 { 8 } else { 4 };
+    let mut entries = Vec::new();
+
+    while !bytes.is_empty() {
+        let (word, rest) = take_cstr(bytes)?;
+        if rest.len() < offset_len + 4 {
+            return None;
+        }
+        let offset = if offset_len == 8 {
+            u64::from_be_bytes(rest[..8].try_into().ok()?)
+        } else {
+            u32::from_be_bytes(rest[..4].try_into().ok()?) as u64
+        };
+        let size = u32::from_be_bytes(rest[offset_len..offset_len + 4].try_into().ok()?);
+        entries.push(IdxEntry { word, offset, size });
+        bytes = &rest[offset_len + 4..];
+    }
+
+    Some(entries)
+}
+
+fn parse_syn(mut bytes: &[u8]) -> Option<Vec<(String, u32)>> {
+    let mut synonyms = Vec::new();
+
+    while !bytes.is_empty() {
+        let (word, rest) = take_cstr(bytes)?;
+        if rest.len() < 4 {
+            return None;
+        }
+        synonyms.push((word, u32::from_be_bytes(rest[..4].try_into().ok()?)));
+        bytes = &rest[4..];
+    }
+
+    Some(synonyms)
+}
+
+/// Split an entry's raw data into typed fields. Lowercase types are text,
+/// uppercase are binary; with `sametypesequence` the type bytes are omitted
+/// and the final field runs to the end of the entry.
+fn parse_fields(mut data: &[u8], sametypesequence: Option<&str>) -> Vec<(char, Vec<u8>)> {
+    let mut fields = Vec::new();
+
+    match sametypesequence {
+        Some(sequence) => {
+            let types: Vec<char> = sequence.chars().collect();
+            for (i, kind) in types.iter().enumerate() {
+                let is_last = i == types.len() - 1;
+                let (field, rest) = if is_last {
+                    (data, &data[data.len()..])
+                } else if kind.is_ascii_lowercase() {
+                    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+                    (&data[..end], &data[(end + 1).min(data.len())..])
+                } else {
+                    if data.len() < 4 {
+                        break;
+                    }
+                    let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+                    let end = (4 + size).min(data.len());
+                    (&data[4..end], &data[end..])
+                };
+                fields.push((*kind, field.to_vec()));
+                data = rest;
+            }
+        }
+        None => {
+            while let Some((&kind, rest)) = data.split_first() {
+                let kind = kind as char;
+                if kind.is_ascii_lowercase() {
+                    let end = rest.iter().position(|b| *b == 0).unwrap_or(rest.len());
+                    fields.push((kind, rest[..end].to_vec()));
+                    data = &rest[(end + 1).min(rest.len())..];
+                } else {
+                    if rest.len() < 4 {
+                        break;
+                    }
+                    let size = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+                    let end = (4 + size).min(rest.len());
+                    fields.push((kind, rest[4..end].to_vec()));
+                    data = &rest[end..];
+                }
+            }
+        }
+    }
+
+    fields
+}
+
+fn text_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Crude HTML/Pango/XDXF to text: line-breaking tags become newlines, other tags vanish
+fn strip_markup(markup: &str) -> String {
+    let mut text = String::with_capacity(markup.len());
+    let mut tag = String::new();
+    let mut in_tag = false;
+
+    for c in markup.chars() {
+        match (in_tag, c) {
+            (false, '<') => {
+                in_tag = true;
+                tag.clear();
+            }
+            (true, '>') => {
+                in_tag = false;
+                let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("").to_lowercase();
+                if matches!(name.as_str(), "br" | "br/" | "p" | "div" | "li" | "def" | "ar") {
+                    text.push('\n');
+                }
+            }
+            (true, c) => tag.push(c),
+            (false, c) => text.push(c),
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Where installed StarDict dictionaries live: one directory per dictionary
+pub fn stardict_root<R: Runtime>(handle: &AppHandle<R>) -> Option<PathBuf> {
+    handle.path().app_data_dir().ok().map(|dir| dir.join("dictionaries").join("stardict"))
+}
+
+/// All `.ifo` files one level below `root`
+fn discover(root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(dirs) = fs::read_dir(root) else { return found };
+
+    for dir in dirs.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        if let Ok(files) = fs::read_dir(&dir) {
+            found.extend(files.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|ext| ext.to_str()) == Some("ifo")));
+        }
+    }
+
+    found.sort();
+    found
+}
+
+/// Copy every dictionary found in `source_dir` (directly or one level down)
+/// into its own directory under `root`. Returns the installed `.ifo` paths.
+fn install_from_directory(source_dir: &Path, root: &Path) -> DictionaryResult<Vec<PathBuf>> {
+    let io_error = |e: io::Error| stardict_error("install", &e.to_string());
+
+    let mut ifo_files: Vec<PathBuf> = fs::read_dir(source_dir).map_err(io_error)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|ext| ext.to_str()) == Some("ifo"))
+        .collect();
+    ifo_files.extend(discover(source_dir));
+
+    if ifo_files.is_empty() {
+        return Err(DictionaryError::InvalidInput {
+            message: format!("No StarDict .ifo files found in {}", source_dir.display()),
+        });
+    }
+
+    let mut installed = Vec::new();
+    for ifo in ifo_files {
+        let stem = ifo.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let target = root.join(&stem);
+        fs::create_dir_all(&target).map_err(io_error)?;
+
+        for ext in ["ifo", "idx", "idx.gz", "dict", "dict.dz", "syn", "syn.gz"] {
+            let file = sibling(&ifo, ext);
+            if file.exists() {
+                fs::copy(&file, target.join(format!("{}.{}", stem, ext))).map_err(io_error)?;
+            }
+        }
+
+        // Make sure it actually opens before reporting success
+        let installed_ifo = target.join(format!("{}.ifo", stem));
+        StarDict::open(&installed_ifo)?;
+        installed.push(installed_ifo);
+    }
+
+    Ok(installed)
+}
+
+/// The `.ifo` file of the installed dictionary `id`
+fn find_installed(root: &Path, id: &str) -> Option<PathBuf> {
+    discover(root).into_iter().find(|ifo| dictionary_id(ifo) == id)
+}
+
+/// Open every installed, enabled dictionary and register it as a source
+pub fn register_stardict_sources<R: Runtime>(handle: &AppHandle<R>, dictionary_service: &DictionaryService, settings: &Settings) {
+    let Some(root) = stardict_root(handle) else { return };
+
+    for ifo in discover(&root) {
+        let id = dictionary_id(&ifo);
+        if settings.sources.stardict_disabled.contains(&id) {
+            continue;
+        }
+        match StarDict::open(&ifo) {
+            Ok(dict) => dictionary_service.add_source(Arc::new(dict)),
+            Err(e) => e.log_error(),
+        }
+    }
+}
+
+/// Tauri command to list installed StarDict dictionaries
+#[tauri::command]
+pub fn list_stardict_dictionaries(app_handle: AppHandle) -> Result<Vec<StarDictInfo>, String> {
+    let settings = SettingsManager::new(&app_handle).map_err(|e| e.to_string())?;
+    let Some(root) = stardict_root(&app_handle) else { return Ok(vec![]) };
+
+    Ok(discover(&root).iter()
+        .filter_map(|ifo| {
+            let id = dictionary_id(ifo);
+            let contents = fs::read_to_string(ifo).ok()?;
+            let mut info = StarDictInfo::parse(&id, &contents).ok()?;
+            info.enabled = !settings.get_settings().sources.stardict_disabled.contains(&id);
+            Some(info)
+        })
+        .collect())
+}
+
+/// Tauri command to install all StarDict dictionaries from a directory
+#[tauri::command]
+pub fn install_stardict_dictionaries(
+    path: String,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<Vec<String>, String> {
+    let root = stardict_root(&app_handle).ok_or("Failed to get app data directory")?;
+    let installed = install_from_directory(Path::new(&path), &root).map_err(|e| e.to_string())?;
+
+    for ifo in &installed {
+        let source_name = format!("stardict:{}", dictionary_id(ifo));
+        dictionary_service.remove_source(&source_name);
+        match StarDict::open(ifo) {
+            Ok(dict) => dictionary_service.add_source(Arc::new(dict)),
+            Err(e) => e.log_error(),
+        }
+    }
+
+    Ok(installed.iter().map(|ifo| dictionary_id(ifo)).collect())
+}
+
+/// Tauri command to enable or disable an installed dictionary
+#[tauri::command]
+pub fn set_stardict_enabled(
+    id: String,
+    enabled: bool,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<(), String> {
+    let mut manager = SettingsManager::new(&app_handle).map_err(|e| e.to_string())?;
+    let mut settings = manager.get_settings().clone();
+    settings.sources.stardict_disabled.retain(|d| d != &id);
+    if !enabled {
+        settings.sources.stardict_disabled.push(id.clone());
+    }
+    manager.update_settings(settings).map_err(|e| e.to_string())?;
+
+    let source_name = format!("stardict:{}", id);
+    dictionary_service.remove_source(&source_name);
+    if enabled {
+        let root = stardict_root(&app_handle).ok_or("Failed to get app data directory")?;
+        let ifo = find_installed(&root, &id).ok_or_else(|| format!("StarDict dictionary '{}' is not installed", id))?;
+        let dict = StarDict::open(&ifo).map_err(|e| e.to_string())?;
+        dictionary_service.add_source(Arc::new(dict));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+
+    fn idx_entry(word: &str, offset: u32, size: u32) -> Vec<u8> {
+        let mut bytes = word.as_bytes().to_vec();
+        bytes.push(0);
+        bytes.extend_from_slice(&offset.to_be_bytes());
+        bytes.extend_from_slice(&size.to_be_bytes());
+        bytes
+    }
+
+    /// Build dictzip data by hand: raw deflate chunks, each ending in a full flush
+    fn dictzip(data: &[u8], chunk_len: usize) -> Vec<u8> {
+        let mut chunks = Vec::new();
+        let mut compress = Compress::new(Compression::default(), false);
+        for chunk in data.chunks(chunk_len) {
+            let mut out = Vec::with_capacity(chunk.len() + 64);
+            let before = compress.total_out();
+            compress.compress_vec(chunk, &mut out, FlushCompress::Full).unwrap();
+            assert_eq!(compress.total_out() - before, out.len() as u64);
+            chunks.push(out);
+        }
+
+        let mut ra = vec![b'R', b'A', 0, 0, 1, 0];
+        ra.extend_from_slice(&(chunk_len as u16).to_le_bytes());
+        ra.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+        for chunk in &chunks {
+            ra.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+        }
+        let ra_len = (ra.len() - 4) as u16;
+        ra[2..4].copy_from_slice(&ra_len.to_le_bytes());
+
+        let mut file = vec![0x1f, 0x8b, 8, 0x04, 0, 0, 0, 0, 0, 3];
+        file.extend_from_slice(&(ra.len() as u16).to_le_bytes());
+        file.extend_from_slice(&ra);
+        for chunk in chunks {
+            file.extend_from_slice(&chunk);
+        }
+        file
+    }
+
+    fn write_test_dictionary(name: &str, stem: &str, compressed: bool) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let apple = "A round fruit.\nGrows on trees.";
+        let apply = "To put to use.";
+        let data = format!("{}{}", apple, apply);
+
+        fs::write(dir.join(format!("{}.ifo", stem)), "StarDict's dict ifo file\nversion=2.4.2\nbookname=Test Dict\nwordcount=2\nsynwordcount=1\nsametypesequence=m\n").unwrap();
+
+        let mut idx = idx_entry("apple", 0, apple.len() as u32);
+        idx.extend(idx_entry("apply", apple.len() as u32, apply.len() as u32));
+        fs::write(dir.join(format!("{}.idx", stem)), idx).unwrap();
+
+        let mut syn = b"pomme\0".to_vec();
+        syn.extend_from_slice(&0u32.to_be_bytes());
+        fs::write(dir.join(format!("{}.syn", stem)), syn).unwrap();
+
+        if compressed {
+            // Small chunks so entries straddle chunk boundaries
+            fs::write(dir.join(format!("{}.dict.dz", stem)), dictzip(data.as_bytes(), 8)).unwrap();
+        } else {
+            fs::write(dir.join(format!("{}.dict", stem)), data).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn test_lookup_with_synonyms() {
+        let dir = write_test_dictionary("stardict-plain", "test", false);
+        let dict = StarDict::open(&dir.join("test.ifo")).unwrap();
+
+        assert_eq!(dict.info.bookname, "Test Dict");
+        let apple = dict.lookup("Apple").unwrap().unwrap();
+        assert_eq!(apple.definitions, vec!["A round fruit.", "Grows on trees."]);
+
+        let synonym = dict.lookup("pomme").unwrap().unwrap();
+        assert_eq!(synonym.word, "apple");
+        assert!(dict.lookup("banana").unwrap().is_none());
+
+        assert_eq!(dict.search("app", 10).unwrap(), vec!["apple", "apply"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dictzip_random_access() {
+        let dir = write_test_dictionary("stardict-dz", "test", true);
+        let dict = StarDict::open(&dir.join("test.ifo")).unwrap();
+
+        assert!(matches!(*dict.data.lock().unwrap(), DictData::DictZip(_)));
+        assert_eq!(dict.lookup("apply").unwrap().unwrap().definitions, vec!["To put to use."]);
+        assert_eq!(dict.lookup("apple").unwrap().unwrap().definitions, vec!["A round fruit.", "Grows on trees."]);
+
+        // Read once for the reverse index, then served from memory
+        let collect = || {
+            let mut documents = Vec::new();
+            dict.documents(&mut |word, texts| documents.push((word.to_string(), texts.to_vec()))).unwrap();
+            documents
+        };
+        let first = collect();
+        assert_eq!(first[1], ("apply".to_string(), vec!["To put to use.".to_string()]));
+        assert!(dict.documents.get().is_some());
+        assert_eq!(collect(), first);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rejects_zero_chunk_length() {
+        let dir = std::env::temp_dir().join(format!("stardict-zero-chunk-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("zero.dict.dz");
+        // chunk_len sits after the gzip header, XLEN, "RA", LEN and VER
+        let mut file = dictzip(b"some data", 8);
+        file[18..20].copy_from_slice(&0u16.to_le_bytes());
+        fs::write(&path, file).unwrap();
+
+        let err = DictZip::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_install_dotted_stem() {
+        let source = write_test_dictionary("stardict-dotted", "en.wiktionary", true);
+        let root = std::env::temp_dir().join(format!("stardict-root-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let installed = install_from_directory(&source, &root).unwrap();
+        assert_eq!(installed, vec![root.join("en.wiktionary").join("en.wiktionary.ifo")]);
+        assert_eq!(find_installed(&root, "en.wiktionary"), Some(installed[0].clone()));
+
+        let dict = StarDict::open(&installed[0]).unwrap();
+        assert_eq!(dict.name(), "stardict:en.wiktionary");
+        assert_eq!(dict.lookup("pomme").unwrap().unwrap().word, "apple");
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_parse_fields_without_sametypesequence() {
+        let mut data = "tˈæpəl\0".as_bytes().to_vec();
+        data.extend_from_slice(b"h<b>fruit</b><br>red\0");
+        data.push(b'W');
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&[1, 2]);
+
+        let fields = parse_fields(&data, None);
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0], ('t', "ˈæpəl".as_bytes().to_vec()));
+        assert_eq!(strip_markup(&String::from_utf8_lossy(&fields[1].1)), "fruit\nred");
+        assert_eq!(fields[2], ('W', vec![1, 2]));
+    }
+}
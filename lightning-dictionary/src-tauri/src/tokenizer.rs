@@ -0,0 +1,227 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// Longest word tried when segmenting unspaced Chinese or Japanese
+const MAX_CJK_WORD: usize = 6;
+// Only the start of a huge selection is searched for a word
+const MAX_SCANNED_CHARS: usize = 1000;
+
+// Base letters and what they become with a combining mark, for text that
+// arrives decomposed ("nai\u{308}ve"); headwords are stored precomposed
+const COMPOSITIONS: &[(char, &str, &str)] = &[
+    ('\u{300}', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('\u{301}', "aeiouycnszAEIOUYCNSZ", "áéíóúýćńśźÁÉÍÓÚÝĆŃŚŹ"),
+    ('\u{302}', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('\u{303}', "anoANO", "ãñõÃÑÕ"),
+    ('\u{308}', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('\u{30a}', "auAU", "åůÅŮ"),
+    ('\u{30c}', "cenrszCENRSZ", "čěňřšžČĚŇŘŠŽ"),
+    ('\u{327}', "csCS", "çşÇŞ"),
+];
+
+/// Fold the typographic variants people actually select into the forms
+/// headwords use: curly apostrophes, non-breaking and other Unicode
+/// hyphens, soft hyphens and decomposed accents
+pub fn normalize(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{2019}' | '\u{2018}' | '\u{2bc}' | '\u{ff07}' => normalized.push('\''),
+            '\u{2010}' | '\u{2011}' | '\u{fe63}' | '\u{ff0d}' => normalized.push('-'),
+            '\u{ad}' | '\u{200b}' | '\u{feff}' => {}
+            _ => {
+                let composed = COMPOSITIONS.iter()
+                    .find(|(mark, _, _)| *mark == c)
+                    .and_then(|(_, bases, composed)| {
+                        let base = normalized.chars().last()?;
+                        let i = bases.chars().position(|b| b == base)?;
+                        composed.chars().nth(i)
+                    });
+                match composed {
+                    Some(composed) => {
+                        normalized.pop();
+                        normalized.push(composed);
+                    }
+                    None => normalized.push(c),
+                }
+            }
+        }
+    }
+    normalized
+}
+
+/// Chinese characters and Japanese kana, written without spaces
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' | '\u{3400}'..='\u{4dbf}' |
+        '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}' | '\u{ff66}'..='\u{ff9f}')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SegmentKind {
+    /// Letters, with hyphenated compounds kept whole ("co-operate")
+    Word,
+    /// Unspaced Chinese or Japanese, to be split by dictionary
+    Cjk,
+    Other,
+}
+
+/// Split on UAX #29 word boundaries, then rejoin hyphenated compounds
+/// (which UAX #29 splits) and runs of CJK characters (which it splits
+/// per character). Returns each segment's kind and byte range.
+fn segments(text: &str) -> Vec<(SegmentKind, usize, usize)> {
+    let mut segments: Vec<(SegmentKind, usize, usize)> = Vec::new();
+    let mut bounds = text.split_word_bound_indices().peekable();
+    while let Some((start, piece)) = bounds.next() {
+        let end = start + piece.len();
+        let kind = if piece.chars().all(is_cjk) {
+            SegmentKind::Cjk
+        } else if piece.chars().any(char::is_alphabetic) {
+            SegmentKind::Word
+        } else {
+            SegmentKind::Other
+        };
+
+        match segments.last_mut() {
+            // Consecutive CJK characters form one run
+            Some((SegmentKind::Cjk, _, last_end)) if kind == SegmentKind::Cjk => *last_end = end,
+            // "co" "-" "operate": the hyphen was held back below
+            Some((SegmentKind::Word, _, last_end)) if kind == SegmentKind::Word && text[..start].ends_with('-') => {
+                *last_end = end;
+            }
+            Some((SegmentKind::Word, _, _)) if piece == "-"
+                && bounds.peek().is_some_and(|(_, next)| next.chars().next().is_some_and(char::is_alphabetic)) => {}
+            _ => segments.push((kind, start, end)),
+        }
+    }
+    segments
+}
+
+/// Split unspaced CJK text into words by forward maximum matching: the
+/// longest known word at each position, else a single character
+fn segment_cjk(run: &str, is_known: &impl Fn(&str) -> bool) -> Vec<String> {
+    let chars: Vec<char> = run.chars().collect();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let longest = (2..=MAX_CJK_WORD.min(chars.len() - i)).rev()
+            .map(|n| chars[i..i + n].iter().collect::<String>())
+            .find(|word| is_known(word));
+        let word = longest.unwrap_or_else(|| chars[i].to_string());
+        i += word.chars().count();
+        words.push(word);
+    }
+    words
+}
+
+/// The words of `text`, normalized, with CJK runs split against
+/// `is_known`
+pub fn words(text: &str, is_known: impl Fn(&str) -> bool) -> Vec<String> {
+    let text = normalize(text);
+    segments(&text).into_iter()
+        .flat_map(|(kind, start, end)| match kind {
+            SegmentKind::Word => vec![text[start..end].trim_matches('\'').to_string()],
+            SegmentKind::Cjk => segment_cjk(&text[start..end], &is_known),
+            SegmentKind::Other => vec![],
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Byte ranges of the words in `text` as written, hyphenated compounds
+/// and CJK runs whole, for mapping matches back onto it
+pub fn word_ranges(text: &str) -> Vec<(usize, usize)> {
+    segments(text).into_iter()
+        .filter(|(kind, _, _)| *kind != SegmentKind::Other)
+        .map(|(_, start, end)| (start, end))
+        .collect()
+}
+
+/// The most plausible thing to look up in a selection: the whole of it
+/// when it's one word or a known expression, otherwise the longest word
+/// in it that `is_known`, otherwise its longest word
+pub fn lookup_token(selection: &str, is_known: impl Fn(&str) -> bool) -> Option<String> {
+    let selection: String = selection.chars().take(MAX_SCANNED_CHARS).collect();
+    let words = words(&selection, &is_known);
+    if words.len() <= 1 {
+        return words.into_iter().next();
+    }
+
+    // A phrase as selected, minus surrounding punctuation and extra spaces
+    let phrase = normalize(&selection)
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if is_known(&phrase) {
+        return Some(phrase);
+    }
+
+    let longest = |candidates: Vec<&String>| {
+        candidates.into_iter()
+            .rev()
+            .max_by_key(|word| word.chars().count())
+            .cloned()
+    };
+    longest(words.iter().filter(|word| is_known(word)).collect())
+        .or_else(|| longest(words.iter().collect()))
+}
+
+/// The sentence of `text` that `word` was taken from, for reading the
+/// word in context. `None` when the text is nothing but the word.
+pub fn sentence_around(text: &str, word: &str) -> Option<String> {
+    let text = normalize(text);
+    let word = word.to_lowercase();
+    let sentences: Vec<&str> = text.unicode_sentences().map(str::trim).collect();
+    // A sentence with the word in it, else one containing it ("give up", CJK)
+    let sentence = sentences.iter()
+        .find(|sentence| words(sentence, |_| false).iter().any(|w| w.to_lowercase() == word))
+        .or_else(|| sentences.iter().find(|sentence| sentence.to_lowercase().contains(&word)))?;
+    (sentence.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric()) != word).then(|| sentence.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN: &[&str] = &["naïve", "co-operate", "l'homme", "homme", "approach", "give up", "中文", "学习", "喜欢", "日本語", "勉強"];
+
+    fn known(word: &str) -> bool {
+        KNOWN.contains(&word.to_lowercase().as_str())
+    }
+
+    #[test]
+    fn test_normalize_and_words() {
+        assert_eq!(normalize("nai\u{308}ve"), "naïve");
+        assert_eq!(normalize("co\u{2011}operate"), "co-operate");
+        assert_eq!(normalize("l\u{2019}homme"), "l'homme");
+        assert_eq!(normalize("hy\u{ad}phen"), "hyphen");
+
+        assert_eq!(words("A nai\u{308}ve, co\u{2011}operative move!", known), vec!["A", "naïve", "co-operative", "move"]);
+        assert_eq!(words("l\u{2019}homme - well-being", known), vec!["l'homme", "well-being"]);
+        assert_eq!(words("我喜欢学习中文", known), vec!["我", "喜欢", "学习", "中文"]);
+        assert_eq!(word_ranges("She gave up, didn\u{2019}t she?"), vec![(0, 3), (4, 8), (9, 11), (13, 21), (22, 25)]);
+        assert_eq!(words("日本語を勉強する", known), vec!["日本語", "を", "勉強", "す", "る"]);
+    }
+
+    #[test]
+    fn test_lookup_token() {
+        assert_eq!(lookup_token("  nai\u{308}ve ", known).as_deref(), Some("naïve"));
+        assert_eq!(lookup_token("co\u{2011}operate", known).as_deref(), Some("co-operate"));
+        assert_eq!(lookup_token("“Give up!”", known).as_deref(), Some("Give up"));
+        assert_eq!(lookup_token("a naïve approach to this", known).as_deref(), Some("approach"));
+        assert_eq!(lookup_token("some unknown words", known).as_deref(), Some("unknown"));
+        assert_eq!(lookup_token("我喜欢学习中文", known).as_deref(), Some("喜欢"));
+        assert_eq!(lookup_token("日本語を勉強する", known).as_deref(), Some("日本語"));
+        assert_eq!(lookup_token("42 + 7", known), None);
+    }
+
+    #[test]
+    fn test_sentence_around() {
+        let text = "It rained. She gave up the \u{2019}naïve\u{2019} plan! Then left.";
+        assert_eq!(sentence_around(text, "naïve").as_deref(), Some("She gave up the 'naïve' plan!"));
+        assert_eq!(sentence_around(text, "the").as_deref(), Some("She gave up the 'naïve' plan!"));
+        assert_eq!(sentence_around(text, "Then").as_deref(), Some("Then left."));
+        assert_eq!(sentence_around("“Approach.”", "approach"), None);
+        assert_eq!(sentence_around(text, "absent"), None);
+    }
+}
@@ -0,0 +1,559 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::cache::Definition;
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::language::ENGLISH;
+use crate::settings::Settings;
+use crate::source::DictionarySource;
+
+const MAX_DEPTH: usize = 10;
+const POS_FILES: [(char, &str); 4] = [('n', "noun"), ('v', "verb"), ('a', "adj"), ('r', "adv")];
+
+/// Semantic and lexical relations, grouping WordNet's finer pointer kinds
+/// (e.g. part/member/substance meronyms are all `Meronym`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    Synonym,
+    Antonym,
+    Hypernym,
+    Hyponym,
+    Holonym,
+    Meronym,
+    Derivation,
+    SimilarTo,
+    Entailment,
+    Cause,
+    AlsoSee,
+    Attribute,
+    Pertainym,
+}
+
+impl Relation {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "!" => Some(Relation::Antonym),
+            "@" | "@i" => Some(Relation::Hypernym),
+            "~" | "~i" => Some(Relation::Hyponym),
+            "#m" | "#s" | "#p" => Some(Relation::Holonym),
+            "%m" | "%s" | "%p" => Some(Relation::Meronym),
+            "+" => Some(Relation::Derivation),
+            "&" => Some(Relation::SimilarTo),
+            "*" => Some(Relation::Entailment),
+            ">" => Some(Relation::Cause),
+            "^" => Some(Relation::AlsoSee),
+            "=" => Some(Relation::Attribute),
+            "\\" => Some(Relation::Pertainym),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SynsetId {
+    pos: char,
+    offset: u32,
+}
+
+impl SynsetId {
+    fn to_id_string(self) -> String {
+        format!("{}{:08}", self.pos, self.offset)
+    }
+}
+
+struct Pointer {
+    relation: Relation,
+    target: SynsetId,
+    // 1-based word numbers for lexical pointers; 0 means the whole synset
+    source_word: usize,
+    target_word: usize,
+}
+
+struct Synset {
+    pos: char,
+    words: Vec<String>,
+    gloss: String,
+    pointers: Vec<Pointer>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SynsetInfo {
+    pub id: String,
+    pub pos: String,
+    pub words: Vec<String>,
+    pub gloss: String,
+}
+
+/// One node reached by `related`, with the synset it was reached from
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedSynset {
+    pub id: String,
+    pub pos: String,
+    pub words: Vec<String>,
+    pub gloss: String,
+    pub depth: usize,
+    pub parent: Option<String>,
+}
+
+/// In-memory Princeton WordNet graph, loaded from the `data.*` and
+/// `index.*` files of a WordNet 3.x `dict` directory.
+pub struct WordNet {
+    synsets: HashMap<SynsetId, Synset>,
+    // Lowercased lemma (spaces, not underscores) to synsets in sense order
+    index: HashMap<String, Vec<SynsetId>>,
+    lemmas: Vec<String>,
+}
+
+impl WordNet {
+    pub fn load(dir: &Path) -> DictionaryResult<Self> {
+        let mut synsets = HashMap::new();
+        let mut index: HashMap<String, Vec<SynsetId>> = HashMap::new();
+
+        for (pos, name) in POS_FILES {
+            let data_path = dir.join(format!("data.{}", name));
+            if !data_path.exists() {
+                continue;
+            }
+            let mut loaded = Vec::new();
+            for line in read_lines(&data_path)? {
+                if let Some((offset, synset)) = parse_data_line(&line) {
+                    loaded.push(SynsetId { pos, offset });
+                    synsets.insert(SynsetId { pos, offset }, synset);
+                }
+            }
+
+            let index_path = dir.join(format!("index.{}", name));
+            if index_path.exists() {
+                for line in read_lines(&index_path)? {
+                    if let Some((lemma, offsets)) = parse_index_line(&line) {
+                        index.entry(lemma).or_default()
+                            .extend(offsets.into_iter().map(|offset| SynsetId { pos, offset }));
+                    }
+                }
+            } else {
+                // Without an index file, fall back to the synsets' own word lists
+                for id in loaded {
+                    for word in &synsets[&id].words {
+                        index.entry(word.to_lowercase()).or_default().push(id);
+                    }
+                }
+            }
+        }
+
+        if synsets.is_empty() {
+            return Err(wordnet_error(&format!("no WordNet data files in {}", dir.display())));
+        }
+
+        index.retain(|_, ids| {
+            ids.retain(|id| synsets.contains_key(id));
+            !ids.is_empty()
+        });
+
+        let mut lemmas: Vec<String> = index.keys().cloned().collect();
+        lemmas.sort();
+
+        Ok(Self { synsets, index, lemmas })
+    }
+
+    fn info(&self, id: SynsetId) -> Option<SynsetInfo> {
+        let synset = self.synsets.get(&id)?;
+        Some(SynsetInfo {
+            id: id.to_id_string(),
+            pos: synset.pos.to_string(),
+            words: synset.words.clone(),
+            gloss: synset.gloss.clone(),
+        })
+    }
+
+    fn lookup_ids(&self, word: &str, pos: Option<char>) -> Vec<SynsetId> {
+        let key = word.trim().to_lowercase().replace('_', " ");
+        self.index.get(&key)
+            .map(|ids| ids.iter().copied().filter(|id| pos.is_none() || Some(id.pos) == pos).collect())
+            .unwrap_or_default()
+    }
+
+    /// Every sense of `word`, most frequent first
+    pub fn synsets(&self, word: &str) -> Vec<SynsetInfo> {
+        self.lookup_ids(word, None).into_iter().filter_map(|id| self.info(id)).collect()
+    }
+
+    /// Breadth-first walk along `relation` from each sense of `word`, up to
+    /// `depth` hops. Lexical pointers (antonyms, derivations) are only followed
+    /// from the queried word itself, not from its synonyms.
+    pub fn related(&self, word: &str, relation: Relation, depth: usize, pos: Option<char>) -> Vec<RelatedSynset> {
+        let starts = self.lookup_ids(word, pos);
+        let word = word.trim().replace('_', " ");
+
+        if relation == Relation::Synonym {
+            return starts.into_iter()
+                .filter_map(|id| {
+                    let info = self.info(id)?;
+                    let words: Vec<String> = info.words.into_iter().filter(|w| !w.eq_ignore_ascii_case(&word)).collect();
+                    (!words.is_empty()).then_some(RelatedSynset {
+                        id: info.id,
+                        pos: info.pos,
+                        words,
+                        gloss: info.gloss,
+                        depth: 1,
+                        parent: None,
+                    })
+                })
+                .collect();
+        }
+
+        let max_depth = depth.clamp(1, MAX_DEPTH);
+        let mut visited: HashSet<SynsetId> = starts.iter().copied().collect();
+        let mut queue: VecDeque<(SynsetId, usize)> = starts.into_iter().map(|id| (id, 0)).collect();
+        let mut results = Vec::new();
+
+        while let Some((id, current_depth)) = queue.pop_front() {
+            let Some(synset) = self.synsets.get(&id) else { continue };
+
+            for pointer in synset.pointers.iter().filter(|p| p.relation == relation) {
+                if pointer.source_word != 0 && current_depth == 0 {
+                    let source = synset.words.get(pointer.source_word - 1);
+                    if !matches!(source, Some(s) if s.eq_ignore_ascii_case(&word)) {
+                        continue;
+                    }
+                }
+                if !visited.insert(pointer.target) {
+                    continue;
+                }
+                let Some(target) = self.synsets.get(&pointer.target) else { continue };
+
+                let words = match target.words.get(pointer.target_word.wrapping_sub(1)) {
+                    Some(target_word) if pointer.target_word != 0 => vec![target_word.clone()],
+                    _ => target.words.clone(),
+                };
+                results.push(RelatedSynset {
+                    id: pointer.target.to_id_string(),
+                    pos: target.pos.to_string(),
+                    words,
+                    gloss: target.gloss.clone(),
+                    depth: current_depth + 1,
+                    parent: Some(id.to_id_string()),
+                });
+
+                if current_depth + 1 < max_depth {
+                    queue.push_back((pointer.target, current_depth + 1));
+                }
+            }
+        }
+
+        results
+    }
+}
+
+impl DictionarySource for WordNet {
+    fn name(&self) -> &str {
+        "wordnet"
+    }
+
+    fn language(&self) -> Option<&str> {
+        Some(ENGLISH)
+    }
+
+    fn lookup(&self, word: &str) -> DictionaryResult<Option<Definition>> {
+        let ids = self.lookup_ids(word, None);
+        let Some(first) = ids.first().and_then(|id| self.synsets.get(id)) else { return Ok(None) };
+
+        let headword = first.words.iter()
+            .find(|w| w.eq_ignore_ascii_case(word.trim()))
+            .cloned()
+            .unwrap_or_else(|| word.trim().to_string());
+
+        // Senses in another part of speech say which one they are
+        let definitions = ids.iter()
+            .filter_map(|id| self.synsets.get(id))
+            .map(|s| if s.pos == first.pos { s.gloss.clone() } else { format!("({}) {}", s.pos, s.gloss) })
+            .collect();
+
+        Ok(Some(Definition {
+            word: headword,
+            pronunciation: None,
+            pos: first.pos.to_string(),
+            definitions,
+            frequency: None,
+            inflection: None,
+            language: Some(ENGLISH.to_string()),
+        }))
+    }
+
+    fn search(&self, prefix: &str, limit: usize) -> DictionaryResult<Vec<String>> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let start = self.lemmas.partition_point(|l| l.as_str() < prefix.as_str());
+        Ok(self.lemmas[start..].iter()
+            .take_while(|l| l.starts_with(&prefix))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn headwords(&self, visit: &mut dyn FnMut(&str, Option<u32>)) -> DictionaryResult<bool> {
+        for lemma in &self.lemmas {
+            visit(lemma, None);
+        }
+        Ok(true)
+    }
+
+    // Every word of a synset shares its gloss, so synonyms all match
+    fn documents(&self, visit: &mut dyn FnMut(&str, &[String])) -> DictionaryResult<bool> {
+        for synset in self.synsets.values() {
+            let gloss = [synset.gloss.clone()];
+            for word in &synset.words {
+                visit(word, &gloss);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn wordnet_error(message: &str) -> DictionaryError {
+    DictionaryError::SourceError {
+        source: "wordnet".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Lines of a WordNet file, minus the indented license header
+fn read_lines(path: &Path) -> DictionaryResult<Vec<String>> {
+    let file = File::open(path).map_err(|e| wordnet_error(&format!("{}: {}", path.display(), e)))?;
+    let mut reader = BufReader::new(file);
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => break,
+            // The files predate UTF-8; a stray Latin-1 byte shouldn't abort the load
+            Ok(_) if !buffer.starts_with(b"  ") => lines.push(String::from_utf8_lossy(&buffer).trim_end().to_string()),
+            Ok(_) => {}
+            Err(e) => return Err(wordnet_error(&format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Lemmas are stored with underscores, and adjectives may carry a
+/// syntactic marker such as `(a)` or `(ip)`
+fn clean_lemma(raw: &str) -> String {
+    let lemma = match raw.find('(') {
+        Some(i) if raw.ends_with(')') => &raw[..i],
+        _ => raw,
+    };
+    lemma.replace('_', " ")
+}
+
+/// `offset lex_filenum ss_type w_cnt (word lex_id)* p_cnt (symbol offset pos src/tgt)* [frames] | gloss`
+fn parse_data_line(line: &str) -> Option<(u32, Synset)> {
+    let (fields, gloss) = line.split_once(" | ").unwrap_or((line, ""));
+    let tokens: Vec<&str> = fields.split_whitespace().collect();
+
+    let offset = tokens.first()?.parse().ok()?;
+    let pos = match tokens.get(2)?.chars().next()? {
+        's' => 'a',
+        c => c,
+    };
+    let word_count = usize::from_str_radix(tokens.get(3)?, 16).ok()?;
+    let words = (0..word_count)
+        .map(|i| tokens.get(4 + i * 2).map(|w| clean_lemma(w)))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut cursor = 4 + word_count * 2;
+    let pointer_count: usize = tokens.get(cursor)?.parse().ok()?;
+    cursor += 1;
+
+    let mut pointers = Vec::new();
+    for _ in 0..pointer_count {
+        let (symbol, target, target_pos, source_target) = (
+            tokens.get(cursor)?,
+            tokens.get(cursor + 1)?,
+            tokens.get(cursor + 2)?,
+            tokens.get(cursor + 3)?,
+        );
+        cursor += 4;
+
+        let Some(relation) = Relation::from_symbol(symbol) else { continue };
+        let target_pos = match target_pos.chars().next()? {
+            's' => 'a',
+            c => c,
+        };
+        let source_target = u16::from_str_radix(source_target, 16).ok()?;
+        pointers.push(Pointer {
+            relation,
+            target: SynsetId { pos: target_pos, offset: target.parse().ok()? },
+            source_word: (source_target >> 8) as usize,
+            target_word: (source_target & 0xff) as usize,
+        });
+    }
+
+    Some((offset, Synset {
+        pos,
+        words,
+        gloss: gloss.trim().to_string(),
+        pointers,
+    }))
+}
+
+/// `lemma pos synset_cnt p_cnt (symbol)* sense_cnt tagsense_cnt (offset)*`
+fn parse_index_line(line: &str) -> Option<(String, Vec<u32>)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let lemma = clean_lemma(tokens.first()?);
+    let synset_count: usize = tokens.get(2)?.parse().ok()?;
+    let pointer_count: usize = tokens.get(3)?.parse().ok()?;
+    let offsets_start = 4 + pointer_count + 2;
+
+    let offsets = tokens.get(offsets_start..offsets_start + synset_count)?
+        .iter()
+        .map(|t| t.parse().ok())
+        .collect::<Option<Vec<u32>>>()?;
+
+    Some((lemma.to_lowercase(), offsets))
+}
+
+fn wordnet_dir<R: Runtime>(handle: &AppHandle<R>, settings: &Settings) -> Option<PathBuf> {
+    settings.sources.wordnet_path.clone()
+        .map(PathBuf::from)
+        .or_else(|| handle.path().app_data_dir().ok().map(|dir| dir.join("dictionaries").join("wordnet")))
+}
+
+/// Parsing takes a second or so, so load off the setup thread; the
+/// graph commands report "not loaded" until it's managed.
+pub fn load_in_background<R: Runtime>(handle: AppHandle<R>, dictionary_service: Arc<DictionaryService>, settings: &Settings) {
+    let Some(dir) = wordnet_dir(&handle, settings).filter(|d| d.is_dir()) else { return };
+
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        match WordNet::load(&dir) {
+            Ok(wordnet) => {
+                println!("Loaded WordNet ({} synsets) in {:?}", wordnet.synsets.len(), start.elapsed());
+                let wordnet = Arc::new(wordnet);
+                handle.manage(wordnet.clone());
+                dictionary_service.add_source(wordnet);
+            }
+            Err(e) => e.log_error(),
+        }
+    });
+}
+
+fn loaded_wordnet(app_handle: &AppHandle) -> Result<Arc<WordNet>, String> {
+    app_handle.try_state::<Arc<WordNet>>()
+        .map(|state| state.inner().clone())
+        .ok_or_else(|| "WordNet is not loaded".to_string())
+}
+
+/// Tauri command to list a word's WordNet senses
+#[tauri::command]
+pub fn wordnet_synsets(word: String, app_handle: AppHandle) -> Result<Vec<SynsetInfo>, String> {
+    Ok(loaded_wordnet(&app_handle)?.synsets(&word))
+}
+
+/// Tauri command for relation navigation, e.g. broader terms are
+/// `relation: "hypernym"` and narrower terms `relation: "hyponym"`
+#[tauri::command]
+pub fn wordnet_related(
+    word: String,
+    relation: Relation,
+    depth: Option<usize>,
+    pos: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Vec<RelatedSynset>, String> {
+    let pos = pos.and_then(|p| p.chars().next());
+    Ok(loaded_wordnet(&app_handle)?.related(&word, relation, depth.unwrap_or(1), pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA_NOUN: &str = "  1 This software and database is being provided
+00001740 03 n 01 entity 0 001 ~ 00002000 n 0000 | that which is perceived to exist
+00002000 03 n 01 animal 0 003 @ 00001740 n 0000 ~ 00003000 n 0000 ~ 00004000 n 0000 | a living organism
+00003000 05 n 02 cat 0 true_cat 0 003 @ 00002000 n 0000 %p 00005000 n 0000 + 00006000 v 0101 | feline mammal; \"the cat purred\"
+00004000 05 n 01 dog 0 001 @ 00002000 n 0000 | a domesticated canid
+00005000 05 n 01 whisker 0 001 #p 00003000 n 0000 | a stiff hair on the face
+";
+    const INDEX_NOUN: &str = "  1 This software and database is being provided
+entity n 1 1 ~ 1 0 00001740
+animal n 1 2 @ ~ 1 0 00002000
+cat n 1 3 @ %p + 1 0 00003000
+true_cat n 1 1 @ 1 0 00003000
+dog n 1 1 @ 1 0 00004000
+whisker n 1 1 #p 1 0 00005000
+";
+    const DATA_VERB: &str = "00006000 38 v 01 cat 0 001 + 00003000 n 0101 01 + 02 00 | to vomit
+";
+    const INDEX_VERB: &str = "cat v 1 1 + 1 0 00006000
+";
+    const DATA_ADJ: &str = "00007000 00 a 01 hot(a) 0 001 ! 00008000 a 0101 | high in temperature
+00008000 00 s 01 cold 0 001 ! 00007000 a 0101 | low in temperature
+";
+
+    fn write_test_wordnet(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in [
+            ("data.noun", DATA_NOUN),
+            ("index.noun", INDEX_NOUN),
+            ("data.verb", DATA_VERB),
+            ("index.verb", INDEX_VERB),
+            ("data.adj", DATA_ADJ),
+        ] {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_relation_navigation() {
+        let dir = write_test_wordnet("wordnet-relations");
+        let wordnet = WordNet::load(&dir).unwrap();
+
+        let broader = wordnet.related("cat", Relation::Hypernym, 2, None);
+        assert_eq!(broader.iter().map(|r| (r.words[0].as_str(), r.depth)).collect::<Vec<_>>(), vec![("animal", 1), ("entity", 2)]);
+        assert_eq!(broader[1].parent.as_deref(), Some("n00002000"));
+
+        let narrower = wordnet.related("animal", Relation::Hyponym, 1, None);
+        assert_eq!(narrower.len(), 2);
+
+        assert_eq!(wordnet.related("cat", Relation::Meronym, 1, None)[0].words, vec!["whisker"]);
+        assert_eq!(wordnet.related("cat", Relation::Synonym, 1, None)[0].words, vec!["true cat"]);
+
+        // Lexical pointers only apply to the word they're attached to
+        assert_eq!(wordnet.related("cat", Relation::Derivation, 1, Some('n'))[0].pos, "v");
+        assert!(wordnet.related("true cat", Relation::Derivation, 1, None).is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_source_lookup_and_index_fallback() {
+        let dir = write_test_wordnet("wordnet-source");
+        let wordnet = WordNet::load(&dir).unwrap();
+
+        let definition = wordnet.lookup("Cat").unwrap().unwrap();
+        assert_eq!(definition.pos, "n");
+        assert_eq!(definition.definitions, vec!["feline mammal; \"the cat purred\"", "(v) to vomit"]);
+        assert_eq!(wordnet.search("ca", 10).unwrap(), vec!["cat"]);
+
+        // No index.adj: adjectives come from the data file, markers stripped
+        assert_eq!(wordnet.related("hot", Relation::Antonym, 1, None)[0].words, vec!["cold"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,474 @@
+use serde::Deserialize;
+use std::io::{self, BufRead, Read};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use tauri::{AppHandle, State};
+
+use crate::dictionary::DictionaryService;
+use crate::error::{DictionaryError, DictionaryResult};
+use crate::local_store::{spawn_import, ImportProgress, LocalStore, StoreEntry, WordForm};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// Give up on a file that is broken throughout rather than logging forever
+const MAX_XML_ERRORS: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum XmlFormat {
+    Xdxf,
+    Tei,
+}
+
+impl XmlFormat {
+    fn from_root(name: &[u8]) -> Option<Self> {
+        match name {
+            b"xdxf" => Some(XmlFormat::Xdxf),
+            b"TEI" | b"teiCorpus" => Some(XmlFormat::Tei),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            XmlFormat::Xdxf => "xdxf",
+            XmlFormat::Tei => "tei",
+        }
+    }
+
+    fn entry_element(&self) -> &'static [u8] {
+        match self {
+            XmlFormat::Xdxf => b"ar",
+            XmlFormat::Tei => b"entry",
+        }
+    }
+
+    /// What text inside this element means, or `None` to inherit the parent's
+    fn field(&self, element: &BytesStart, parent_form_type: Option<&str>, has_headword: bool) -> Option<Field> {
+        let name = element.local_name();
+        match (self, name.as_ref()) {
+            // Visual-format XDXF puts definition text straight into <ar>
+            (XmlFormat::Xdxf, b"ar") => Some(Field::Definition),
+            (XmlFormat::Xdxf, b"k") => Some(Field::Headword),
+            (XmlFormat::Xdxf, b"tr") => Some(Field::Pronunciation),
+            (XmlFormat::Xdxf, b"gr") => Some(Field::Grammar),
+            (XmlFormat::Xdxf, b"def" | b"deftext" | b"dtrn") => Some(Field::Definition),
+            (XmlFormat::Xdxf, b"ex") => Some(Field::Example),
+            (XmlFormat::Xdxf, b"kref") => Some(Field::CrossReference),
+            (XmlFormat::Xdxf, b"etm") => Some(Field::Etymology),
+            (XmlFormat::Xdxf, b"rref") => Some(Field::Ignore),
+
+            (XmlFormat::Tei, b"entry" | b"form" | b"usg" | b"lbl" | b"note" | b"bibl") => Some(Field::Ignore),
+            (XmlFormat::Tei, b"orth") => match parent_form_type {
+                Some("lemma") | None if !has_headword => Some(Field::Headword),
+                Some(form_type) => Some(Field::Form(form_type.to_string())),
+                None => Some(Field::Form("variant".to_string())),
+            },
+            (XmlFormat::Tei, b"pron") => Some(Field::Pronunciation),
+            (XmlFormat::Tei, b"pos" | b"gen" | b"number" | b"case" | b"per" | b"tns" | b"mood" | b"gram" | b"subc" | b"iType") => {
+                Some(Field::Grammar)
+            }
+            (XmlFormat::Tei, b"sense" | b"def") => Some(Field::Definition),
+            (XmlFormat::Tei, b"cit") => match attribute(element, "type").as_deref() {
+                Some("translation" | "translationEquivalent") => Some(Field::Definition),
+                _ => Some(Field::Example),
+            },
+            (XmlFormat::Tei, b"xr") => Some(Field::CrossReference),
+            (XmlFormat::Tei, b"etym") => Some(Field::Etymology),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Headword,
+    Form(String),
+    Pronunciation,
+    Grammar,
+    Definition,
+    Example,
+    CrossReference,
+    Etymology,
+    Ignore,
+}
+
+/// An open element inside the current entry. Elements that set a field
+/// collect their own text; the rest pass text up to the nearest one that does.
+struct Frame {
+    name: Vec<u8>,
+    field: Option<Field>,
+    form_type: Option<String>,
+    text: String,
+}
+
+struct EntryBuilder {
+    entry: StoreEntry,
+    grammar: Vec<String>,
+    line: u64,
+    stack: Vec<Frame>,
+}
+
+impl EntryBuilder {
+    fn new(line: u64) -> Self {
+        Self {
+            entry: StoreEntry::default(),
+            grammar: Vec::new(),
+            line,
+            stack: Vec::new(),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(frame) = self.stack.iter_mut().rev().find(|f| f.field.is_some()) {
+            frame.text.push_str(text);
+        }
+    }
+
+    fn commit(&mut self, field: Field, text: String) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return;
+        }
+
+        let entry = &mut self.entry;
+        match field {
+            Field::Headword if entry.word.is_empty() => entry.word = text,
+            Field::Headword => entry.forms.push(WordForm { form: text, tags: vec!["variant".to_string()] }),
+            Field::Form(tag) => entry.forms.push(WordForm { form: text, tags: vec![tag] }),
+            Field::Pronunciation => {
+                if entry.pronunciation.is_none() {
+                    entry.pronunciation = Some(text);
+                }
+            }
+            Field::Grammar => self.grammar.push(text),
+            Field::Definition => entry.definitions.push(text),
+            Field::Example => entry.examples.push(text),
+            Field::CrossReference => entry.cross_references.push(text),
+            Field::Etymology => entry.etymology = Some(text),
+            Field::Ignore => {}
+        }
+    }
+
+    fn finish(mut self) -> Result<StoreEntry, (u64, String)> {
+        if self.entry.word.is_empty() {
+            return Err((self.line, "entry has no headword".to_string()));
+        }
+        if self.entry.definitions.is_empty() {
+            // Pure cross-reference entries still deserve a line in the popup
+            if self.entry.cross_references.is_empty() {
+                return Err((self.line, format!("entry '{}' has no definitions", self.entry.word)));
+            }
+            self.entry.definitions.push(format!("See: {}", self.entry.cross_references.join(", ")));
+        }
+        self.entry.pos = self.grammar.join(", ");
+        Ok(self.entry)
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element.try_get_attribute(name).ok().flatten()
+        .and_then(|a| a.unescape_value().ok().map(|v| v.to_string()))
+}
+
+/// Tracks the current line for error reports. quick-xml reads through
+/// `fill_buf`/`consume`, so counting newlines in consumed bytes is exact.
+struct LineCounter<B> {
+    inner: B,
+    newlines: u64,
+}
+
+impl<B: BufRead> LineCounter<B> {
+    fn line(&self) -> u64 {
+        self.newlines + 1
+    }
+}
+
+impl<B: BufRead> Read for LineCounter<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.newlines += buf[..n].iter().filter(|b| **b == b'\n').count() as u64;
+        Ok(n)
+    }
+}
+
+impl<B: BufRead> BufRead for LineCounter<B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The bytes being consumed are still at the front of the buffer
+        if let Ok(buffer) = self.inner.fill_buf() {
+            let end = amt.min(buffer.len());
+            self.newlines += buffer[..end].iter().filter(|b| **b == b'\n').count() as u64;
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Stream an XDXF or TEI Lex-0 file into the store. With no `format`, the
+/// root element decides. Malformed entries are reported with their line
+/// number and skipped; only I/O and store errors abort the import.
+pub fn import_xml<B: BufRead>(
+    input: B,
+    store: &mut LocalStore,
+    format: Option<XmlFormat>,
+    origin: &str,
+    bytes_read: &AtomicU64,
+    progress: &mut ImportProgress,
+    mut on_progress: impl FnMut(&ImportProgress),
+) -> DictionaryResult<()> {
+    let mut reader = Reader::from_reader(LineCounter { inner: input, newlines: 0 });
+    // End tags are checked per entry below, so one bad entry doesn't derail the rest
+    reader.expand_empty_elements(true).check_end_names(false);
+
+    let mut format = format;
+    // Dropped without `finish` (e.g. not XDXF or TEI after all), this rolls back
+    let mut import = store.import(origin)?;
+    let mut entry: Option<EntryBuilder> = None;
+    let mut buffer = Vec::new();
+    let mut xml_errors = 0u64;
+    let mut last_error_position = None;
+    let mut last_report = Instant::now();
+
+    loop {
+        buffer.clear();
+        let event = match reader.read_event_into(&mut buffer) {
+            Ok(event) => event,
+            Err(quick_xml::Error::Io(e)) => {
+                return Err(DictionaryError::SourceError {
+                    source: origin.to_string(),
+                    message: format!("read failed at line {}: {}", reader.get_ref().line(), e),
+                });
+            }
+            Err(e) => {
+                let line = reader.get_ref().line();
+                // Drop whatever entry was open; resume at the next one
+                let line = entry.take().map(|builder| builder.line).unwrap_or(line);
+                progress.report_issue(line, format!("malformed XML: {}", e));
+
+                xml_errors += 1;
+                let position = reader.buffer_position();
+                if xml_errors >= MAX_XML_ERRORS || last_error_position == Some(position) {
+                    break;
+                }
+                last_error_position = Some(position);
+                continue;
+            }
+        };
+
+        match event {
+            Event::Start(element) => {
+                let Some(current_format) = format.or_else(|| XmlFormat::from_root(element.local_name().as_ref())) else {
+                    return Err(DictionaryError::InvalidInput {
+                        message: "Not an XDXF or TEI document".to_string(),
+                    });
+                };
+                format = Some(current_format);
+
+                let name = element.local_name().as_ref().to_vec();
+                if entry.is_none() && name == current_format.entry_element() {
+                    entry = Some(EntryBuilder::new(reader.get_ref().line()));
+                }
+
+                if let Some(builder) = entry.as_mut() {
+                    // Example translations read better set apart from the original
+                    if name == b"ex_tran" {
+                        builder.text(" — ");
+                    }
+
+                    let parent_form_type = builder.stack.last().and_then(|f| f.form_type.clone());
+                    let field = current_format.field(&element, parent_form_type.as_deref(), !builder.entry.word.is_empty());
+                    let form_type = match name.as_slice() {
+                        b"form" => Some(attribute(&element, "type").unwrap_or_else(|| "lemma".to_string())),
+                        _ => parent_form_type,
+                    };
+                    builder.stack.push(Frame { name, field, form_type, text: String::new() });
+                }
+            }
+            Event::End(end) => {
+                let Some(builder) = entry.as_mut() else { continue };
+                let Some(frame) = builder.stack.pop() else { continue };
+
+                if end.local_name().as_ref() != frame.name.as_slice() {
+                    let message = format!(
+                        "malformed XML: expected </{}>, found </{}>",
+                        String::from_utf8_lossy(&frame.name),
+                        String::from_utf8_lossy(end.local_name().as_ref()),
+                    );
+                    progress.report_issue(builder.line, message);
+                    entry = None;
+                    continue;
+                }
+
+                if let Some(field) = frame.field {
+                    builder.commit(field, frame.text);
+                } else if !frame.text.is_empty() {
+                    builder.text(&frame.text);
+                }
+
+                if builder.stack.is_empty() {
+                    match entry.take().map(EntryBuilder::finish) {
+                        Some(Ok(store_entry)) => {
+                            import.insert(&store_entry)?;
+                            progress.entries_imported += 1;
+                        }
+                        Some(Err((line, message))) => progress.report_issue(line, message),
+                        None => {}
+                    }
+                }
+            }
+            Event::Text(text) => {
+                if let Some(builder) = entry.as_mut() {
+                    // Entities from an external DTD (e.g. &nbsp;) can't be resolved; keep them raw
+                    let text = text.unescape()
+                        .map(|t| t.to_string())
+                        .unwrap_or_else(|_| String::from_utf8_lossy(&text).to_string());
+                    builder.text(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(builder) = entry.as_mut() {
+                    builder.text(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+            on_progress(progress);
+            last_report = Instant::now();
+        }
+    }
+
+    if let Some(builder) = entry {
+        progress.report_issue(builder.line, "entry not closed before end of file".to_string());
+    }
+    import.finish()?;
+    progress.bytes_read = bytes_read.load(Ordering::Relaxed);
+    Ok(())
+}
+
+/// Tauri command to import an XDXF or TEI Lex-0 dictionary (optionally
+/// gzipped). Reimporting the same file replaces its earlier entries.
+#[tauri::command]
+pub fn import_xml_dictionary(
+    path: String,
+    format: Option<XmlFormat>,
+    app_handle: AppHandle,
+    dictionary_service: State<'_, Arc<DictionaryService>>,
+) -> Result<(), String> {
+    let source_path = PathBuf::from(&path);
+    let stem = source_path.file_name()
+        .map(|n| n.to_string_lossy().split('.').next().unwrap_or_default().to_string())
+        .unwrap_or_default();
+    let label = format.map(|f| f.as_str()).unwrap_or("xml");
+    let origin = format!("{}:{}", label, stem);
+
+    spawn_import(app_handle, dictionary_service.inner().clone(), source_path, label,
+        move |reader, bytes_read, store, progress, on_progress| {
+            import_xml(reader, store, format, &origin, bytes_read, progress, on_progress)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XDXF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xdxf lang_from="ENG" lang_to="ENG" format="logical" revision="033">
+<meta_info><title>Test</title></meta_info>
+<lexicon>
+<ar><k>apple</k><k>aple</k>
+  <def><gr><abbr>n</abbr></gr><tr>ˈæpl</tr>
+    <def><deftext>A round <i>fresh</i> fruit.</deftext>
+      <ex><ex_orig>an apple a day</ex_orig><ex_tran>keeps the doctor away</ex_tran></ex></def>
+    <def><deftext>The tree bearing it.</deftext></def>
+  </def>
+  <kref>pear</kref>
+</ar>
+<ar><def>orphan text</def></ar>
+<ar><k>broken</k><def>missing end
+</ar>
+<ar><k>pomme</k><kref>apple</kref></ar>
+</lexicon>
+</xdxf>
+"#;
+
+    const TEI: &str = r##"<TEI xmlns="http://www.tei-c.org/ns/1.0">
+<teiHeader><fileDesc><titleStmt><title>Test</title></titleStmt></fileDesc></teiHeader>
+<text><body>
+<entry xml:id="cat">
+  <form type="lemma"><orth>cat</orth><pron>kæt</pron></form>
+  <form type="inflected"><orth>cats</orth></form>
+  <gramGrp><pos norm="noun">n.</pos><gen>neut</gen></gramGrp>
+  <sense><usg type="register">informal</usg><def>A small feline.</def>
+    <cit type="example"><quote>The cat sat.</quote></cit>
+    <xr type="synonym"><ref target="#feline">feline</ref></xr>
+  </sense>
+  <etym>From Latin <mentioned>cattus</mentioned>.</etym>
+</entry>
+</body></text>
+</TEI>
+"##;
+
+    fn test_store(name: &str) -> (PathBuf, LocalStore) {
+        let path = std::env::temp_dir().join(format!("{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = LocalStore::open(&path).unwrap();
+        (path, store)
+    }
+
+    #[test]
+    fn test_xdxf_import_reports_bad_entries() {
+        let (path, mut store) = test_store("xdxf-import");
+        let mut progress = ImportProgress::default();
+        import_xml(XDXF.as_bytes(), &mut store, None, "xdxf:test", &AtomicU64::new(0), &mut progress, |_| {}).unwrap();
+
+        assert_eq!(progress.entries_imported, 2);
+        assert_eq!(progress.issues.iter().map(|i| i.line).collect::<Vec<_>>(), vec![13, 14]);
+        assert!(progress.issues[0].message.contains("no headword"));
+        assert!(progress.issues[1].message.contains("malformed XML"));
+
+        let apple = &store.details("apple").unwrap()[0];
+        assert_eq!(apple.pos, "n");
+        assert_eq!(apple.pronunciation.as_deref(), Some("ˈæpl"));
+        assert_eq!(apple.definitions, vec!["A round fresh fruit.", "The tree bearing it."]);
+        assert_eq!(apple.examples, vec!["an apple a day — keeps the doctor away"]);
+        assert_eq!(apple.cross_references, vec!["pear"]);
+        assert_eq!(apple.forms[0].form, "aple");
+
+        assert_eq!(store.details("pomme").unwrap()[0].definitions, vec!["See: apple"]);
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tei_import() {
+        let (path, mut store) = test_store("tei-import");
+        let mut progress = ImportProgress::default();
+        import_xml(TEI.as_bytes(), &mut store, None, "tei:test", &AtomicU64::new(0), &mut progress, |_| {}).unwrap();
+
+        assert_eq!(progress.entries_imported, 1);
+        assert!(progress.issues.is_empty());
+
+        let cat = &store.details("cat").unwrap()[0];
+        assert_eq!(cat.pos, "n., neut");
+        assert_eq!(cat.pronunciation.as_deref(), Some("kæt"));
+        assert_eq!(cat.forms, vec![WordForm { form: "cats".to_string(), tags: vec!["inflected".to_string()] }]);
+        assert_eq!(cat.definitions, vec!["A small feline."]);
+        assert_eq!(cat.examples, vec!["The cat sat."]);
+        assert_eq!(cat.cross_references, vec!["feline"]);
+        assert_eq!(cat.etymology.as_deref(), Some("From Latin cattus."));
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+}